@@ -0,0 +1,155 @@
+# English response templates. Values may reference arguments as { $name }.
+# Failure messages start with "Error:" or "Failed" so macros can detect them.
+
+unknown-command = Sorry, I didn’t get you.
+language-set = Language set to { $language }.
+language-unknown = Error: Unsupported language '{ $language }'. Available: { $available }.
+languages-available = Available languages: { $available }. Current: { $current }.
+
+hello = Hello!
+who-created-you = Sutharsan and Nandhana.
+today-is = Today is { $day }
+todays-date-is = Today's date is { $date }
+
+calendar-unknown-month = Error: I don't know which month '{ $month }' is.
+calendar-out-of-range = Error: { $year } is out of range.
+calendar-intro = Here is the calendar for { $month } { $year }
+calendar-weekdays-sunday-first = Su Mo Tu We Th Fr Sa
+calendar-weekdays-monday-first = Mo Tu We Th Fr Sa Su
+calendar-has-events = (+ has events)
+
+home-directory = Home directory: { $path }
+home-directory-failed = Failed to change to home directory.
+root-directory = You are now in the root directory.
+root-directory-failed = Failed to change to root directory.
+files-listed = { $count } items in { $place }.
+folders-listed = { $count } folders in { $place }.
+files-listed-one = 1 item in { $place }.
+folders-listed-one = 1 folder in { $place }.
+files-none = Nothing in { $place } matches.
+read-directory-failed = Failed to read directory.
+my-directory = You are now in your personal directory: { $path }
+my-directory-create-failed = Failed to create your personal directory.
+my-directory-failed = Failed to navigate to your personal directory.
+current-directory = You are in { $path }
+current-directory-failed = Failed to get current directory.
+
+disk-usage-line = Disk { $mount }: Total: { $total }, Used: { $used }, Free: { $free }
+disk-usage-intro = Here is the disk usage report.
+memory-usage = Memory: Total: { $total } MB, Used: { $used } MB, Free: { $free } MB
+memory-usage-intro = Here is the memory usage report.
+free-memory = Free Memory: { $free } MB
+swap-memory = Swap Memory: { $swap } MB
+cpu-total = CPU total: { $usage }%
+cpu-usage-intro = Here is the CPU usage report.
+process-list-intro = Here is the list of running processes.
+uptime = Uptime: { $hours }h { $minutes }m { $seconds }s
+
+help-header = Available commands:
+help-intro = Here are the available commands.
+
+file-name-missing = Error: Please provide a file name.
+valid-file-name-missing = Error: Please provide a valid file name.
+opening = Opening { $name }
+open-failed = Failed to open file: { $error }
+file-created = File '{ $name }' created successfully.
+file-create-failed = Failed to create file '{ $name }': { $error }
+file-deleted = File '{ $name }' deleted successfully.
+file-delete-failed = Failed to delete file '{ $name }': { $error }
+move-args-missing = Error: Please provide source and destination.
+move-source-missing = Error: Source '{ $source }' does not exist.
+moved = Moved '{ $source }' to '{ $destination }'.
+move-failed = Failed to move '{ $source }': { $error }
+rename-args-missing = Error: Please provide both old and new file names.
+renamed = Renamed: { $old } -> { $new }
+rename-failed = Failed to rename: { $error }
+copy-done = Copied { $count } files, { $size }, to '{ $destination }'.
+copy-done-one = Copied 1 file, { $size }, to '{ $destination }'.
+copy-skipped = Skipped { $count } that already existed.
+copy-failed = { $count } could not be copied.
+copy-all-failed = Failed to copy '{ $name }': { $error }
+file-read-failed = Failed to open file '{ $name }': { $error }
+search-found = Found: { $path }
+search-not-found = '{ $name }' not found.
+search-found-many = Found { $count } matches for '{ $name }'. The best is { $path }
+
+shutdown-confirm = Are you sure you want to shut down the PC? (Assuming yes for now)
+shutting-down = Shutting down the PC.
+shutdown-done = Shutdown command executed.
+shutdown-failed = Failed to execute shutdown: { $error }
+restarting = Restarting the system.
+restart-done = Restart command executed.
+restart-failed = Failed to restart: { $error }
+logging-out = Logging out.
+logout-done = Logout command executed.
+logout-failed = Failed to log out: { $error }
+hibernating = Hibernating system.
+hibernate-done = Hibernate command executed.
+hibernate-failed = Failed to hibernate: { $error }
+sleeping = Putting system to sleep.
+sleep-done = Sleep command executed.
+sleep-failed = Failed to sleep: { $error }
+
+volume-up = Volume increased.
+volume-up-failed = Failed to increase volume: { $error }
+volume-down = Volume decreased.
+volume-down-failed = Failed to decrease volume: { $error }
+volume-unsupported = Volume control not supported on this OS yet.
+
+compile-file-missing = Error: Please provide a file name to compile.
+unsupported-format = Error: Unsupported file format.
+compile-ok = Compilation successful. Executable: { $name }
+compile-failed = Error: Compilation failed: { $error }
+compile-error = Failed to compile: { $error }
+run-output = Output:
+run-failed = Error: Execution failed: { $error }
+run-error = Error running the program: { $error }
+
+symlink-args-missing = Error: Please provide source and target (e.g., 'source target').
+symlink-created = Created symlink from { $source } to { $target }
+symlink-failed = Failed to create symlink: { $error }
+navigate-unsupported = Error: Interactive navigation not supported via API yet.
+history-shown = Command history displayed.
+history-missing = No command history found.
+goodbye = Goodbye!
+
+macros-unreadable = Error: { $path } could not be read: { $error }
+macro-too-deep = Error: Macro '{ $name }' nests too deeply.
+macro-stopped = Macro '{ $name }' stopped at step { $step } of { $count }.
+macro-missing = Error: No macro named '{ $name }'.
+macro-running = Running { $name }
+macro-define-usage = Error: Use 'define_macro <name> as <command>; <command>'.
+macro-define-empty = Error: Please provide a macro name and at least one command.
+macro-saved = Macro '{ $name }' saved with { $count } steps.
+macro-save-failed = Failed to save macro '{ $name }': { $error }
+macros-none = No macros defined.
+macros-count = You have { $count } macros.
+macro-deleted = Macro '{ $name }' deleted.
+macro-delete-failed = Failed to delete macro '{ $name }': { $error }
+recording-name-missing = Error: Please name the macro to record.
+recording-already = Error: Already recording '{ $name }'.
+recording-started = Recording macro '{ $name }'. Say stop recording when done.
+recording-none = Error: Not recording a macro.
+recording-kept = { $error }. Still recording; fix or remove the file and stop again.
+recording-discarded = Recording of '{ $name }' discarded; no commands were run.
+macro-recorded = Macro '{ $name }' recorded with { $count } steps.
+
+month-1 = January
+month-2 = February
+month-3 = March
+month-4 = April
+month-5 = May
+month-6 = June
+month-7 = July
+month-8 = August
+month-9 = September
+month-10 = October
+month-11 = November
+month-12 = December
+weekday-1 = Monday
+weekday-2 = Tuesday
+weekday-3 = Wednesday
+weekday-4 = Thursday
+weekday-5 = Friday
+weekday-6 = Saturday
+weekday-7 = Sunday
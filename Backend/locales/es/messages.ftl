@@ -0,0 +1,159 @@
+# Plantillas de respuesta en español. Los argumentos se escriben { $nombre }.
+# Los mensajes de fallo empiezan por "Error:" para que las macros los detecten.
+
+unknown-command = Lo siento, no te he entendido.
+language-set = Idioma cambiado a { $language }.
+language-unknown = Error: Idioma no disponible '{ $language }'. Disponibles: { $available }.
+languages-available = Idiomas disponibles: { $available }. Actual: { $current }.
+
+hello = ¡Hola!
+who-created-you = Sutharsan y Nandhana.
+today-is = Hoy es { $day }
+todays-date-is = La fecha de hoy es { $date }
+spoken-date = { $weekday } { $day } de { $month } de { $year }
+spoken-date-short = { $weekday } { $day } de { $month }
+spoken-time = las { $hour } y { $minute }
+spoken-time-exact = las { $hour } en punto
+
+calendar-unknown-month = Error: No sé qué mes es '{ $month }'.
+calendar-out-of-range = Error: { $year } está fuera de rango.
+calendar-intro = Aquí está el calendario de { $month } de { $year }
+calendar-weekdays-sunday-first = Do Lu Ma Mi Ju Vi Sá
+calendar-weekdays-monday-first = Lu Ma Mi Ju Vi Sá Do
+calendar-has-events = (+ tiene eventos)
+
+home-directory = Directorio personal: { $path }
+home-directory-failed = Error: No se pudo cambiar al directorio personal.
+root-directory = Ahora estás en el directorio raíz.
+root-directory-failed = Error: No se pudo cambiar al directorio raíz.
+files-listed = { $count } elementos en { $place }.
+folders-listed = { $count } carpetas en { $place }.
+files-listed-one = 1 elemento en { $place }.
+folders-listed-one = 1 carpeta en { $place }.
+files-none = Nada en { $place } coincide.
+read-directory-failed = Error: No se pudo leer el directorio.
+my-directory = Ahora estás en tu directorio propio: { $path }
+my-directory-create-failed = Error: No se pudo crear tu directorio propio.
+my-directory-failed = Error: No se pudo ir a tu directorio propio.
+current-directory = Estás en { $path }
+current-directory-failed = Error: No se pudo obtener el directorio actual.
+
+disk-usage-line = Disco { $mount }: Total: { $total }, Usado: { $used }, Libre: { $free }
+disk-usage-intro = Este es el informe de uso de disco.
+memory-usage = Memoria: Total: { $total } MB, Usada: { $used } MB, Libre: { $free } MB
+memory-usage-intro = Este es el informe de uso de memoria.
+free-memory = Memoria libre: { $free } MB
+swap-memory = Memoria de intercambio: { $swap } MB
+cpu-total = CPU total: { $usage } %
+cpu-usage-intro = Este es el informe de uso de CPU.
+process-list-intro = Esta es la lista de procesos en ejecución.
+uptime = Tiempo encendido: { $hours }h { $minutes }m { $seconds }s
+
+help-header = Comandos disponibles:
+help-intro = Estos son los comandos disponibles.
+
+file-name-missing = Error: Indica un nombre de archivo.
+valid-file-name-missing = Error: Indica un nombre de archivo válido.
+opening = Abriendo { $name }
+open-failed = Error: No se pudo abrir el archivo: { $error }
+file-created = Archivo '{ $name }' creado correctamente.
+file-create-failed = Error: No se pudo crear el archivo '{ $name }': { $error }
+file-deleted = Archivo '{ $name }' eliminado correctamente.
+file-delete-failed = Error: No se pudo eliminar el archivo '{ $name }': { $error }
+move-args-missing = Error: Indica origen y destino.
+move-source-missing = Error: El origen '{ $source }' no existe.
+moved = '{ $source }' movido a '{ $destination }'.
+move-failed = Error: No se pudo mover '{ $source }': { $error }
+rename-args-missing = Error: Indica el nombre actual y el nuevo.
+renamed = Renombrado: { $old } -> { $new }
+rename-failed = Error: No se pudo renombrar: { $error }
+copy-done = Copié { $count } archivos, { $size }, en '{ $destination }'.
+copy-done-one = Copié 1 archivo, { $size }, en '{ $destination }'.
+copy-skipped = Omití { $count } que ya existían.
+copy-failed = No se pudieron copiar { $count }.
+copy-all-failed = Error: No se pudo copiar '{ $name }': { $error }
+file-read-failed = Error: No se pudo abrir el archivo '{ $name }': { $error }
+search-found = Encontrado: { $path }
+search-not-found = No se encontró '{ $name }'.
+search-found-many = Encontré { $count } resultados para '{ $name }'. El mejor es { $path }
+
+shutdown-confirm = ¿Seguro que quieres apagar el equipo? (De momento se asume que sí)
+shutting-down = Apagando el equipo.
+shutdown-done = Orden de apagado ejecutada.
+shutdown-failed = Error: No se pudo apagar: { $error }
+restarting = Reiniciando el sistema.
+restart-done = Orden de reinicio ejecutada.
+restart-failed = Error: No se pudo reiniciar: { $error }
+logging-out = Cerrando sesión.
+logout-done = Orden de cierre de sesión ejecutada.
+logout-failed = Error: No se pudo cerrar la sesión: { $error }
+hibernating = Hibernando el sistema.
+hibernate-done = Orden de hibernación ejecutada.
+hibernate-failed = Error: No se pudo hibernar: { $error }
+sleeping = Suspendiendo el sistema.
+sleep-done = Orden de suspensión ejecutada.
+sleep-failed = Error: No se pudo suspender: { $error }
+
+volume-up = Volumen subido.
+volume-up-failed = Error: No se pudo subir el volumen: { $error }
+volume-down = Volumen bajado.
+volume-down-failed = Error: No se pudo bajar el volumen: { $error }
+volume-unsupported = El control de volumen aún no está disponible en este sistema.
+
+compile-file-missing = Error: Indica el archivo que quieres compilar.
+unsupported-format = Error: Formato de archivo no compatible.
+compile-ok = Compilación correcta. Ejecutable: { $name }
+compile-failed = Error: La compilación falló: { $error }
+compile-error = Error: No se pudo compilar: { $error }
+run-output = Salida:
+run-failed = Error: La ejecución falló: { $error }
+run-error = Error al ejecutar el programa: { $error }
+
+symlink-args-missing = Error: Indica origen y destino (por ejemplo, 'origen destino').
+symlink-created = Enlace simbólico creado de { $source } a { $target }
+symlink-failed = Error: No se pudo crear el enlace simbólico: { $error }
+navigate-unsupported = Error: La navegación interactiva aún no está disponible por la API.
+history-shown = Historial de comandos mostrado.
+history-missing = No hay historial de comandos.
+goodbye = ¡Adiós!
+
+macros-unreadable = Error: No se pudo leer { $path }: { $error }
+macro-too-deep = Error: La macro '{ $name }' se anida demasiado.
+macro-stopped = La macro '{ $name }' se detuvo en el paso { $step } de { $count }.
+macro-missing = Error: No hay ninguna macro llamada '{ $name }'.
+macro-running = Ejecutando { $name }
+macro-define-usage = Error: Usa 'define_macro <nombre> as <comando>; <comando>'.
+macro-define-empty = Error: Indica un nombre de macro y al menos un comando.
+macro-saved = Macro '{ $name }' guardada con { $count } pasos.
+macro-save-failed = Error: No se pudo guardar la macro '{ $name }': { $error }
+macros-none = No hay macros definidas.
+macros-count = Tienes { $count } macros.
+macro-deleted = Macro '{ $name }' eliminada.
+macro-delete-failed = Error: No se pudo eliminar la macro '{ $name }': { $error }
+recording-name-missing = Error: Indica el nombre de la macro que quieres grabar.
+recording-already = Error: Ya se está grabando '{ $name }'.
+recording-started = Grabando la macro '{ $name }'. Di stop recording cuando termines.
+recording-none = Error: No se está grabando ninguna macro.
+recording-kept = { $error }. La grabación sigue activa; corrige o elimina el archivo y vuelve a detenerla.
+recording-discarded = Grabación de '{ $name }' descartada; no se ejecutó ningún comando.
+macro-recorded = Macro '{ $name }' grabada con { $count } pasos.
+
+month-1 = enero
+month-2 = febrero
+month-3 = marzo
+month-4 = abril
+month-5 = mayo
+month-6 = junio
+month-7 = julio
+month-8 = agosto
+month-9 = septiembre
+month-10 = octubre
+month-11 = noviembre
+month-12 = diciembre
+weekday-1 = lunes
+weekday-2 = martes
+weekday-3 = miércoles
+weekday-4 = jueves
+weekday-5 = viernes
+weekday-6 = sábado
+weekday-7 = domingo
@@ -0,0 +1,557 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fs, path::Path, sync::Mutex};
+
+use crate::{
+    attach_data, clean_filename, data_path,
+    dates::{find_date, parse_date},
+    scheduler::{parse_clock_time, parse_duration, parse_number},
+    speak_to_speaker,
+};
+
+const EVENTS_FILE: &str = "calendar.json";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CalendarEvent {
+    id: u64,
+    uid: String,
+    title: String,
+    date: String,
+    #[serde(default)]
+    time: Option<String>,
+    #[serde(default)]
+    duration_minutes: i64,
+}
+
+impl CalendarEvent {
+    fn date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, DATE_FORMAT).ok()
+    }
+
+    fn time(&self) -> Option<NaiveTime> {
+        self.time.as_deref().and_then(|t| NaiveTime::parse_from_str(t, TIME_FORMAT).ok())
+    }
+
+    fn describe(&self) -> String {
+        let when = match (self.date(), self.time()) {
+            (Some(date), Some(time)) => format!("{} at {}", date.format("%a %d %b %Y"), time.format("%I:%M %p")),
+            (Some(date), None) => format!("{} (all day)", date.format("%a %d %b %Y")),
+            _ => self.date.clone(),
+        };
+        format!("{}. {}: {}", self.id, when, self.title)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct EventStore {
+    next_id: u64,
+    events: Vec<CalendarEvent>,
+}
+
+// Serializes read-modify-write cycles on the calendar file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn load_events() -> EventStore {
+    fs::read_to_string(data_path(EVENTS_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_events(store: &EventStore) -> std::io::Result<()> {
+    fs::write(data_path(EVENTS_FILE), serde_json::to_string_pretty(store)?)
+}
+
+fn new_uid(id: u64) -> String {
+    format!("{}-{}@aurora", Utc::now().format("%Y%m%dT%H%M%S"), id)
+}
+
+fn push_event(store: &mut EventStore, uid: Option<String>, title: String, date: NaiveDate, time: Option<NaiveTime>, duration: i64) -> u64 {
+    store.next_id += 1;
+    let id = store.next_id;
+    store.events.push(CalendarEvent {
+        id,
+        uid: uid.unwrap_or_else(|| new_uid(id)),
+        title,
+        date: date.format(DATE_FORMAT).to_string(),
+        time: time.map(|t| t.format(TIME_FORMAT).to_string()),
+        duration_minutes: duration,
+    });
+    id
+}
+
+/// Days of the given month that have at least one event, for `show_calendar`.
+pub fn days_with_events(year: i32, month: u32) -> Vec<u32> {
+    let mut days: Vec<u32> = load_events()
+        .events
+        .iter()
+        .filter_map(CalendarEvent::date)
+        .filter(|date| date.year() == year && date.month() == month)
+        .map(|date| date.day())
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+/// Finds "at 3 pm" (or "at 15:30") in the words, returning where it starts,
+/// how many words it spans and the time.
+fn find_time(words: &[&str]) -> Option<(usize, usize, NaiveTime)> {
+    for start in 0..words.len() {
+        if words[start] != "at" {
+            continue;
+        }
+        for len in (1..=2).rev() {
+            let end = start + 1 + len;
+            if end > words.len() {
+                continue;
+            }
+            if let Some(time) = parse_clock_time(&words[start + 1..end], None) {
+                return Some((start, len + 1, time));
+            }
+        }
+    }
+    None
+}
+
+/// Finds "for 2 hours" in the words, returning where it starts, how many
+/// words it spans and the minutes.
+fn find_duration(words: &[&str]) -> Option<(usize, usize, i64)> {
+    for start in 0..words.len() {
+        if words[start] != "for" {
+            continue;
+        }
+        for end in (start + 2..=words.len()).rev() {
+            if let Some(seconds) = parse_duration(&words[start + 1..end]) {
+                return Some((start, end - start, seconds / 60));
+            }
+        }
+    }
+    None
+}
+
+/// `add_event meeting with design team tomorrow at 3 pm [for 2 hours]`.
+/// Dates and times are matched in lowercase; the title keeps its case.
+pub fn add_event(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let mut words: Vec<&str> = lowered.split_whitespace().collect();
+    let mut title: Vec<&str> = arg.split_whitespace().collect();
+    let today = Local::now().date_naive();
+    let mut take = |start: usize, len: usize, words: &mut Vec<&str>| {
+        words.drain(start..start + len);
+        title.drain(start..start + len);
+    };
+
+    let time = find_time(&words).map(|(start, len, time)| {
+        take(start, len, &mut words);
+        time
+    });
+    let duration = find_duration(&words).map(|(start, len, minutes)| {
+        take(start, len, &mut words);
+        minutes
+    });
+    let date = match find_date(&words, today) {
+        Some((start, len, date)) => {
+            take(start, len, &mut words);
+            date
+        }
+        None if time.is_some() => today,
+        None => return "Error: Say when, e.g. 'add_event dentist on friday at 10 am'.".to_string(),
+    };
+    let title = title.join(" ");
+    if title.is_empty() {
+        return "Error: Please give the event a name.".to_string();
+    }
+
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_events();
+    let id = push_event(&mut store, None, title.clone(), date, time, duration.unwrap_or(DEFAULT_DURATION_MINUTES));
+    if let Err(e) = save_events(&store) {
+        return format!("Failed to save event: {}", e);
+    }
+
+    let when = match time {
+        Some(time) => format!("{} at {}", date.format("%A %d %B"), time.format("%I:%M %p")),
+        None => date.format("%A %d %B").to_string(),
+    };
+    let message = format!("Added event {}: {} on {}.", id, title, when);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `list_events [today | friday | this week | next week | 25 december]`;
+/// with no argument, lists upcoming events.
+pub fn list_events(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "what's" | "whats" | "on" | "my" | "calendar" | "for"))
+        .collect();
+    let today = Local::now().date_naive();
+
+    let (from, to, label) = match words.as_slice() {
+        [] | ["upcoming"] => (today, today + Duration::days(365), "coming up".to_string()),
+        ["this", "week"] => (today, today + Duration::days(6 - today.weekday().num_days_from_sunday() as i64), "this week".to_string()),
+        ["next", "week"] => {
+            let start = today + Duration::days(7 - today.weekday().num_days_from_sunday() as i64);
+            (start, start + Duration::days(6), "next week".to_string())
+        }
+        _ => match parse_date(&words, today) {
+            Some(date) => (date, date, date.format("%A %d %B").to_string()),
+            None => return format!("Error: I don't know which day '{}' is.", arg.trim()),
+        },
+    };
+
+    let mut events: Vec<CalendarEvent> = load_events()
+        .events
+        .into_iter()
+        .filter(|event| event.date().is_some_and(|date| date >= from && date <= to))
+        .collect();
+    events.sort_by_key(|event| (event.date(), event.time()));
+
+    attach_data(json!({
+        "from": from.format(DATE_FORMAT).to_string(),
+        "to": to.format(DATE_FORMAT).to_string(),
+        "events": events,
+    }));
+
+    if events.is_empty() {
+        let message = format!("Nothing on your calendar for {}.", label);
+        if let Err(e) = speak_to_speaker(&message) {
+            return format!("Error: {}", e);
+        }
+        return message;
+    }
+
+    let summary = format!(
+        "You have {} event{} {}. First: {}.",
+        events.len(),
+        if events.len() == 1 { "" } else { "s" },
+        if label == "coming up" { label.clone() } else { format!("for {}", label) },
+        events[0].title
+    );
+    if let Err(e) = speak_to_speaker(&summary) {
+        return format!("Error: {}", e);
+    }
+    events.iter().map(CalendarEvent::describe).collect::<Vec<_>>().join("\n")
+}
+
+pub fn delete_event(arg: &str) -> String {
+    let Some(id) = arg.split_whitespace().last().and_then(parse_number) else {
+        return "Error: Please say which event number to delete.".to_string();
+    };
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_events();
+    let Some(index) = store.events.iter().position(|event| event.id == id as u64) else {
+        return format!("Error: No event {}.", id);
+    };
+    let removed = store.events.remove(index);
+    if let Err(e) = save_events(&store) {
+        return format!("Failed to save calendar: {}", e);
+    }
+
+    let message = format!("Deleted event {}: {}.", id, removed.title);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+fn escape_ics(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape_ics(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+/// Folds content lines longer than 75 octets as RFC 5545 requires.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+fn to_ics(events: &[CalendarEvent]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Aurora Assistant//Voice Calendar//EN".to_string(),
+    ];
+    for event in events {
+        let Some(date) = event.date() else { continue };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        match event.time() {
+            Some(time) => {
+                let start = date.and_time(time);
+                let end = start + Duration::minutes(event.duration_minutes.max(1));
+                lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+            }
+            None => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+                lines.push(format!("DTEND;VALUE=DATE:{}", (date + Duration::days(1)).format("%Y%m%d")));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", escape_ics(&event.title)));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+/// Parses an iCalendar DATE or DATE-TIME value. UTC times and times with a
+/// known TZID are converted to local; other times are taken as local already.
+fn parse_ics_datetime(value: &str, tzid: Option<&str>) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Some((date, None));
+    }
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(rest) => (rest, true),
+        None => (value, false),
+    };
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = tzid.and_then(|name| name.trim_matches('"').parse::<Tz>().ok());
+    let local = match zone {
+        _ if utc => Utc.from_utc_datetime(&naive).with_timezone(&Local).naive_local(),
+        Some(zone) => zone.from_local_datetime(&naive).earliest()?.with_timezone(&Local).naive_local(),
+        None => naive,
+    };
+    Some((local.date(), Some(local.time().with_second(0)?)))
+}
+
+/// Parses an iCalendar DURATION such as "PT1H30M" or "P1D" into minutes.
+fn parse_ics_duration(value: &str) -> Option<i64> {
+    let mut minutes = 0;
+    let mut number = String::new();
+    for c in value.trim_start_matches(['+', 'P']).chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                minutes += match unit {
+                    'W' => n * 7 * 24 * 60,
+                    'D' => n * 24 * 60,
+                    'H' => n * 60,
+                    'M' => n,
+                    'S' => n / 60,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(minutes)
+}
+
+struct ImportedEvent {
+    uid: Option<String>,
+    title: String,
+    date: NaiveDate,
+    time: Option<NaiveTime>,
+    duration: i64,
+}
+
+fn from_ics(text: &str) -> Vec<ImportedEvent> {
+    // Unfold continuation lines first.
+    let unfolded = text.replace("\r\n ", "").replace("\r\n\t", "").replace("\n ", "").replace("\n\t", "");
+    let mut events = Vec::new();
+    // Property name, value and TZID parameter of each line in the event.
+    let mut current: Option<Vec<(String, String, Option<String>)>> = None;
+
+    for line in unfolded.lines() {
+        let line = line.trim_end_matches('\r');
+        match line {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => {
+                let Some(props) = current.take() else { continue };
+                let find = |key: &str| props.iter().find(|(k, _, _)| k == key);
+                let get = |key: &str| find(key).map(|(_, v, _)| v.as_str());
+                let datetime = |key: &str| find(key).and_then(|(_, v, tzid)| parse_ics_datetime(v, tzid.as_deref()));
+                let Some((date, time)) = datetime("DTSTART") else {
+                    continue;
+                };
+                let duration = match (get("DURATION").and_then(parse_ics_duration), datetime("DTEND")) {
+                    (Some(minutes), _) => minutes,
+                    (None, Some((end_date, Some(end_time)))) => time
+                        .map(|start| (end_date.and_time(end_time) - date.and_time(start)).num_minutes())
+                        .unwrap_or(DEFAULT_DURATION_MINUTES),
+                    _ => DEFAULT_DURATION_MINUTES,
+                };
+                events.push(ImportedEvent {
+                    uid: get("UID").map(String::from),
+                    title: get("SUMMARY").map(unescape_ics).unwrap_or_else(|| "Untitled event".to_string()),
+                    date,
+                    time,
+                    duration,
+                });
+            }
+            _ => {
+                if let (Some(props), Some((name, value))) = (current.as_mut(), line.split_once(':')) {
+                    // Keep only the TZID of parameters such as ";VALUE=DATE" or ";TZID=...".
+                    let mut params = name.split(';');
+                    let name = params.next().unwrap_or(name).to_uppercase();
+                    let tzid = params
+                        .find_map(|param| param.split_once('=').filter(|(key, _)| key.eq_ignore_ascii_case("TZID")))
+                        .map(|(_, zone)| zone.to_string());
+                    props.push((name, value.to_string(), tzid));
+                }
+            }
+        }
+    }
+    events
+}
+
+pub fn import_events(arg: &str) -> String {
+    let filename = clean_filename(arg);
+    if filename.is_empty() {
+        return "Error: Please provide an .ics file to import.".to_string();
+    }
+    let text = match fs::read_to_string(&filename) {
+        Ok(text) => text,
+        Err(e) => return format!("Failed to read '{}': {}", filename, e),
+    };
+
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_events();
+    let mut imported = 0;
+    let mut skipped = 0;
+    for event in from_ics(&text) {
+        // Events without a UID are recognised by their title and start.
+        let date = event.date.format(DATE_FORMAT).to_string();
+        let time = event.time.map(|t| t.format(TIME_FORMAT).to_string());
+        let duplicate = store.events.iter().any(|existing| match &event.uid {
+            Some(uid) => &existing.uid == uid,
+            None => existing.title == event.title && existing.date == date && existing.time == time,
+        });
+        if duplicate {
+            skipped += 1;
+            continue;
+        }
+        push_event(&mut store, event.uid, event.title, event.date, event.time, event.duration);
+        imported += 1;
+    }
+    if let Err(e) = save_events(&store) {
+        return format!("Failed to save calendar: {}", e);
+    }
+
+    let message = format!("Imported {} events from {} ({} already present).", imported, filename, skipped);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn export_events(arg: &str) -> String {
+    let filename = match clean_filename(arg) {
+        name if name.is_empty() => "calendar.ics".to_string(),
+        name if Path::new(&name).extension().is_none() => format!("{}.ics", name),
+        name => name,
+    };
+    let store = {
+        let _guard = LOCK.lock().unwrap();
+        load_events()
+    };
+
+    match fs::write(&filename, to_ics(&store.events)) {
+        Ok(_) => {
+            let message = format!("Exported {} events to {}.", store.events.len(), filename);
+            if let Err(e) = speak_to_speaker(&message) {
+                return format!("Error: {}", e);
+            }
+            message
+        }
+        Err(e) => format!("Failed to export calendar to '{}': {}", filename, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_times_and_durations_in_words() {
+        let words: Vec<&str> = "lunch at cern at 1 pm for 2 hours".split_whitespace().collect();
+        assert_eq!(find_time(&words), Some((3, 3, NaiveTime::from_hms_opt(13, 0, 0).unwrap())));
+        assert_eq!(find_duration(&words), Some((6, 3, 120)));
+        assert_eq!(find_time(&["meet", "at", "noonish"]), None);
+    }
+
+    #[test]
+    fn adds_spoken_meetings() {
+        crate::i18n::set_request_language(Some("en"));
+        let intent = crate::i18n::match_intent("add meeting with design team tomorrow at 3 pm").unwrap();
+        assert_eq!(intent, "add_event meeting with design team tomorrow at 3 pm");
+        let intent = crate::i18n::match_intent("Add Review with Ana on friday at 10 am").unwrap();
+        assert_eq!(intent, "add_event Review with Ana on friday at 10 am");
+
+        add_event(intent.strip_prefix("add_event ").unwrap());
+        add_event("meeting with design team tomorrow at 3 pm");
+        let events = load_events().events;
+        let event = events.iter().find(|event| event.title == "meeting with design team").unwrap();
+        assert_eq!(event.date(), Some(Local::now().date_naive() + Duration::days(1)));
+        assert_eq!(event.time(), NaiveTime::from_hms_opt(15, 0, 0));
+        assert_eq!(event.duration_minutes, DEFAULT_DURATION_MINUTES);
+        assert!(events.iter().any(|event| event.title == "Review with Ana"));
+    }
+
+    #[test]
+    fn imports_zoned_times_and_skips_repeated_events() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART;TZID=America/New_York:20260701T090000\r\n\
+                   DTEND;TZID=\"America/New_York\":20260701T103000\r\nSUMMARY:Stand-up\\, team\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260702\r\nSUMMARY:Holiday\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let events = from_ics(ics);
+        let start = chrono_tz::America::New_York
+            .with_ymd_and_hms(2026, 7, 1, 9, 0, 0)
+            .unwrap()
+            .with_timezone(&Local)
+            .naive_local();
+        assert_eq!((events[0].date, events[0].time), (start.date(), Some(start.time())));
+        assert_eq!((events[0].title.as_str(), events[0].duration, events[0].uid.as_ref()), ("Stand-up, team", 90, None));
+        assert_eq!((events[1].date, events[1].time), (NaiveDate::from_ymd_opt(2026, 7, 2).unwrap(), None));
+
+        let file = std::env::temp_dir().join(format!("aurora-agenda-{}.ics", std::process::id()));
+        fs::write(&file, ics).unwrap();
+        import_events(&file.display().to_string());
+        import_events(&file.display().to_string());
+        let stored = load_events().events;
+        assert_eq!(stored.iter().filter(|event| event.title == "Stand-up, team").count(), 1);
+        assert_eq!(stored.iter().filter(|event| event.title == "Holiday").count(), 1);
+        let _ = fs::remove_file(&file);
+    }
+}
@@ -0,0 +1,491 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashMap, fs, sync::Mutex};
+
+use crate::{
+    data_path, events, locale,
+    metrics::Snapshot,
+    scheduler::{parse_duration, parse_number},
+    speak_to_speaker,
+};
+
+const ALERTS_FILE: &str = "alerts.json";
+// A firing alert that nobody acknowledged is repeated this often.
+const REPEAT_SECONDS: i64 = 10 * 60;
+const DEFAULT_SNOOZE_SECONDS: i64 = 30 * 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Measure {
+    CpuUsed,
+    MemoryUsed,
+    MemoryFree,
+    SwapUsed,
+    DiskUsed,
+    DiskFree,
+    Load,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Threshold {
+    Value(f64),
+    // The number of logical cores, for load average rules.
+    Cores,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Rule {
+    id: u64,
+    measure: Measure,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mount: Option<String>,
+    above: bool,
+    threshold: Threshold,
+    // How long the condition must hold before the alert fires, in seconds.
+    duration: i64,
+    #[serde(default)]
+    snoozed_until: i64,
+    #[serde(default)]
+    acknowledged: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AlertStore {
+    next_id: u64,
+    rules: Vec<Rule>,
+}
+
+// Per-rule state while the backend runs; not worth persisting.
+#[derive(Debug, Default)]
+struct RuleState {
+    since: Option<i64>,
+    firing: bool,
+    last_announced: i64,
+}
+
+// Serializes read-modify-write cycles on the alerts file.
+static LOCK: Mutex<()> = Mutex::new(());
+static STATE: Mutex<Option<HashMap<u64, RuleState>>> = Mutex::new(None);
+
+fn load_alerts() -> AlertStore {
+    fs::read_to_string(data_path(ALERTS_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_alerts(store: &AlertStore) -> std::io::Result<()> {
+    fs::write(data_path(ALERTS_FILE), serde_json::to_string_pretty(store)?)
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+impl Rule {
+    fn describe(&self) -> String {
+        let what = match self.measure {
+            Measure::CpuUsed => "CPU usage".to_string(),
+            Measure::MemoryUsed => "memory used".to_string(),
+            Measure::MemoryFree => "memory free".to_string(),
+            Measure::SwapUsed => "swap used".to_string(),
+            Measure::DiskUsed => format!("disk used on {}", self.mount.as_deref().unwrap_or("/")),
+            Measure::DiskFree => format!("disk free on {}", self.mount.as_deref().unwrap_or("/")),
+            Measure::Load => "load average".to_string(),
+        };
+        let threshold = match self.threshold {
+            Threshold::Cores => "core count".to_string(),
+            Threshold::Value(value) if self.measure == Measure::Load => locale::format_decimal(value, 1),
+            Threshold::Value(value) => format!("{}%", locale::format_decimal(value, 0)),
+        };
+        let held = if self.duration > 0 {
+            format!(" for {} minutes", (self.duration as f64 / 60.0).round())
+        } else {
+            String::new()
+        };
+        format!("{} {} {}{}", what, if self.above { "above" } else { "below" }, threshold, held)
+    }
+
+    /// The current value and the threshold it is compared with.
+    fn reading(&self, snapshot: &Snapshot) -> Option<(f64, f64)> {
+        let disk = || {
+            let mount = self.mount.as_deref().unwrap_or("/");
+            snapshot.disks.iter().find(|disk| disk.mount_point == mount)
+        };
+        let value = match self.measure {
+            Measure::CpuUsed => snapshot.cpu_usage as f64,
+            Measure::MemoryUsed => percent(snapshot.memory_used, snapshot.memory_total),
+            Measure::MemoryFree => percent(snapshot.memory_available, snapshot.memory_total),
+            Measure::SwapUsed => percent(snapshot.swap_used, snapshot.swap_total),
+            Measure::DiskUsed => disk().map(|d| percent(d.total.saturating_sub(d.available), d.total))?,
+            Measure::DiskFree => disk().map(|d| percent(d.available, d.total))?,
+            Measure::Load => snapshot.load_average[0],
+        };
+        let threshold = match self.threshold {
+            Threshold::Value(value) => value,
+            Threshold::Cores => snapshot.cpus.len() as f64,
+        };
+        Some((value, threshold))
+    }
+}
+
+/// Parses rules such as "disk free below 10% on /", "memory used above 90%
+/// for 5 minutes" or "load average above core count".
+fn parse_rule(text: &str) -> Result<Rule, String> {
+    let lowered = text.to_lowercase().replace('%', " percent");
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let has = |word: &str| words.contains(&word);
+
+    let free = has("free") || has("available");
+    let measure = if has("disk") || has("storage") || words.iter().any(|w| w.starts_with('/')) {
+        if free { Measure::DiskFree } else { Measure::DiskUsed }
+    } else if has("memory") || has("ram") {
+        if free { Measure::MemoryFree } else { Measure::MemoryUsed }
+    } else if has("swap") {
+        Measure::SwapUsed
+    } else if has("cpu") || has("processor") {
+        Measure::CpuUsed
+    } else if has("load") {
+        Measure::Load
+    } else {
+        return Err("Error: Say what to watch: cpu, memory, swap, disk or load.".to_string());
+    };
+
+    let comparison = words.iter().enumerate().find_map(|(i, w)| match *w {
+        "above" | "over" | "exceeds" => Some((i, true)),
+        "below" | "under" => Some((i, false)),
+        "more" | "greater" | "higher" if words.get(i + 1) == Some(&"than") => Some((i + 1, true)),
+        "less" | "lower" if words.get(i + 1) == Some(&"than") => Some((i + 1, false)),
+        _ => None,
+    });
+    let Some((position, above)) = comparison else {
+        return Err("Error: Say 'above' or 'below' and a threshold, e.g. 'memory used above 90%'.".to_string());
+    };
+    let after = &words[position + 1..];
+
+    let threshold = if matches!(after, ["core" | "cores", ..] | ["the", "core", ..] | ["number", "of", "cores", ..]) {
+        Threshold::Cores
+    } else {
+        let value = after
+            .first()
+            .and_then(|w| w.parse::<f64>().ok().or_else(|| parse_number(w).map(f64::from)))
+            .ok_or_else(|| "Error: Say the threshold as a number, e.g. 'above 90%'.".to_string())?;
+        Threshold::Value(value)
+    };
+
+    let duration = match words.iter().rposition(|w| *w == "for") {
+        Some(position) => {
+            let span: Vec<&str> = words[position + 1..].iter().take_while(|w| !w.starts_with('/') && **w != "on").copied().collect();
+            parse_duration(&span).ok_or_else(|| format!("Error: I don't understand 'for {}'.", span.join(" ")))?
+        }
+        None => 0,
+    };
+    let mount = matches!(measure, Measure::DiskUsed | Measure::DiskFree)
+        .then(|| words.iter().find(|w| w.starts_with('/')).map_or("/", |w| w).to_string());
+
+    Ok(Rule { id: 0, measure, mount, above, threshold, duration, snoozed_until: 0, acknowledged: false })
+}
+
+/// `add_alert disk free below 10% on /`, `add_alert memory used above 90% for
+/// 5 minutes`, `add_alert load average above core count`.
+pub fn add_alert(arg: &str) -> String {
+    let mut rule = match parse_rule(arg) {
+        Ok(rule) => rule,
+        Err(e) => return e,
+    };
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_alerts();
+    store.next_id += 1;
+    rule.id = store.next_id;
+    let message = format!("Alert {} added: {}.", rule.id, rule.describe());
+    store.rules.push(rule);
+    if let Err(e) = save_alerts(&store) {
+        return format!("Failed to save alert: {}", e);
+    }
+
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn list_alerts(_arg: &str) -> String {
+    let store = {
+        let _guard = LOCK.lock().unwrap();
+        load_alerts()
+    };
+    if store.rules.is_empty() {
+        if let Err(e) = speak_to_speaker("There are no alert rules.") {
+            return format!("Error: {}", e);
+        }
+        return "There are no alert rules.".to_string();
+    }
+
+    let now = Local::now().timestamp();
+    let state = STATE.lock().unwrap();
+    let lines: Vec<String> = store
+        .rules
+        .iter()
+        .map(|rule| {
+            let firing = state.as_ref().and_then(|s| s.get(&rule.id)).is_some_and(|s| s.firing);
+            let mut line = format!("{}. {}", rule.id, rule.describe());
+            if firing {
+                line.push_str(if rule.acknowledged { " (firing, acknowledged)" } else { " (firing)" });
+            }
+            if rule.snoozed_until > now {
+                line.push_str(&format!(" (snoozed for {} more minutes)", (rule.snoozed_until - now + 59) / 60));
+            }
+            line
+        })
+        .collect();
+
+    if let Err(e) = speak_to_speaker(&format!("You have {} alert rules.", lines.len())) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+pub fn delete_alert(arg: &str) -> String {
+    let Some(id) = arg.split_whitespace().last().and_then(parse_number) else {
+        return "Error: Say which alert to delete, e.g. 'delete_alert 2'.".to_string();
+    };
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_alerts();
+    let before = store.rules.len();
+    store.rules.retain(|rule| rule.id != id as u64);
+    if store.rules.len() == before {
+        return format!("Error: No alert {}.", id);
+    }
+    if let Err(e) = save_alerts(&store) {
+        return format!("Failed to save alerts: {}", e);
+    }
+
+    let message = format!("Deleted alert {}.", id);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// Applies `update` to alert `id`, or to every firing alert when no id is
+/// given. Returns the ids that were changed.
+fn update_alerts(id: Option<u64>, update: impl Fn(&mut Rule)) -> Result<Vec<u64>, String> {
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_alerts();
+    let state = STATE.lock().unwrap();
+    let firing = |rule: &Rule| state.as_ref().and_then(|s| s.get(&rule.id)).is_some_and(|s| s.firing);
+
+    let mut changed = Vec::new();
+    for rule in store.rules.iter_mut() {
+        if id.map_or(firing(rule), |id| rule.id == id) {
+            update(rule);
+            changed.push(rule.id);
+        }
+    }
+    match (id, changed.is_empty()) {
+        (Some(id), true) => return Err(format!("Error: No alert {}.", id)),
+        (None, true) => return Err("Error: No alerts are firing.".to_string()),
+        _ => {}
+    }
+    save_alerts(&store).map_err(|e| format!("Failed to save alerts: {}", e))?;
+    Ok(changed)
+}
+
+fn alert_ids(ids: &[u64]) -> String {
+    let ids: Vec<String> = ids.iter().map(u64::to_string).collect();
+    format!("alert{} {}", if ids.len() == 1 { "" } else { "s" }, ids.join(", "))
+}
+
+/// The alert a request names: digits anywhere, or a number word right after
+/// "alert" or "number". "an alert" names none, so it means every firing one.
+fn alert_id(words: &[&str]) -> Option<u64> {
+    words.iter().enumerate().find_map(|(i, word)| {
+        if word.chars().all(|c| c.is_ascii_digit()) {
+            return word.parse().ok();
+        }
+        let labelled = i > 0 && matches!(words[i - 1], "alert" | "number");
+        if !labelled || matches!(*word, "a" | "an") {
+            return None;
+        }
+        parse_number(word).map(u64::from)
+    })
+}
+
+/// `acknowledge_alert` silences every firing alert until it clears;
+/// `acknowledge_alert 2` just that one.
+pub fn acknowledge_alert(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let id = alert_id(&words);
+    let changed = match update_alerts(id, |rule| rule.acknowledged = true) {
+        Ok(changed) => changed,
+        Err(e) => return e,
+    };
+
+    let message = format!("Acknowledged {}.", alert_ids(&changed));
+    events::publish("alert_acknowledged", &message, json!({ "ids": changed }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `snooze_alert for 1 hour` snoozes every firing alert, `snooze_alert 2 for
+/// 10 minutes` one rule. Without a duration alerts are snoozed for 30 minutes.
+pub fn snooze_alert(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let (id_words, duration_words) = match words.iter().position(|w| *w == "for") {
+        Some(position) => (&words[..position], &words[position + 1..]),
+        None => (&words[..], &words[words.len()..]),
+    };
+    let id = alert_id(id_words);
+    let seconds = if duration_words.is_empty() {
+        DEFAULT_SNOOZE_SECONDS
+    } else {
+        match parse_duration(duration_words) {
+            Some(seconds) => seconds,
+            None => return format!("Error: I don't understand 'for {}'.", duration_words.join(" ")),
+        }
+    };
+
+    let until = Local::now().timestamp() + seconds;
+    let changed = match update_alerts(id, |rule| rule.snoozed_until = until) {
+        Ok(changed) => changed,
+        Err(e) => return e,
+    };
+
+    let message = format!("Snoozed {} for {} minutes.", alert_ids(&changed), seconds / 60);
+    events::publish("alert_snoozed", &message, json!({ "ids": changed, "until": until }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// Checks every rule against a collector reading, publishing and speaking
+/// warnings for rules that start firing or keep firing unacknowledged.
+pub fn evaluate(snapshot: &Snapshot) {
+    let now = snapshot.time;
+    let mut announcements = Vec::new();
+    {
+        let _guard = LOCK.lock().unwrap();
+        let mut store = load_alerts();
+        let mut state = STATE.lock().unwrap();
+        let state = state.get_or_insert_with(HashMap::new);
+        state.retain(|id, _| store.rules.iter().any(|rule| rule.id == *id));
+        let mut changed = false;
+
+        for rule in store.rules.iter_mut() {
+            let Some((value, threshold)) = rule.reading(snapshot) else {
+                continue;
+            };
+            let entry = state.entry(rule.id).or_default();
+            let breached = if rule.above { value > threshold } else { value < threshold };
+
+            if !breached {
+                if entry.firing {
+                    let message = format!("Alert {} cleared: {} is back to {}.", rule.id, rule.describe(), format_value(rule, value));
+                    events::publish("alert_resolved", &message, json!({ "id": rule.id, "value": value }));
+                    if rule.acknowledged {
+                        rule.acknowledged = false;
+                        changed = true;
+                    }
+                }
+                *entry = RuleState::default();
+                continue;
+            }
+
+            let since = *entry.since.get_or_insert(now);
+            if now - since < rule.duration {
+                continue;
+            }
+            let started = !entry.firing;
+            entry.firing = true;
+            let due = !rule.acknowledged && now >= rule.snoozed_until && now - entry.last_announced >= REPEAT_SECONDS;
+            if started || due {
+                let message = format!("Warning: {} is {}.", rule.describe(), format_value(rule, value));
+                events::publish(
+                    "alert",
+                    &message,
+                    json!({ "id": rule.id, "rule": rule.describe(), "value": value, "threshold": threshold }),
+                );
+                if due {
+                    entry.last_announced = now;
+                    announcements.push(message);
+                }
+            }
+        }
+        if changed {
+            if let Err(e) = save_alerts(&store) {
+                eprintln!("Failed to save alerts: {}", e);
+            }
+        }
+    }
+
+    for message in announcements {
+        if let Err(e) = speak_to_speaker(&message) {
+            eprintln!("Error: {}", e);
+        }
+    }
+}
+
+fn format_value(rule: &Rule, value: f64) -> String {
+    if rule.measure == Measure::Load {
+        locale::format_decimal(value, 2)
+    } else {
+        format!("{}%", locale::format_decimal(value, 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        let rule = parse_rule("disk free below 10% on /home").unwrap();
+        assert_eq!(rule.measure, Measure::DiskFree);
+        assert_eq!(rule.mount.as_deref(), Some("/home"));
+        assert!(!rule.above);
+        assert_eq!(rule.threshold, Threshold::Value(10.0));
+        assert_eq!(rule.duration, 0);
+
+        let rule = parse_rule("Memory used above 90 percent for 5 minutes").unwrap();
+        assert_eq!(rule.measure, Measure::MemoryUsed);
+        assert_eq!(rule.mount, None);
+        assert!(rule.above);
+        assert_eq!(rule.threshold, Threshold::Value(90.0));
+        assert_eq!(rule.duration, 300);
+
+        let rule = parse_rule("disk used more than 80.5% for an hour on /").unwrap();
+        assert_eq!((rule.measure, rule.above, rule.threshold), (Measure::DiskUsed, true, Threshold::Value(80.5)));
+        assert_eq!((rule.duration, rule.mount.as_deref()), (3600, Some("/")));
+
+        let rule = parse_rule("load average above core count").unwrap();
+        assert_eq!((rule.measure, rule.threshold), (Measure::Load, Threshold::Cores));
+        let rule = parse_rule("swap less than twenty").unwrap();
+        assert_eq!((rule.measure, rule.above, rule.threshold), (Measure::SwapUsed, false, Threshold::Value(20.0)));
+
+        assert!(parse_rule("temperature above 80").is_err());
+        assert!(parse_rule("cpu at 90%").is_err());
+        assert!(parse_rule("cpu above lots").is_err());
+        assert!(parse_rule("cpu above 90% for a while").is_err());
+    }
+
+    #[test]
+    fn names_alerts_only_by_number() {
+        assert_eq!(alert_id(&["2"]), Some(2));
+        assert_eq!(alert_id(&["alert", "number", "three"]), Some(3));
+        assert_eq!(alert_id(&["alert", "two"]), Some(2));
+        assert_eq!(alert_id(&["an", "alert"]), None);
+        assert_eq!(alert_id(&["a", "warning"]), None);
+        assert_eq!(alert_id(&["one", "alert"]), None);
+    }
+}
@@ -1,56 +1,167 @@
 use axum::{
     routing::{post, get},
-    Router, Json, extract::State, response::IntoResponse,
+    Router, Json, extract::State,
 };
 use serde::{Deserialize, Serialize};
 use tower_http::cors::{CorsLayer, Any};
-use std::{net::SocketAddr, collections::HashMap, sync::Arc, path::Path, io, io::BufReader, io::BufRead, fs::{File, OpenOptions}, process};
-use walkdir::WalkDir;
-use std::io::Write;
-use chrono::{Local, TimeZone, Datelike, Duration, NaiveDate};
+use std::{net::SocketAddr, collections::HashMap, sync::{Arc, Mutex}, cell::RefCell, path::{Path, PathBuf}, io::BufReader, io::BufRead, process};
+use chrono::{Local, Datelike, Duration, NaiveDate, Month, Weekday};
 use tokio::net::TcpListener;
 use std::fs;
 use dirs::home_dir;
 use anyhow::{Result, Context};
-use sysinfo::{System, SystemExt, ProcessExt, DiskExt, CpuExt};
 use std::process::Command;
+use i18n::tr;
+
+mod agenda;
+mod alerts;
+mod copy;
+mod dates;
+mod diagnostics;
+mod events;
+mod files;
+mod i18n;
+mod index;
+mod locale;
+mod macros;
+mod metrics;
+mod network;
+mod paths;
+mod plugins;
+mod ports;
+mod processes;
+mod scheduler;
+mod scripting;
+mod search;
+mod space;
+mod storage;
+mod telemetry;
+mod timers;
+mod trends;
+
+type CommandFn = Box<dyn Fn(&str) -> String + Send + Sync>;
+type CommandMap = HashMap<String, CommandFn>;
 
 #[derive(Debug, Deserialize)]
 struct CommandRequest {
     command: String,
+    #[serde(default)]
+    language: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct CommandResponse {
     response: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
-struct CommandOutput {
-    response: String,
+thread_local! {
+    static RESPONSE_DATA: RefCell<Option<serde_json::Value>> = const { RefCell::new(None) };
+}
+
+/// Attaches structured data to the response of the command currently being
+/// handled. Commands still return their text; clients may use either.
+fn attach_data(data: serde_json::Value) {
+    RESPONSE_DATA.with(|slot| *slot.borrow_mut() = Some(data));
 }
 
 async fn handle_command_request(
-    State(commands): State<Arc<HashMap<String, Box<dyn Fn(&str) -> String + Send + Sync>>>>,
+    State(commands): State<Arc<CommandMap>>,
     Json(payload): Json<CommandRequest>,
 ) -> Json<CommandResponse> {
-    let parts: Vec<&str> = payload.command.split_whitespace().collect();
-    let command = parts[0];
-    let argument = parts.get(1..).map(|args| args.join(" ")).unwrap_or_default();
+    RESPONSE_DATA.with(|slot| slot.borrow_mut().take());
+    i18n::set_request_language(payload.language.as_deref());
+    let started = std::time::Instant::now();
+    let response = dispatch(&commands, &payload.command);
+    let data = RESPONSE_DATA.with(|slot| slot.borrow_mut().take());
+    let failed = is_failure(&response);
+    if !failed {
+        macros::record(&payload.command);
+    }
+
+    // Only registered names become metric labels; free-form phrases are grouped.
+    let name = payload.command.split_whitespace().next().unwrap_or_default();
+    if response == tr!("unknown-command") {
+        telemetry::record_unknown_utterance();
+    } else if is_command(&commands, name) {
+        telemetry::record_command(name, started.elapsed(), failed);
+    } else {
+        telemetry::record_command("phrase", started.elapsed(), failed);
+    }
 
-    let response = commands
-        .get(command)
-        .map(|func| func(&argument))
-        .unwrap_or_else(|| "Sorry, I didn’t get you.".to_string());
+    Json(CommandResponse { response, data })
+}
 
-    Json(CommandResponse { response })
+fn dispatch(commands: &CommandMap, line: &str) -> String {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let Some(&command) = parts.first() else {
+        return tr!("unknown-command");
+    };
+    let argument = parts[1..].join(" ");
+
+    // These commands need the registry itself, so they are handled here.
+    match command {
+        "run_macro" => macros::run_macro(commands, &argument),
+        "define_macro" => macros::define_macro(commands, &argument),
+        "schedule" => scheduler::schedule(commands, &argument),
+        _ => scheduler::try_schedule(commands, line)
+            .or_else(|| commands.get(command).map(|func| func(&argument)))
+            .or_else(|| {
+                // The resolved intent is dispatched like a typed command, so
+                // "remind me ... at 5 pm" is scheduled rather than run now.
+                let intent = i18n::match_intent(line)?;
+                let command = intent.split_whitespace().next()?;
+                is_command(commands, command).then(|| dispatch(commands, &intent))
+            })
+            .or_else(|| macros::run_by_phrase(commands, line))
+            .unwrap_or_else(|| tr!("unknown-command")),
+    }
+}
+
+fn is_command(commands: &CommandMap, name: &str) -> bool {
+    matches!(name, "run_macro" | "define_macro" | "schedule") || commands.contains_key(name)
+}
+
+fn is_failure(response: &str) -> bool {
+    ["Error", "Failed", "Command failed", "Sorry"]
+        .iter()
+        .any(|prefix| response.starts_with(prefix))
+        || response == tr!("unknown-command")
+}
+
+fn data_path(name: &str) -> PathBuf {
+    // Tests keep their files out of the user's data directory.
+    let dir = if cfg!(test) {
+        std::env::temp_dir().join(format!("aurora-test-{}", std::process::id()))
+    } else {
+        dirs::data_dir()
+            .or_else(home_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("aurora")
+    };
+    let _ = fs::create_dir_all(&dir);
+    dir.join(name)
+}
+
+/// Human-readable size, e.g. "512 KB" or "1.5 GB".
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let decimals = if unit == 0 || value >= 100.0 { 0 } else { 1 };
+    format!("{} {}", locale::format_decimal(value, decimals), UNITS[unit])
 }
 
 fn speak_to_speaker(text: &str) -> Result<()> {
     println!("{}", text);
     if cfg!(target_os = "windows") {
         Command::new("powershell")
-            .args(&[
+            .args([
                 "-Command",
                 &format!(
                     "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak('{}')",
@@ -58,99 +169,201 @@ fn speak_to_speaker(text: &str) -> Result<()> {
                 ),
             ])
             .status()
-            .context("Failed to speak")?;
+            .context("Failed to speak")
+            .inspect_err(|_| telemetry::record_speech_error())?;
     } else {
         Command::new("espeak")
+            .args(["-v", i18n::language()])
             .arg(text)
             .status()
-            .context("Failed to speak")?;
+            .context("Failed to speak")
+            .inspect_err(|_| telemetry::record_speech_error())?;
     }
     Ok(())
 }
 
 // Command functions
 fn hello(_arg: &str) -> String {
-    if let Err(e) = speak_to_speaker("Hello!") {
+    let message = tr!("hello");
+    if let Err(e) = speak_to_speaker(&message) {
         return format!("Error: {}", e);
     }
-    "Hello!".to_string()
+    message
 }
 
 fn who_created_you(_arg: &str) -> String {
-    if let Err(e) = speak_to_speaker("Sutharsan and Nandhana.") {
+    let message = tr!("who-created-you");
+    if let Err(e) = speak_to_speaker(&message) {
         return format!("Error: {}", e);
     }
-    "Sutharsan and Nandhana.".to_string()
+    message
 }
 
 fn current_day(_arg: &str) -> String {
-    let day = Local::now().format("%A").to_string();
-    if let Err(e) = speak_to_speaker(&format!("Today is {}", day)) {
+    let today = Local::now().date_naive();
+    let day = i18n::weekday_name(today);
+    if let Err(e) = speak_to_speaker(&tr!("today-is", day = i18n::speak_date(today, false))) {
         return format!("Error: {}", e);
     }
     day
 }
 
 fn current_date(_arg: &str) -> String {
-    let date = Local::now().format("%d %m %Y").to_string();
-    if let Err(e) = speak_to_speaker(&format!("Today's date is {}", date)) {
+    let today = Local::now().date_naive();
+    let date = locale::format_date(today);
+    if let Err(e) = speak_to_speaker(&tr!("todays-date-is", date = i18n::speak_date(today, true))) {
         return format!("Error: {}", e);
     }
     date
 }
 
 fn current_time(_arg: &str) -> String {
-    let time = Local::now().format("%H:%M:%S").to_string();
-    if let Err(e) = speak_to_speaker(&time) {
+    let now = Local::now().time();
+    let time = locale::format_time(now);
+    if let Err(e) = speak_to_speaker(&i18n::speak_time(now)) {
         return format!("Error: {}", e);
     }
     time
 }
 
-fn generate_calendar(year: i32, month: u32) -> String {
-    let first_day = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
-    let days_in_month = first_day
-        .with_day(1)
-        .unwrap()
-        .with_day(31)
-        .map_or_else(|| first_day.with_day(30), Some)
-        .unwrap()
-        .day();
+// Month last shown by `show_calendar`, so "next month" pages forward from it.
+static LAST_CALENDAR: Mutex<Option<(i32, u32)>> = Mutex::new(None);
 
-    let mut calendar = format!("\n   {} {}\n", first_day.format("%B"), year);
-    calendar.push_str("Su Mo Tu We Th Fr Sa\n");
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(31)
+}
 
-    let start_weekday = first_day.weekday().num_days_from_sunday() as usize;
-    let mut day = 1;
+fn shift_month(year: i32, month: u32, delta: i32) -> (i32, u32) {
+    let index = year * 12 + month as i32 - 1 + delta;
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
 
-    for _ in 0..start_weekday {
-        calendar.push_str("   ");
+/// Works out which month a spoken request refers to: "March 2027",
+/// "next month", "last year", "2030", or nothing for the current month.
+fn parse_calendar_month(arg: &str, current: (i32, u32)) -> Option<(i32, u32)> {
+    let today = Local::now();
+    let words: Vec<String> = arg
+        .to_lowercase()
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "for" | "of" | "in" | "the" | "show" | "calendar" | "de"))
+        .map(String::from)
+        .collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let (year, month) = current;
+
+    match words.as_slice() {
+        [] | ["this", "month"] | ["today"] => Some((today.year(), today.month())),
+        ["next", "month"] => Some(shift_month(year, month, 1)),
+        ["last", "month"] | ["previous", "month"] => Some(shift_month(year, month, -1)),
+        ["next", "year"] => Some((year + 1, month)),
+        ["last", "year"] | ["previous", "year"] => Some((year - 1, month)),
+        ["this", "year"] => Some((today.year(), month)),
+        [single] => match single.parse::<i32>() {
+            Ok(y) => Some((y, month)),
+            Err(_) => month_number(single).map(|m| (today.year(), m)),
+        },
+        [name, y] => Some((y.parse().ok()?, month_number(name)?)),
+        _ => None,
     }
+}
 
-    while day <= days_in_month {
-        for weekday in 0..7 {
-            if day > days_in_month {
-                break;
-            }
-            if weekday < start_weekday && day == 1 {
-                continue;
-            }
-            calendar.push_str(&format!("{:2} ", day));
-            day += 1;
+fn month_number(name: &str) -> Option<u32> {
+    name.parse::<Month>()
+        .ok()
+        .map(|m| m.number_from_month())
+        .or_else(|| i18n::month_number(name))
+}
+
+fn generate_calendar(year: i32, month: u32, week_start: Weekday) -> (String, serde_json::Value) {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let days_in_month = days_in_month(year, month);
+    let today = Local::now().date_naive();
+    let today_day = (today.year() == year && today.month() == month).then(|| today.day());
+    let event_days = agenda::days_with_events(year, month);
+
+    let mut calendar = format!("\n   {} {}\n", i18n::month_name(month), year);
+    calendar.push_str(&if week_start == Weekday::Mon {
+        tr!("calendar-weekdays-monday-first")
+    } else {
+        tr!("calendar-weekdays-sunday-first")
+    });
+    calendar.push('\n');
+
+    let start_weekday = first_day.weekday().days_since(week_start) as usize;
+    let mut weeks: Vec<Vec<Option<u32>>> = Vec::new();
+    let mut week: Vec<Option<u32>> = vec![None; start_weekday];
+    for day in 1..=days_in_month {
+        week.push(Some(day));
+        if week.len() == 7 {
+            weeks.push(std::mem::take(&mut week));
         }
+    }
+    if !week.is_empty() {
+        week.resize(7, None);
+        weeks.push(week);
+    }
+
+    // Today is marked with a trailing '*' and days with events with '+', so the
+    // columns stay aligned.
+    for week in &weeks {
+        let line: String = week
+            .iter()
+            .map(|day| match day {
+                Some(day) if Some(*day) == today_day => format!("{:2}*", day),
+                Some(day) if event_days.contains(day) => format!("{:2}+", day),
+                Some(day) => format!("{:2} ", day),
+                None => "   ".to_string(),
+            })
+            .collect();
+        calendar.push_str(line.trim_end());
+        calendar.push('\n');
+    }
+    if !event_days.is_empty() {
+        calendar.push_str(&tr!("calendar-has-events"));
         calendar.push('\n');
     }
 
-    calendar
+    let data = serde_json::json!({
+        "year": year,
+        "month": month,
+        "month_name": first_day.format("%B").to_string(),
+        "days_in_month": days_in_month,
+        "leap_year": NaiveDate::from_ymd_opt(year, 2, 29).is_some(),
+        "week_start": if week_start == Weekday::Mon { "monday" } else { "sunday" },
+        "today": today_day,
+        "event_days": event_days,
+        "weeks": weeks,
+    });
+    (calendar, data)
 }
 
-fn show_calendar(_arg: &str) -> String {
+fn show_calendar(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let week_start = if lowered.contains("monday") { Weekday::Mon } else { Weekday::Sun };
+    let arg = lowered
+        .replace("starting monday", "")
+        .replace("starting on monday", "")
+        .replace("monday first", "");
+
     let now = Local::now();
-    let year = now.year();
-    let month = now.month();
-    let calendar_text = generate_calendar(year, month);
+    let mut last = LAST_CALENDAR.lock().unwrap();
+    let current = last.unwrap_or((now.year(), now.month()));
+    let Some((year, month)) = parse_calendar_month(&arg, current) else {
+        return tr!("calendar-unknown-month", month = arg.trim());
+    };
+    if NaiveDate::from_ymd_opt(year, month, 1).is_none() {
+        return tr!("calendar-out-of-range", year = year);
+    }
+    *last = Some((year, month));
 
-    if let Err(e) = speak_to_speaker(&format!("Here is the calendar for {} {}", now.format("%B"), year)) {
+    let (calendar_text, data) = generate_calendar(year, month, week_start);
+    attach_data(data);
+
+    if let Err(e) = speak_to_speaker(&tr!("calendar-intro", month = i18n::month_name(month), year = year)) {
         return format!("Error: {}", e);
     }
 
@@ -160,39 +373,25 @@ fn show_calendar(_arg: &str) -> String {
 fn go_home(_arg: &str) -> String {
     if let Some(home) = home_dir() {
         if std::env::set_current_dir(&home).is_ok() {
-            if let Err(e) = speak_to_speaker(&format!("Home directory: {}", home.display())) {
+            let message = tr!("home-directory", path = home.display());
+            if let Err(e) = speak_to_speaker(&message) {
                 return format!("Error: {}", e);
             }
-            return format!("Home directory: {}", home.display());
+            return message;
         }
     }
-    "Failed to change to home directory.".to_string()
+    tr!("home-directory-failed")
 }
 
 fn root_directory(_arg: &str) -> String {
     let root = if cfg!(target_os = "windows") { "C:\\" } else { "/" };
     if std::env::set_current_dir(root).is_ok() {
-        if let Err(e) = speak_to_speaker("You are now in the root directory.") {
+        if let Err(e) = speak_to_speaker(&tr!("root-directory")) {
             return format!("Error: {}", e);
         }
         return root.to_string();
     }
-    "Failed to change to root directory.".to_string()
-}
-
-fn list_files(_arg: &str) -> String {
-    match fs::read_dir(".") {
-        Ok(entries) => {
-            let files: Vec<String> = entries
-                .filter_map(|entry| entry.ok().map(|e| e.file_name().to_string_lossy().into_owned()))
-                .collect();
-            if let Err(e) = speak_to_speaker("The files in the current directory are listed.") {
-                return format!("Error: {}", e);
-            }
-            files.join("\n")
-        }
-        Err(_) => "Failed to read directory.".to_string(),
-    }
+    tr!("root-directory-failed")
 }
 
 fn go_my_directory(_arg: &str) -> String {
@@ -201,76 +400,71 @@ fn go_my_directory(_arg: &str) -> String {
         let my_dir = home.join(MY_DIRECTORY);
        
         if fs::create_dir_all(&my_dir).is_err() {
-            return "Failed to create your personal directory.".to_string();
+            return tr!("my-directory-create-failed");
         }
 
         if std::env::set_current_dir(&my_dir).is_ok() {
-            let message = format!("You are now in your personal directory: {}", my_dir.display());
+            let message = tr!("my-directory", path = my_dir.display());
             if let Err(e) = speak_to_speaker(&message) {
                 return format!("Error: {}", e);
             }
             return message;
         }
     }
-    "Failed to navigate to your personal directory.".to_string()
+    tr!("my-directory-failed")
 }
 
 fn current_directory(_arg: &str) -> String {
     match std::env::current_dir() {
         Ok(path) => {
-            if let Err(e) = speak_to_speaker(&format!("You are in {}", path.display())) {
+            let message = tr!("current-directory", path = path.display());
+            if let Err(e) = speak_to_speaker(&message) {
                 return format!("Error: {}", e);
             }
-            format!("You are in {}", path.display())
+            message
         },
-        Err(_) => "Failed to get current directory.".to_string(),
+        Err(_) => tr!("current-directory-failed"),
     }
 }
 
 fn disk_usage(_arg: &str) -> String {
-    let mut sys = System::new_all();
-    sys.refresh_disks_list();
-    let disk_info: Vec<String> = sys.disks()
+    let filesystems = storage::filesystems(false);
+    let disk_info: Vec<String> = filesystems
         .iter()
-        .map(|disk| {
-            let total = disk.total_space() / (1024 * 1024 * 1024);
-            let used = (disk.total_space() - disk.available_space()) / (1024 * 1024 * 1024);
-            let free = disk.available_space() / (1024 * 1024 * 1024);
-            format!(
-                "Disk {}: Total: {} GB, Used: {} GB, Free: {} GB",
-                disk.mount_point().display(), total, used, free
+        .map(|fs| {
+            tr!(
+                "disk-usage-line",
+                mount = fs.mount_point,
+                total = format_bytes(fs.total),
+                used = format_bytes(fs.used),
+                free = format_bytes(fs.available)
             )
         })
         .collect();
+    attach_data(serde_json::json!({ "disks": filesystems }));
    
     let result = disk_info.join("\n");
-    if let Err(e) = speak_to_speaker("Here is the disk usage report.") {
+    if let Err(e) = speak_to_speaker(&tr!("disk-usage-intro")) {
         return format!("Error: {}", e);
     }
     result
 }
 
 fn memory_usage(_arg: &str) -> String {
-    let mut sys = System::new_all();
-    sys.refresh_memory();
-    let total_memory = sys.total_memory() / (1024 * 1024);
-    let used_memory = sys.used_memory() / (1024 * 1024);
-    let free_memory = sys.free_memory() / (1024 * 1024);
-    let result = format!(
-        "Memory: Total: {} MB, Used: {} MB, Free: {} MB",
-        total_memory, used_memory, free_memory
-    );
-    if let Err(e) = speak_to_speaker("Here is the memory usage report.") {
+    let snapshot = metrics::snapshot();
+    let total_memory = snapshot.memory_total / (1024 * 1024);
+    let used_memory = snapshot.memory_used / (1024 * 1024);
+    let free_memory = snapshot.memory_free / (1024 * 1024);
+    let result = tr!("memory-usage", total = total_memory, used = used_memory, free = free_memory);
+    if let Err(e) = speak_to_speaker(&tr!("memory-usage-intro")) {
         return format!("Error: {}", e);
     }
     result
 }
 
 fn free_memory(_arg: &str) -> String {
-    let mut sys = System::new_all();
-    sys.refresh_memory();
-    let free = sys.free_memory() / 1024 / 1024;
-    let result = format!("Free Memory: {} MB", free);
+    let free = metrics::snapshot().memory_free / 1024 / 1024;
+    let result = tr!("free-memory", free = free);
     if let Err(e) = speak_to_speaker(&result) {
         return format!("Error: {}", e);
     }
@@ -278,10 +472,8 @@ fn free_memory(_arg: &str) -> String {
 }
 
 fn swap_memory(_arg: &str) -> String {
-    let mut sys = System::new_all();
-    sys.refresh_memory();
-    let swap = sys.used_swap() / 1024 / 1024;
-    let result = format!("Swap Memory: {} MB", swap);
+    let swap = metrics::snapshot().swap_used / 1024 / 1024;
+    let result = tr!("swap-memory", swap = swap);
     if let Err(e) = speak_to_speaker(&result) {
         return format!("Error: {}", e);
     }
@@ -289,15 +481,17 @@ fn swap_memory(_arg: &str) -> String {
 }
 
 fn cpu_usage(_arg: &str) -> String {
-    let mut sys = System::new_all();
-    sys.refresh_cpu();
-    let cpu_usage: Vec<String> = sys.cpus()
-        .iter()
-        .map(|cpu| format!("CPU {}: {}%", cpu.name(), cpu.cpu_usage()))
-        .collect();
-   
+    let snapshot = metrics::snapshot();
+    let mut cpu_usage = vec![tr!("cpu-total", usage = locale::format_decimal(snapshot.cpu_usage as f64, 1))];
+    cpu_usage.extend(
+        snapshot.cpus
+            .iter()
+            .map(|cpu| format!("CPU {}: {}%", cpu.name, locale::format_decimal(cpu.usage as f64, 1))),
+    );
+    attach_data(serde_json::json!({ "total": snapshot.cpu_usage, "cpus": snapshot.cpus }));
+
     let result = cpu_usage.join("\n");
-    if let Err(e) = speak_to_speaker("Here is the CPU usage report.") {
+    if let Err(e) = speak_to_speaker(&tr!("cpu-usage-intro")) {
         return format!("Error: {}", e);
     }
     result
@@ -319,32 +513,6 @@ fn run_command(command: &str, args: &[&str]) -> String {
     }
 }
 
-fn ps_command(_arg: &str) -> String {
-    let result = if cfg!(target_os = "windows") {
-        run_command("tasklist", &[])
-    } else {
-        run_command("ps", &["aux"])
-    };
-
-    if let Err(e) = speak_to_speaker("Here is the list of running processes.") {
-        return format!("Error: {}", e);
-    }
-    result
-}
-
-fn df_command(_arg: &str) -> String {
-    let result = if cfg!(target_os = "windows") {
-        run_command("wmic", &["logicaldisk", "get", "size,freespace,caption"])
-    } else {
-        run_command("df", &["-h"])
-    };
-
-    if let Err(e) = speak_to_speaker("Here is the disk space information.") {
-        return format!("Error: {}", e);
-    }
-    result
-}
-
 fn list_users(_arg: &str) -> String {
     if cfg!(target_os = "windows") {
         run_command("net", &["user"])
@@ -361,30 +529,6 @@ fn list_services(_arg: &str) -> String {
     }
 }
 
-fn list_ports(_arg: &str) -> String {
-    if cfg!(target_os = "windows") {
-        run_command("netstat", &["-ano"])
-    } else {
-        run_command("netstat", &["-tulnp"])
-    }
-}
-
-fn list_networks(_arg: &str) -> String {
-    if cfg!(target_os = "windows") {
-        run_command("ipconfig", &["/all"])
-    } else {
-        run_command("ifconfig", &[])
-    }
-}
-
-fn list_drives(_arg: &str) -> String {
-    if cfg!(target_os = "windows") {
-        run_command("wmic", &["logicaldisk", "get", "caption"])
-    } else {
-        run_command("lsblk", &["-o", "NAME,MOUNTPOINT"])
-    }
-}
-
 fn list_printers(_arg: &str) -> String {
     if cfg!(target_os = "windows") {
         run_command("wmic", &["printer", "get", "name"])
@@ -393,42 +537,42 @@ fn list_printers(_arg: &str) -> String {
     }
 }
 
-fn list_disks(_arg: &str) -> String {
-    if cfg!(target_os = "windows") {
-        run_command("wmic", &["diskdrive", "get", "caption,size"])
-    } else {
-        run_command("lsblk", &["-o", "NAME,SIZE,TYPE"])
-    }
-}
-
-fn list_folders(_arg: &str) -> String {
-    if cfg!(target_os = "windows") {
-        run_command("cmd", &["/C", "dir /AD /B"])
-    } else {
-        run_command("ls", &["-d", "*/"])
-    }
-}
-
 fn help(_arg: &str) -> String {
     let commands = vec![
         "hello", "who_created_you", "help",
         "current_day", "current_date", "current_time", "show_calendar",
         "go_home", "root_directory", "go_my_directory", "current_directory", "navigate_directories",
-        "list_files", "open_file", "create_file", "delete_file", "create_nano_file", "print_file_content",
+        "list_files", "open_file", "create_file", "delete_file", "move_file_or_folder", "rename_file_or_folder", "create_nano_file", "print_file_content",
         "create_symlink",
         "disk_usage", "memory_usage", "cpu_usage", "swap_memory", "free_memory", "df_command", "ps_command",
         "get_uptime",
-        "list_processes", "list_services", "list_users",
+        "list_services", "list_users",
         "list_ports", "list_networks",
         "list_drives", "list_disks", "list_printers", "list_folders",
         "volume_up", "volume_down",
         "compile_code", "run_code",
         "shutdown", "restart", "logout", "hibernate", "sleep",
-        "command_history"
+        "command_history",
+        "run_macro", "define_macro", "list_macros", "delete_macro", "start_recording", "stop_recording",
+        "list_scripts", "list_plugins",
+        "schedule", "remind_me", "list_scheduled", "cancel_scheduled",
+        "set_timer", "set_alarm", "list_timers", "cancel_timer",
+        "add_event", "list_events", "delete_event", "import_events", "export_events",
+        "day_of", "days_until", "date_of", "time_in",
+        "set_locale", "show_locale",
+        "set_language", "list_languages",
+        "metric_summary", "metric_peak", "disk_trend", "set_metrics_retention",
+        "add_alert", "list_alerts", "delete_alert", "acknowledge_alert", "snooze_alert",
+        "top_processes", "find_process", "process_details", "kill_process",
+        "port_owner", "process_ports", "network_usage", "my_ip_address",
+        "ping", "check_port", "resolve", "trace_route", "set_resolver", "set_network_timeout",
+        "space_usage", "search_content",
+        "find_file", "index_status", "add_index_root", "remove_index_root", "rebuild_index",
+        "copy_file_or_folder"
     ];
 
-    let response = format!("Available commands:\n{}", commands.join("\n"));
-    if let Err(e) = speak_to_speaker("Here are the available commands.") {
+    let response = format!("{}\n{}", tr!("help-header"), commands.join("\n"));
+    if let Err(e) = speak_to_speaker(&tr!("help-intro")) {
         return format!("Error: {}", e);
     }
     response
@@ -442,11 +586,11 @@ fn clean_filename(arg: &str) -> String {
 
 fn open_file(arg: &str) -> String {
     if arg.is_empty() {
-        return "Error: Please provide a file name.".to_string();
+        return tr!("file-name-missing");
     }
     let result = if cfg!(target_os = "windows") {
         Command::new("cmd")
-            .args(&["/C", "start", "", arg])
+            .args(["/C", "start", "", arg])
             .status()
     } else {
         Command::new("xdg-open")
@@ -455,12 +599,13 @@ fn open_file(arg: &str) -> String {
     };
     match result {
         Ok(_) => {
-            if let Err(e) = speak_to_speaker(&format!("Opening {}", arg)) {
+            let message = tr!("opening", name = arg);
+            if let Err(e) = speak_to_speaker(&message) {
                 return format!("Error: {}", e);
             }
-            format!("Opening {}", arg)
+            message
         }
-        Err(e) => format!("Failed to open file: {}", e),
+        Err(e) => tr!("open-failed", error = e),
     }
 }
 
@@ -471,12 +616,12 @@ fn create_file(arg: &str) -> String {
     let cleaned_arg = clean_filename(arg);
     println!("Creating file: {}", cleaned_arg);
     if cleaned_arg.is_empty() {
-        return "Error: Please provide a valid file name.".to_string();
+        return tr!("valid-file-name-missing");
     }
 
     match fs::File::create(&cleaned_arg) {
-        Ok(_) => format!("File '{}' created successfully.", cleaned_arg),
-        Err(e) => format!("Failed to create file '{}': {}", cleaned_arg, e),
+        Ok(_) => tr!("file-created", name = cleaned_arg),
+        Err(e) => tr!("file-create-failed", name = cleaned_arg, error = e),
     }
 }
 
@@ -485,201 +630,178 @@ fn delete_file(arg: &str) -> String {
     let cleaned_arg = clean_filename(arg);
 
     if cleaned_arg.is_empty() {
-        return "Error: Please provide a valid file name.".to_string();
+        return tr!("valid-file-name-missing");
     }
 
     match fs::remove_file(&cleaned_arg) {
-        Ok(_) => format!("File '{}' deleted successfully.", cleaned_arg),
-        Err(e) => format!("Failed to delete file '{}': {}", cleaned_arg, e),
+        Ok(_) => tr!("file-deleted", name = cleaned_arg),
+        Err(e) => tr!("file-delete-failed", name = cleaned_arg, error = e),
     }
 }
 
 fn move_file_or_folder(args: &str) -> String {
     let parts: Vec<&str> = args.splitn(2, ' ').collect();
     if parts.len() < 2 {
-        return "Error: Please provide source and destination.".to_string();
+        return tr!("move-args-missing");
     }
 
     let source = clean_filename(parts[0]);
     let destination = clean_filename(parts[1]);
 
     if !Path::new(&source).exists() {
-        return format!("Error: Source '{}' does not exist.", source);
+        return tr!("move-source-missing", source = source);
     }
 
     match fs::rename(&source, &destination) {
-        Ok(_) => format!("Moved '{}' to '{}'.", source, destination),
-        Err(e) => format!("Failed to move '{}': {}", source, e),
+        Ok(_) => tr!("moved", source = source, destination = destination),
+        Err(e) => tr!("move-failed", source = source, error = e),
     }
 }
 
 fn rename_file_or_folder(args: &str) -> String {
     let parts: Vec<&str> = args.splitn(2, ' ').collect();
     if parts.len() != 2 {
-        return "Error: Please provide both old and new file names.".to_string();
+        return tr!("rename-args-missing");
     }
 
     let old_name = clean_filename(parts[0]);
     let new_name = clean_filename(parts[1]);
 
     match fs::rename(&old_name, &new_name) {
-        Ok(_) => format!("Renamed: {} -> {}", old_name, new_name),
-        Err(e) => format!("Failed to rename: {}", e),
+        Ok(_) => tr!("renamed", old = old_name, new = new_name),
+        Err(e) => tr!("rename-failed", error = e),
     }
 }
-fn search_file_or_folder(arg: &str) -> String {
-    let cleaned_arg = clean_filename(arg);
-    
-    fn search_recursive(path: &Path, target: &str) -> Option<String> {
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                if entry.file_name().to_string_lossy().contains(target) {
-                    return Some(entry_path.display().to_string());
-                }
-                if entry_path.is_dir() {
-                    if let Some(found) = search_recursive(&entry_path, target) {
-                        return Some(found);
-                    }
-                }
-            }
-        }
-        None
-    }
-
-    match search_recursive(Path::new("."), &cleaned_arg) {
-        Some(found) => format!("Found: {}", found),
-        None => format!("'{}' not found.", cleaned_arg),
-    }
-}
-
 fn shutdown(_arg: &str) -> String {
-    let confirmation = "Are you sure you want to shut down the PC? (Assuming yes for now)";
-    if let Err(e) = speak_to_speaker(confirmation) {
+    if let Err(e) = speak_to_speaker(&tr!("shutdown-confirm")) {
         return format!("Error: {}", e);
     }
-    if let Err(e) = speak_to_speaker("Shutting down the PC.") {
+    if let Err(e) = speak_to_speaker(&tr!("shutting-down")) {
         return format!("Error: {}", e);
     }
     let status = if cfg!(target_os = "windows") {
-        Command::new("shutdown").args(&["/s", "/t", "0"]).status()
+        Command::new("shutdown").args(["/s", "/t", "0"]).status()
     } else {
-        Command::new("shutdown").args(&["-h", "now"]).status()
+        Command::new("shutdown").args(["-h", "now"]).status()
     };
     match status {
-        Ok(_) => "Shutdown command executed.".to_string(),
-        Err(e) => format!("Failed to execute shutdown: {}", e),
+        Ok(_) => tr!("shutdown-done"),
+        Err(e) => tr!("shutdown-failed", error = e),
     }
 }
 
 fn restart(_arg: &str) -> String {
-    if let Err(e) = speak_to_speaker("Restarting the system.") {
+    if let Err(e) = speak_to_speaker(&tr!("restarting")) {
         return format!("Error: {}", e);
     }
     let status = if cfg!(target_os = "windows") {
-        Command::new("shutdown").args(&["/r", "/t", "0"]).status()
+        Command::new("shutdown").args(["/r", "/t", "0"]).status()
     } else {
-        Command::new("shutdown").args(&["-r", "now"]).status()
+        Command::new("shutdown").args(["-r", "now"]).status()
     };
     match status {
-        Ok(_) => "Restart command executed.".to_string(),
-        Err(e) => format!("Failed to restart: {}", e),
+        Ok(_) => tr!("restart-done"),
+        Err(e) => tr!("restart-failed", error = e),
     }
 }
 
 fn logout(_arg: &str) -> String {
-    if let Err(e) = speak_to_speaker("Logging out.") {
+    if let Err(e) = speak_to_speaker(&tr!("logging-out")) {
         return format!("Error: {}", e);
     }
     let status = if cfg!(target_os = "windows") {
-        Command::new("shutdown").args(&["/l"]).status()
+        Command::new("shutdown").args(["/l"]).status()
     } else {
         Command::new("pkill").arg("-KILL").arg("-u").arg(whoami::username()).status()
     };
     match status {
-        Ok(_) => "Logout command executed.".to_string(),
-        Err(e) => format!("Failed to log out: {}", e),
+        Ok(_) => tr!("logout-done"),
+        Err(e) => tr!("logout-failed", error = e),
     }
 }
 
 fn hibernate(_arg: &str) -> String {
-    if let Err(e) = speak_to_speaker("Hibernating system.") {
+    if let Err(e) = speak_to_speaker(&tr!("hibernating")) {
         return format!("Error: {}", e);
     }
     let status = if cfg!(target_os = "windows") {
-        Command::new("shutdown").args(&["/h"]).status()
+        Command::new("shutdown").args(["/h"]).status()
     } else {
         Command::new("systemctl").arg("hibernate").status()
     };
     match status {
-        Ok(_) => "Hibernate command executed.".to_string(),
-        Err(e) => format!("Failed to hibernate: {}", e),
+        Ok(_) => tr!("hibernate-done"),
+        Err(e) => tr!("hibernate-failed", error = e),
     }
 }
 
 fn sleep(_arg: &str) -> String {
-    if let Err(e) = speak_to_speaker("Putting system to sleep.") {
+    if let Err(e) = speak_to_speaker(&tr!("sleeping")) {
         return format!("Error: {}", e);
     }
     let status = if cfg!(target_os = "windows") {
-        Command::new("rundll32.exe").args(&["powrprof.dll,SetSuspendState", "0", "1", "0"]).status()
+        Command::new("rundll32.exe").args(["powrprof.dll,SetSuspendState", "0", "1", "0"]).status()
     } else {
         Command::new("systemctl").arg("suspend").status()
     };
     match status {
-        Ok(_) => "Sleep command executed.".to_string(),
-        Err(e) => format!("Failed to sleep: {}", e),
+        Ok(_) => tr!("sleep-done"),
+        Err(e) => tr!("sleep-failed", error = e),
     }
 }
 
 fn volume_up(_arg: &str) -> String {
     if cfg!(target_os = "windows") {
         match Command::new("powershell")
-            .args(&["-Command", "(New-Object -ComObject WScript.Shell).SendKeys([char]175)"])
+            .args(["-Command", "(New-Object -ComObject WScript.Shell).SendKeys([char]175)"])
             .status()
         {
             Ok(_) => {
-                if let Err(e) = speak_to_speaker("Volume increased.") {
+                let message = tr!("volume-up");
+                if let Err(e) = speak_to_speaker(&message) {
                     return format!("Error: {}", e);
                 }
-                "Volume increased.".to_string()
+                message
             }
-            Err(e) => format!("Failed to increase volume: {}", e),
+            Err(e) => tr!("volume-up-failed", error = e),
         }
     } else {
-        if let Err(e) = speak_to_speaker("Volume control not supported on this OS yet.") {
+        let message = tr!("volume-unsupported");
+        if let Err(e) = speak_to_speaker(&message) {
             return format!("Error: {}", e);
         }
-        "Volume control not supported on this OS yet.".to_string()
+        message
     }
 }
 
 fn volume_down(_arg: &str) -> String {
     if cfg!(target_os = "windows") {
         match Command::new("powershell")
-            .args(&["-Command", "(New-Object -ComObject WScript.Shell).SendKeys([char]174)"])
+            .args(["-Command", "(New-Object -ComObject WScript.Shell).SendKeys([char]174)"])
             .status()
         {
             Ok(_) => {
-                if let Err(e) = speak_to_speaker("Volume decreased.") {
+                let message = tr!("volume-down");
+                if let Err(e) = speak_to_speaker(&message) {
                     return format!("Error: {}", e);
                 }
-                "Volume decreased.".to_string()
+                message
             }
-            Err(e) => format!("Failed to decrease volume: {}", e),
+            Err(e) => tr!("volume-down-failed", error = e),
         }
     } else {
-        if let Err(e) = speak_to_speaker("Volume control not supported on this OS yet.") {
+        let message = tr!("volume-unsupported");
+        if let Err(e) = speak_to_speaker(&message) {
             return format!("Error: {}", e);
         }
-        "Volume control not supported on this OS yet.".to_string()
+        message
     }
 }
 
 fn compile_code(arg: &str) -> String {
     let filename = clean_filename(arg);
     if filename.is_empty() {
-        return "Error: Please provide a file name to compile.".to_string();
+        return tr!("compile-file-missing");
     }
 
     let output_name = filename.trim_end_matches(".c").trim_end_matches(".cpp"); // Get name without extension
@@ -687,24 +809,24 @@ fn compile_code(arg: &str) -> String {
     let output = if filename.ends_with(".rs") {
         Command::new("rustc").arg(&filename).output()
     } else if filename.ends_with(".c") {
-        Command::new("gcc").args(&[&filename, "-o", output_name]).output()
+        Command::new("gcc").args([&filename, "-o", output_name]).output()
     } else if filename.ends_with(".cpp") {
-        Command::new("g++").args(&[&filename, "-o", output_name]).output()
+        Command::new("g++").args([&filename, "-o", output_name]).output()
     } else if filename.ends_with(".py") {
-        Command::new("python3").args(&["-m", "py_compile", &filename]).output()
+        Command::new("python3").args(["-m", "py_compile", &filename]).output()
     } else {
-        return "Error: Unsupported file format.".to_string();
+        return tr!("unsupported-format");
     };
 
     match output {
         Ok(output) => {
             if output.status.success() {
-                format!("Compilation successful. Executable: {}", output_name)
+                tr!("compile-ok", name = output_name)
             } else {
-                format!("Compilation failed: {}", String::from_utf8_lossy(&output.stderr))
+                tr!("compile-failed", error = String::from_utf8_lossy(&output.stderr))
             }
         }
-        Err(e) => format!("Failed to compile: {}", e),
+        Err(e) => tr!("compile-error", error = e),
     }
 }
 
@@ -713,22 +835,20 @@ fn compile_code(arg: &str) -> String {
 fn print_file_content(arg: &str) -> String {
     let filename = clean_filename(arg);
     if filename.is_empty() {
-        return "Error: Please provide a file name.".to_string();
+        return tr!("file-name-missing");
     }
     
     match fs::File::open(&filename) {
         Ok(file) => {
             let reader = BufReader::new(file);
             let mut content = String::new();
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    content.push_str(&line);
-                    content.push('\n');
-                }
+            for line in reader.lines().map_while(Result::ok) {
+                content.push_str(&line);
+                content.push('\n');
             }
             content
         }
-        Err(e) => format!("Failed to open file '{}': {}", filename, e),
+        Err(e) => tr!("file-read-failed", name = filename, error = e),
     }
 }
 
@@ -747,12 +867,12 @@ fn run_code(arg: &str) -> String {
     match output {
         Ok(output) => {
             if output.status.success() {
-                format!("Output:\n{}", String::from_utf8_lossy(&output.stdout))
+                format!("{}\n{}", tr!("run-output"), String::from_utf8_lossy(&output.stdout))
             } else {
-                format!("Execution failed: {}", String::from_utf8_lossy(&output.stderr))
+                tr!("run-failed", error = String::from_utf8_lossy(&output.stderr))
             }
         }
-        Err(e) => format!("Error running the program: {}", e),
+        Err(e) => tr!("run-error", error = e),
     }
 }
 
@@ -761,43 +881,42 @@ fn run_code(arg: &str) -> String {
 fn create_symlink(arg: &str) -> String {
     let args: Vec<&str> = arg.split_whitespace().collect();
     if args.len() < 2 {
-        return "Error: Please provide source and target (e.g., 'source target').".to_string();
+        return tr!("symlink-args-missing");
     }
     let source = args[0];
     let target = args[1];
     let status = if cfg!(target_os = "windows") {
         Command::new("cmd")
-            .args(&["/C", "mklink", target, source])
+            .args(["/C", "mklink", target, source])
             .status()
     } else {
         Command::new("ln")
-            .args(&["-s", source, target])
+            .args(["-s", source, target])
             .status()
     };
     match status {
         Ok(_) => {
-            if let Err(e) = speak_to_speaker(&format!("Created symlink from {} to {}", source, target)) {
+            let message = tr!("symlink-created", source = source, target = target);
+            if let Err(e) = speak_to_speaker(&message) {
                 return format!("Error: {}", e);
             }
-            format!("Created symlink from {} to {}", source, target)
+            message
         }
-        Err(e) => format!("Failed to create symlink: {}", e),
+        Err(e) => tr!("symlink-failed", error = e),
     }
 }
 
 fn navigate_directories(_arg: &str) -> String {
-    "Error: Interactive navigation not supported via API yet.".to_string() // Placeholder
+    tr!("navigate-unsupported") // Placeholder
 }
 
 fn get_uptime(_arg: &str) -> String {
-    let mut sys = System::new_all();
-    sys.refresh_system();
-    let uptime = sys.uptime();
+    let uptime = metrics::snapshot().uptime;
     let duration = Duration::seconds(uptime as i64);
     let hours = duration.num_hours();
     let minutes = duration.num_minutes() % 60;
     let seconds = duration.num_seconds() % 60;
-    let result = format!("Uptime: {}h {}m {}s", hours, minutes, seconds);
+    let result = tr!("uptime", hours = hours, minutes = minutes, seconds = seconds);
     if let Err(e) = speak_to_speaker(&result) {
         return format!("Error: {}", e);
     }
@@ -807,25 +926,27 @@ fn get_uptime(_arg: &str) -> String {
 fn command_history(_arg: &str) -> String {
     match std::fs::read_to_string("history.txt") {
         Ok(history) => {
-            if let Err(e) = speak_to_speaker("Command history displayed.") {
+            if let Err(e) = speak_to_speaker(&tr!("history-shown")) {
                 return format!("Error: {}", e);
             }
             history
         }
         Err(_) => {
-            if let Err(e) = speak_to_speaker("No command history found.") {
+            let message = tr!("history-missing");
+            if let Err(e) = speak_to_speaker(&message) {
                 return format!("Error: {}", e);
             }
-            "No command history found.".to_string()
+            message
         }
     }
 }
 
 fn exit(_arg: &str) -> String {
-    if let Err(e) = speak_to_speaker("Goodbye!") {
+    let message = tr!("goodbye");
+    if let Err(e) = speak_to_speaker(&message) {
         return format!("Error: {}", e);
     }
-    println!("Goodbye!"); // Display message in terminal
+    println!("{}", message); // Display message in terminal
 
     // Send a shutdown signal before terminating
     std::thread::sleep(std::time::Duration::from_secs(1));
@@ -839,7 +960,7 @@ async fn main() {
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let mut commands: HashMap<String, Box<dyn Fn(&str) -> String + Send + Sync>> = HashMap::new();
+    let mut commands: CommandMap = HashMap::new();
     commands.insert("hello".to_string(), Box::new(hello));
     commands.insert("who_created_you".to_string(), Box::new(who_created_you));
     commands.insert("current_day".to_string(), Box::new(current_day));
@@ -848,7 +969,7 @@ async fn main() {
     commands.insert("show_calendar".to_string(), Box::new(show_calendar));
     commands.insert("go_home".to_string(), Box::new(go_home));
     commands.insert("root_directory".to_string(), Box::new(root_directory));
-    commands.insert("list_files".to_string(), Box::new(list_files));
+    commands.insert("list_files".to_string(), Box::new(files::list_files));
     commands.insert("current_directory".to_string(), Box::new(current_directory));
     commands.insert("go_my_directory".to_string(), Box::new(go_my_directory));
     commands.insert("disk_usage".to_string(), Box::new(disk_usage));
@@ -856,8 +977,8 @@ async fn main() {
     commands.insert("swap_memory".to_string(), Box::new(swap_memory));
     commands.insert("free_memory".to_string(), Box::new(free_memory));
     commands.insert("cpu_usage".to_string(), Box::new(cpu_usage));
-    commands.insert("ps_command".to_string(), Box::new(ps_command));
-    commands.insert("df_command".to_string(), Box::new(df_command));
+    commands.insert("ps_command".to_string(), Box::new(processes::ps_command));
+    commands.insert("df_command".to_string(), Box::new(storage::df_command));
     commands.insert("shutdown".to_string(), Box::new(shutdown));
     commands.insert("restart".to_string(), Box::new(restart));
     commands.insert("logout".to_string(), Box::new(logout));
@@ -866,18 +987,20 @@ async fn main() {
     commands.insert("help".to_string(), Box::new(help));
     commands.insert("list_users".to_string(), Box::new(list_users));
     commands.insert("list_services".to_string(), Box::new(list_services));
-    commands.insert("list_ports".to_string(), Box::new(list_ports));
-    commands.insert("list_networks".to_string(), Box::new(list_networks));
-    commands.insert("list_drives".to_string(), Box::new(list_drives));
+    commands.insert("list_ports".to_string(), Box::new(ports::list_ports));
+    commands.insert("list_networks".to_string(), Box::new(network::list_networks));
+    commands.insert("list_drives".to_string(), Box::new(storage::list_drives));
     commands.insert("list_printers".to_string(), Box::new(list_printers));
-    commands.insert("list_disks".to_string(), Box::new(list_disks));
-    commands.insert("list_folders".to_string(), Box::new(list_folders));
+    commands.insert("list_disks".to_string(), Box::new(storage::list_disks));
+    commands.insert("list_folders".to_string(), Box::new(files::list_folders));
     commands.insert("open_file".to_string(), Box::new(open_file));
     commands.insert("create_file".to_string(), Box::new(create_file));
     commands.insert("delete_file".to_string(), Box::new(delete_file));
+    commands.insert("move_file_or_folder".to_string(), Box::new(move_file_or_folder));
+    commands.insert("rename_file_or_folder".to_string(), Box::new(rename_file_or_folder));
     commands.insert("print_file_content".to_string(), Box::new(print_file_content));
     commands.insert("create_symlink".to_string(), Box::new(create_symlink));
-    commands.insert("search_file_or_folder".to_string(), Box::new(search_file_or_folder));
+    commands.insert("search_file_or_folder".to_string(), Box::new(search::search_file_or_folder));
     commands.insert("volume_up".to_string(), Box::new(volume_up));
     commands.insert("volume_down".to_string(), Box::new(volume_down));
     commands.insert("compile_code".to_string(), Box::new(compile_code));
@@ -886,12 +1009,79 @@ async fn main() {
     commands.insert("get_uptime".to_string(), Box::new(get_uptime));
     commands.insert("command_history".to_string(), Box::new(command_history));
     commands.insert("exit".to_string(), Box::new(exit));
+    commands.insert("list_macros".to_string(), Box::new(macros::list_macros));
+    commands.insert("delete_macro".to_string(), Box::new(macros::delete_macro));
+    commands.insert("start_recording".to_string(), Box::new(macros::start_recording));
+    commands.insert("stop_recording".to_string(), Box::new(macros::stop_recording));
+    commands.insert("list_scripts".to_string(), Box::new(scripting::list_scripts));
+    commands.insert("list_plugins".to_string(), Box::new(plugins::list_plugins));
+    commands.insert("remind_me".to_string(), Box::new(scheduler::remind_me));
+    commands.insert("list_scheduled".to_string(), Box::new(scheduler::list_scheduled));
+    commands.insert("cancel_scheduled".to_string(), Box::new(scheduler::cancel_scheduled));
+    commands.insert("set_timer".to_string(), Box::new(timers::set_timer));
+    commands.insert("set_alarm".to_string(), Box::new(timers::set_alarm));
+    commands.insert("list_timers".to_string(), Box::new(timers::list_timers));
+    commands.insert("cancel_timer".to_string(), Box::new(timers::cancel_timer));
+    commands.insert("add_event".to_string(), Box::new(agenda::add_event));
+    commands.insert("list_events".to_string(), Box::new(agenda::list_events));
+    commands.insert("delete_event".to_string(), Box::new(agenda::delete_event));
+    commands.insert("import_events".to_string(), Box::new(agenda::import_events));
+    commands.insert("export_events".to_string(), Box::new(agenda::export_events));
+    commands.insert("day_of".to_string(), Box::new(dates::day_of));
+    commands.insert("days_until".to_string(), Box::new(dates::days_until));
+    commands.insert("date_of".to_string(), Box::new(dates::date_of));
+    commands.insert("time_in".to_string(), Box::new(dates::time_in));
+    commands.insert("set_locale".to_string(), Box::new(locale::set_locale));
+    commands.insert("show_locale".to_string(), Box::new(locale::show_locale));
+    commands.insert("set_language".to_string(), Box::new(i18n::set_language));
+    commands.insert("list_languages".to_string(), Box::new(i18n::list_languages));
+    commands.insert("metric_summary".to_string(), Box::new(trends::metric_summary));
+    commands.insert("metric_peak".to_string(), Box::new(trends::metric_peak));
+    commands.insert("disk_trend".to_string(), Box::new(trends::disk_trend));
+    commands.insert("set_metrics_retention".to_string(), Box::new(trends::set_metrics_retention));
+    commands.insert("add_alert".to_string(), Box::new(alerts::add_alert));
+    commands.insert("list_alerts".to_string(), Box::new(alerts::list_alerts));
+    commands.insert("delete_alert".to_string(), Box::new(alerts::delete_alert));
+    commands.insert("acknowledge_alert".to_string(), Box::new(alerts::acknowledge_alert));
+    commands.insert("snooze_alert".to_string(), Box::new(alerts::snooze_alert));
+    commands.insert("top_processes".to_string(), Box::new(processes::top_processes));
+    commands.insert("find_process".to_string(), Box::new(processes::find_process));
+    commands.insert("process_details".to_string(), Box::new(processes::process_details));
+    commands.insert("kill_process".to_string(), Box::new(processes::kill_process));
+    commands.insert("port_owner".to_string(), Box::new(ports::port_owner));
+    commands.insert("process_ports".to_string(), Box::new(ports::process_ports));
+    commands.insert("network_usage".to_string(), Box::new(network::network_usage));
+    commands.insert("my_ip_address".to_string(), Box::new(network::my_ip_address));
+    commands.insert("ping".to_string(), Box::new(diagnostics::ping));
+    commands.insert("check_port".to_string(), Box::new(diagnostics::check_port));
+    commands.insert("resolve".to_string(), Box::new(diagnostics::resolve));
+    commands.insert("trace_route".to_string(), Box::new(diagnostics::trace_route));
+    commands.insert("set_resolver".to_string(), Box::new(diagnostics::set_resolver));
+    commands.insert("set_network_timeout".to_string(), Box::new(diagnostics::set_network_timeout));
+    commands.insert("space_usage".to_string(), Box::new(space::space_usage));
+    commands.insert("search_content".to_string(), Box::new(search::search_content));
+    commands.insert("find_file".to_string(), Box::new(index::find_file));
+    commands.insert("index_status".to_string(), Box::new(index::index_status));
+    commands.insert("add_index_root".to_string(), Box::new(index::add_index_root));
+    commands.insert("remove_index_root".to_string(), Box::new(index::remove_index_root));
+    commands.insert("rebuild_index".to_string(), Box::new(index::rebuild_index));
+    commands.insert("copy_file_or_folder".to_string(), Box::new(copy::copy_file_or_folder));
+    scripting::load_scripts(&mut commands);
+    plugins::load_plugins(&mut commands);
+
+    let commands = Arc::new(commands);
+    scheduler::start(Arc::clone(&commands));
+    timers::start();
+    metrics::start();
+    index::start();
 
     let app = Router::new()
         .route("/backend", get(|| async { "Hello from Rust backend!" }))
         .route("/command", post(handle_command_request))
+        .route("/events", get(events::stream_events))
+        .route("/metrics", get(telemetry::serve_metrics))
         .layer(cors)
-        .with_state(Arc::new(commands));
+        .with_state(commands);
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 8000));
     println!("🚀 Backend running at http://{}", addr);
@@ -0,0 +1,403 @@
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use walkdir::WalkDir;
+
+use crate::{attach_data, clean_filename, events, format_bytes, i18n::tr, paths, speak_to_speaker};
+
+/// Progress events are sent at most this often while a copy runs.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What to do when a file already exists at the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Overwrite {
+    Skip,
+    Replace,
+    /// Replace only when the source was modified more recently.
+    Newer,
+    /// Copy under a new name such as "report (1).pdf".
+    KeepBoth,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Tally {
+    files: u64,
+    bytes: u64,
+    skipped: u64,
+    failed: u64,
+    total_files: u64,
+    total_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_error: Option<String>,
+}
+
+impl Tally {
+    fn fail(&mut self, path: &Path, error: io::Error) {
+        self.failed += 1;
+        self.first_error.get_or_insert_with(|| format!("{}: {}", path.display(), error));
+    }
+
+    fn percent(&self) -> u64 {
+        match self.total_bytes {
+            0 if self.total_files == 0 => 100,
+            0 => (self.files + self.skipped) * 100 / self.total_files,
+            total => self.bytes * 100 / total,
+        }
+    }
+}
+
+struct Request {
+    source: PathBuf,
+    destination: PathBuf,
+    policy: Overwrite,
+}
+
+fn policy_word(word: &str) -> Option<Option<Overwrite>> {
+    match word.to_lowercase().as_str() {
+        "overwrite" | "overwriting" | "replace" | "replacing" => Some(Some(Overwrite::Replace)),
+        "newer" | "update" | "updating" => Some(Some(Overwrite::Newer)),
+        "both" | "rename" | "renaming" => Some(Some(Overwrite::KeepBoth)),
+        "skip" | "skipping" => Some(Some(Overwrite::Skip)),
+        "existing" | "keep" | "keeping" | "if" | "only" | "and" => Some(None),
+        _ => None,
+    }
+}
+
+/// Splits "notes to backup overwrite" into source, destination and policy.
+/// Policy words only count after the destination, so they are only looked
+/// for when a "to" or "into" comes before them: "notes to update" copies
+/// to a folder called update.
+fn parse_request(arg: &str) -> Option<Request> {
+    let mut words: Vec<&str> = arg.split_whitespace().collect();
+    let mut policy = Overwrite::Skip;
+    let separator = words.iter().rposition(|w| matches!(w.to_lowercase().as_str(), "to" | "into"));
+    if let Some(separator) = separator {
+        // One word after the separator always stays, as the destination.
+        while words.len() > separator + 2 {
+            let Some(meaning) = policy_word(words[words.len() - 1]) else {
+                break;
+            };
+            if let Some(chosen) = meaning {
+                policy = chosen;
+            }
+            words.pop();
+        }
+    }
+
+    let rest = words.join(" ");
+    let (source, destination) = [" to ", " into "]
+        .iter()
+        .find_map(|separator| rest.rsplit_once(separator))
+        .or_else(|| rest.split_once(' '))?;
+    let source = PathBuf::from(clean_filename(source.trim()));
+    let destination = match paths::place(destination) {
+        (Some(place), rest) if rest.is_empty() => place,
+        _ => PathBuf::from(clean_filename(destination.trim())),
+    };
+    if source.as_os_str().is_empty() || destination.as_os_str().is_empty() {
+        return None;
+    }
+    Some(Request { source, destination, policy })
+}
+
+/// "report.pdf" becomes "report (1).pdf", then "report (2).pdf", and so on.
+fn free_name(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap()
+}
+
+/// Copies the source's modification and access times onto `path`. Setting
+/// times only needs a handle, so a write-only copy is opened for writing and
+/// a read-only one, or a folder, for reading.
+fn copy_times(path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    let mut times = fs::FileTimes::new();
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    if let Ok(modified) = metadata.modified() {
+        times = times.set_modified(modified);
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .or_else(|_| fs::File::open(path))?
+        .set_times(times)
+}
+
+fn totals(source: &Path) -> (u64, u64) {
+    WalkDir::new(source)
+        .into_iter()
+        .flatten()
+        .filter(|entry| !entry.file_type().is_dir())
+        .fold((0, 0), |(files, bytes), entry| {
+            (files + 1, bytes + entry.metadata().map(|m| m.len()).unwrap_or(0))
+        })
+}
+
+fn publish_progress(tally: &Tally, source: &Path, target: &Path) {
+    events::publish(
+        "copy_progress",
+        &format!("Copied {} of {} files", tally.files, tally.total_files),
+        json!({ "source": source, "destination": target, "percent": tally.percent(), "progress": tally }),
+    );
+}
+
+/// Recreates the symlink at `from` as `to`, pointing at the same target.
+#[cfg(unix)]
+fn copy_link(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_link(_from: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "symlinks can only be copied on Unix"))
+}
+
+/// Copies one file or symlink, honouring the overwrite policy.
+/// Returns the number of bytes written, or `None` when it was skipped.
+fn copy_entry(from: &Path, to: &Path, metadata: &fs::Metadata, policy: Overwrite) -> io::Result<Option<u64>> {
+    let mut to = to.to_path_buf();
+    if let Ok(existing) = fs::symlink_metadata(&to) {
+        match policy {
+            Overwrite::Skip => return Ok(None),
+            Overwrite::Newer if existing.modified().ok() >= metadata.modified().ok() => return Ok(None),
+            Overwrite::KeepBoth => to = free_name(&to),
+            Overwrite::Newer | Overwrite::Replace if existing.file_type().is_symlink() || metadata.file_type().is_symlink() => {
+                fs::remove_file(&to)?
+            }
+            Overwrite::Newer | Overwrite::Replace => {}
+        }
+    }
+    if metadata.file_type().is_symlink() {
+        copy_link(from, &to)?;
+        return Ok(Some(0));
+    }
+    // `fs::copy` carries the permission bits over with the contents.
+    let bytes = fs::copy(from, &to)?;
+    copy_times(&to, metadata)?;
+    Ok(Some(bytes))
+}
+
+fn copy_tree(source: &Path, target: &Path, policy: Overwrite, tally: &mut Tally) {
+    let mut folders = Vec::new();
+    let mut reported = Instant::now();
+    for entry in WalkDir::new(source).follow_links(false) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e.path().unwrap_or(source).to_path_buf();
+                tally.fail(&path, e.into());
+                continue;
+            }
+        };
+        let Ok(relative) = entry.path().strip_prefix(source) else {
+            continue;
+        };
+        let to = target.join(relative);
+        let metadata = match entry.path().symlink_metadata() {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                tally.fail(entry.path(), e);
+                continue;
+            }
+        };
+
+        if metadata.is_dir() {
+            match fs::create_dir_all(&to) {
+                Ok(()) => folders.push((to, metadata)),
+                Err(e) => tally.fail(&to, e),
+            }
+            continue;
+        }
+        match copy_entry(entry.path(), &to, &metadata, policy) {
+            Ok(Some(bytes)) => {
+                tally.files += 1;
+                tally.bytes += bytes;
+            }
+            Ok(None) => tally.skipped += 1,
+            Err(e) => tally.fail(entry.path(), e),
+        }
+        if reported.elapsed() >= PROGRESS_INTERVAL {
+            publish_progress(tally, source, target);
+            reported = Instant::now();
+        }
+    }
+
+    // Folder permissions and times go on last, deepest first: copying into a
+    // folder would change its time, and a read-only one would refuse the files.
+    for (folder, metadata) in folders.iter().rev() {
+        if let Err(e) = fs::set_permissions(folder, metadata.permissions()).and_then(|_| copy_times(folder, metadata)) {
+            tally.fail(folder, e);
+        }
+    }
+}
+
+/// `copy_file_or_folder report.pdf to backup [overwrite|newer|keep both]`:
+/// copies a file or a whole folder, keeping permissions and times. Existing
+/// files are skipped unless an overwrite policy is given. Large copies report
+/// progress on `/events`.
+pub fn copy_file_or_folder(arg: &str) -> String {
+    let Some(Request { source, destination, policy }) = parse_request(arg) else {
+        return "Error: Please provide source and destination.".to_string();
+    };
+    let Ok(metadata) = fs::symlink_metadata(&source) else {
+        return format!("Error: Source '{}' does not exist.", source.display());
+    };
+
+    // Like cp, copying onto an existing folder puts the source inside it.
+    let mut target = match (destination.is_dir(), source.file_name()) {
+        (true, Some(name)) => destination.join(name),
+        _ => destination.clone(),
+    };
+    let canonical_source = fs::canonicalize(&source).unwrap_or_else(|_| source.clone());
+    let canonical_target = target
+        .parent()
+        .and_then(|parent| fs::canonicalize(parent).ok())
+        .zip(target.file_name())
+        .map(|(parent, name)| parent.join(name))
+        .unwrap_or_else(|| target.clone());
+    if canonical_target == canonical_source {
+        if policy != Overwrite::KeepBoth {
+            return format!("Error: '{}' is already there. Say keep both to make a copy beside it.", source.display());
+        }
+        target = free_name(&target);
+    } else if metadata.is_dir() && canonical_target.starts_with(&canonical_source) {
+        return format!("Error: Can't copy '{}' into itself.", source.display());
+    } else if policy == Overwrite::KeepBoth && fs::symlink_metadata(&target).is_ok() {
+        target = free_name(&target);
+    }
+
+    let (total_files, total_bytes) = totals(&source);
+    let mut tally = Tally { total_files, total_bytes, ..Tally::default() };
+    let started = Instant::now();
+    if metadata.is_dir() {
+        copy_tree(&source, &target, policy, &mut tally);
+    } else {
+        match copy_entry(&source, &target, &metadata, policy) {
+            Ok(Some(bytes)) => {
+                tally.files = 1;
+                tally.bytes = bytes;
+            }
+            Ok(None) => tally.skipped = 1,
+            Err(e) => tally.fail(&source, e),
+        }
+    }
+
+    if tally.files == 0 && tally.skipped == 0 {
+        if let Some(error) = &tally.first_error {
+            return tr!("copy-all-failed", name = source.display().to_string(), error = error);
+        }
+    }
+
+    let name = target.display().to_string();
+    let size = format_bytes(tally.bytes);
+    let mut message = match tally.files {
+        1 => tr!("copy-done-one", size = size, destination = name),
+        files => tr!("copy-done", count = files.to_string(), size = size, destination = name),
+    };
+    if tally.skipped > 0 {
+        message = format!("{} {}", message, tr!("copy-skipped", count = tally.skipped.to_string()));
+    }
+    if tally.failed > 0 {
+        message = format!("{} {}", message, tr!("copy-failed", count = tally.failed.to_string()));
+    }
+    events::publish(
+        "copy_finished",
+        &message,
+        json!({ "source": source, "destination": target, "percent": 100, "progress": tally }),
+    );
+
+    let mut lines = vec![
+        format!("{} -> {}", source.display(), target.display()),
+        format!("Copied: {} files, {}", tally.files, size),
+    ];
+    if tally.skipped > 0 {
+        lines.push(format!("Skipped existing: {}", tally.skipped));
+    }
+    if let Some(error) = &tally.first_error {
+        lines.push(format!("Failed: {} (first: {})", tally.failed, error));
+    }
+    lines.push(format!("Took {:.1} s", started.elapsed().as_secs_f64()));
+    attach_data(json!({ "source": source, "destination": target, "policy": policy, "result": tally }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aurora-copy-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_policies_only_after_the_destination() {
+        let request = parse_request("notes to backup keep both").unwrap();
+        assert_eq!((request.source, request.destination), (PathBuf::from("notes"), PathBuf::from("backup")));
+        assert_eq!(request.policy, Overwrite::KeepBoth);
+
+        let request = parse_request("notes to update").unwrap();
+        assert_eq!(request.destination, PathBuf::from("update"));
+        assert_eq!(request.policy, Overwrite::Skip);
+
+        let request = parse_request("notes update").unwrap();
+        assert_eq!(request.destination, PathBuf::from("update"));
+        assert_eq!(request.policy, Overwrite::Skip);
+
+        let request = parse_request("to do list into archive only if newer").unwrap();
+        assert_eq!(request.source, PathBuf::from("to do list"));
+        assert_eq!(request.destination, PathBuf::from("archive"));
+        assert_eq!(request.policy, Overwrite::Newer);
+
+        let request = parse_request("report.pdf to my home folder overwrite").unwrap();
+        assert_eq!(request.destination, dirs::home_dir().unwrap());
+        assert_eq!(request.policy, Overwrite::Replace);
+        assert!(parse_request("notes").is_none());
+    }
+
+    #[test]
+    fn picks_free_names() {
+        let dir = temp_dir("names");
+        fs::write(dir.join("report.pdf"), "").unwrap();
+        assert_eq!(free_name(&dir.join("report.pdf")), dir.join("report (1).pdf"));
+        fs::write(dir.join("report (1).pdf"), "").unwrap();
+        assert_eq!(free_name(&dir.join("report.pdf")), dir.join("report (2).pdf"));
+        assert_eq!(free_name(&dir.join("notes")), dir.join("notes (1)"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_times_of_write_only_and_read_only_files() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_dir("times");
+        let old = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let source = dir.join("source");
+        fs::write(&source, "source").unwrap();
+        fs::File::options().write(true).open(&source).unwrap().set_modified(old).unwrap();
+        let metadata = fs::symlink_metadata(&source).unwrap();
+        for (name, mode) in [("write-only", 0o200), ("read-only", 0o444)] {
+            let target = dir.join(name);
+            fs::write(&target, name).unwrap();
+            fs::set_permissions(&target, fs::Permissions::from_mode(mode)).unwrap();
+            copy_times(&target, &metadata).unwrap();
+            assert_eq!(fs::symlink_metadata(&target).unwrap().modified().unwrap(), old);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
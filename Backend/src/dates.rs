@@ -0,0 +1,380 @@
+use chrono::{Datelike, Duration, Local, Month, Months, NaiveDate, Offset, Utc, Weekday};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use serde_json::json;
+
+use crate::{attach_data, locale, scheduler::parse_number, speak_to_speaker};
+
+fn parse_day_number(word: &str) -> Option<u32> {
+    let trimmed = word.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == ',');
+    let day = if trimmed.is_empty() {
+        match word {
+            "first" => 1,
+            "second" => 2,
+            "third" => 3,
+            "fourth" => 4,
+            "fifth" => 5,
+            _ => parse_number(word)?,
+        }
+    } else {
+        trimmed.parse().ok()?
+    };
+    (1..=31).contains(&day).then_some(day)
+}
+
+fn parse_month(word: &str) -> Option<u32> {
+    word.trim_end_matches(',').parse::<Month>().ok().map(|m| m.number_from_month())
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    word.trim_end_matches(',').parse().ok()
+}
+
+/// The next date with the given weekday on or after `from`.
+fn upcoming(from: NaiveDate, weekday: Weekday) -> NaiveDate {
+    from + Duration::days(weekday.days_since(from.weekday()) as i64)
+}
+
+/// A day and month without a year means the next time that date comes round.
+fn next_anniversary(today: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month, day);
+    match this_year {
+        Some(date) if date >= today => Some(date),
+        _ => (1..=8)
+            .map(|offset| NaiveDate::from_ymd_opt(today.year() + offset, month, day))
+            .find_map(|date| date),
+    }
+}
+
+fn parse_numeric_date(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(word, "%d/%m/%Y"))
+        .or_else(|_| NaiveDate::parse_from_str(word, "%d-%m-%Y"))
+        .ok()
+}
+
+/// Moves `date` by `amount` days, weeks, months or years (negative goes back).
+pub fn shift_date(date: NaiveDate, amount: i64, unit: &str) -> Option<NaiveDate> {
+    let months = |n: i64| {
+        let months = Months::new(n.unsigned_abs() as u32);
+        if n >= 0 {
+            date.checked_add_months(months)
+        } else {
+            date.checked_sub_months(months)
+        }
+    };
+    match unit.trim_end_matches('s') {
+        "day" => date.checked_add_signed(Duration::days(amount)),
+        "week" => date.checked_add_signed(Duration::weeks(amount)),
+        "fortnight" => date.checked_add_signed(Duration::weeks(2 * amount)),
+        "month" => months(amount),
+        "year" => months(amount * 12),
+        _ => None,
+    }
+}
+
+/// Parses "3 weeks from today", "in 10 days", "2 months ago" or
+/// "a week after 25 december".
+fn parse_relative(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    match words {
+        ["in", amount, unit] => shift_date(today, parse_number(amount)? as i64, unit),
+        [amount, unit, "ago"] => shift_date(today, -(parse_number(amount)? as i64), unit),
+        [amount, unit, "from", "now"] => shift_date(today, parse_number(amount)? as i64, unit),
+        [amount, unit, "from" | "after", rest @ ..] => {
+            shift_date(parse_date(rest, today)?, parse_number(amount)? as i64, unit)
+        }
+        [amount, unit, "before", rest @ ..] => {
+            shift_date(parse_date(rest, today)?, -(parse_number(amount)? as i64), unit)
+        }
+        _ => None,
+    }
+}
+
+/// Parses a complete spoken date such as "tomorrow", "next friday",
+/// "25 december", "the 1st of january 2027", "2027-03-14" or
+/// "3 weeks from today".
+pub fn parse_date(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let words: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|w| !matches!(*w, "on" | "the" | "of"))
+        .collect();
+    if let Some(date) = parse_relative(&words, today) {
+        return Some(date);
+    }
+    match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => Some(today + Duration::days(1)),
+        ["yesterday"] => Some(today - Duration::days(1)),
+        ["day", "after", "tomorrow"] => Some(today + Duration::days(2)),
+        ["day", "before", "yesterday"] => Some(today - Duration::days(2)),
+        ["this", day] => parse_weekday(day).map(|wd| upcoming(today, wd)),
+        ["next", "week"] => Some(today + Duration::weeks(1)),
+        ["next", day] => parse_weekday(day).map(|wd| upcoming(today + Duration::days(1), wd)),
+        ["last", day] => parse_weekday(day).map(|wd| upcoming(today - Duration::days(7), wd)),
+        [single] => parse_numeric_date(single)
+            .or_else(|| parse_weekday(single).map(|wd| upcoming(today, wd))),
+        [a, b] => match (parse_day_number(a), parse_month(b)) {
+            (Some(day), Some(month)) => next_anniversary(today, month, day),
+            _ => next_anniversary(today, parse_month(a)?, parse_day_number(b)?),
+        },
+        [a, b, year] => {
+            let year = year.trim_end_matches(',').parse().ok()?;
+            match (parse_day_number(a), parse_month(b)) {
+                (Some(day), Some(month)) => NaiveDate::from_ymd_opt(year, month, day),
+                _ => NaiveDate::from_ymd_opt(year, parse_month(a)?, parse_day_number(b)?),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Finds the longest date expression anywhere in `words`, returning its start,
+/// length and value, so the surrounding words can be used as a title.
+pub fn find_date(words: &[&str], today: NaiveDate) -> Option<(usize, usize, NaiveDate)> {
+    for len in (1..=6.min(words.len())).rev() {
+        for start in 0..=words.len() - len {
+            if let Some(date) = parse_date(&words[start..start + len], today) {
+                return Some((start, len, date));
+            }
+        }
+    }
+    None
+}
+
+/// Lowercases a spoken question and drops the leading filler words, so
+/// "What day is 25 December?" becomes `["25", "december"]`.
+pub fn question_words(arg: &str, filler: &[&str]) -> Vec<String> {
+    let lowered = arg.to_lowercase().replace(['?', ','], "");
+    let mut words: Vec<String> = lowered.split_whitespace().map(String::from).collect();
+    while words.first().is_some_and(|w| filler.contains(&w.as_str())) {
+        words.remove(0);
+    }
+    words
+}
+
+const QUESTION_FILLER: &[&str] = &[
+    "what", "which", "day", "date", "is", "it", "was", "will", "be", "how", "many", "days", "until", "till", "to",
+];
+
+fn parse_question_date(arg: &str) -> Result<NaiveDate, String> {
+    let words = question_words(arg, QUESTION_FILLER);
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    parse_date(&words, Local::now().date_naive())
+        .ok_or_else(|| format!("Error: I don't understand the date '{}'.", arg.trim()))
+}
+
+/// `day_of 25 december`: which weekday a date falls on.
+pub fn day_of(arg: &str) -> String {
+    let date = match parse_question_date(arg) {
+        Ok(date) => date,
+        Err(e) => return e,
+    };
+    let today = Local::now().date_naive();
+    let verb = if date < today { "was" } else { "is" };
+    let message = format!("{} {} a {}.", date.format("%-d %B %Y"), verb, date.format("%A"));
+    attach_data(json!({ "date": date.to_string(), "weekday": date.format("%A").to_string() }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `days_until 1 january`: whole days between today and a date.
+pub fn days_until(arg: &str) -> String {
+    let date = match parse_question_date(arg) {
+        Ok(date) => date,
+        Err(e) => return e,
+    };
+    let days = (date - Local::now().date_naive()).num_days();
+    let when = date.format("%A %-d %B %Y");
+    let message = match days {
+        0 => format!("{} is today.", when),
+        1 => format!("1 day until {}.", when),
+        -1 => format!("{} was yesterday.", when),
+        n if n > 0 => format!("{} days until {}.", n, when),
+        n => format!("{} was {} days ago.", when, -n),
+    };
+    attach_data(json!({ "date": date.to_string(), "days": days }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `date_of 3 weeks from today`: resolves any date expression to a calendar date.
+pub fn date_of(arg: &str) -> String {
+    let date = match parse_question_date(arg) {
+        Ok(date) => date,
+        Err(e) => return e,
+    };
+    let message = format!("That is {}.", date.format("%A %-d %B %Y"));
+    attach_data(json!({ "date": date.to_string(), "weekday": date.format("%A").to_string() }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+// Places people ask about that are not themselves time zone names.
+const PLACE_ALIASES: &[(&str, &str)] = &[
+    ("india", "Asia/Kolkata"),
+    ("delhi", "Asia/Kolkata"),
+    ("new delhi", "Asia/Kolkata"),
+    ("mumbai", "Asia/Kolkata"),
+    ("chennai", "Asia/Kolkata"),
+    ("bangalore", "Asia/Kolkata"),
+    ("bengaluru", "Asia/Kolkata"),
+    ("beijing", "Asia/Shanghai"),
+    ("china", "Asia/Shanghai"),
+    ("japan", "Asia/Tokyo"),
+    ("san francisco", "America/Los_Angeles"),
+    ("seattle", "America/Los_Angeles"),
+    ("california", "America/Los_Angeles"),
+    ("washington", "America/New_York"),
+    ("boston", "America/New_York"),
+    ("miami", "America/New_York"),
+    ("texas", "America/Chicago"),
+    ("dallas", "America/Chicago"),
+    ("uk", "Europe/London"),
+    ("england", "Europe/London"),
+    ("france", "Europe/Paris"),
+    ("germany", "Europe/Berlin"),
+    ("munich", "Europe/Berlin"),
+    ("australia", "Australia/Sydney"),
+    ("melbourne", "Australia/Melbourne"),
+    ("singapore", "Asia/Singapore"),
+    ("dubai", "Asia/Dubai"),
+    ("utc", "UTC"),
+    ("gmt", "GMT"),
+];
+
+/// Resolves a spoken place to a time zone, matching aliases, full names like
+/// "europe/paris" and city components like "new york" or "tokyo".
+fn find_timezone(place: &str) -> Option<Tz> {
+    let place = place.trim().to_lowercase();
+    if let Some((_, name)) = PLACE_ALIASES.iter().find(|(alias, _)| *alias == place) {
+        return name.parse().ok();
+    }
+    TZ_VARIANTS.iter().copied().find(|tz| {
+        let name = tz.name().to_lowercase();
+        let city = name.rsplit('/').next().unwrap_or(&name).replace('_', " ");
+        name == place || city == place
+    })
+}
+
+/// "5 hours ahead", "1 hour behind" or "5.5 hours ahead" of local time.
+fn relative_offset(hours: f64) -> String {
+    let amount = hours.abs();
+    let unit = if amount == 1.0 { "hour" } else { "hours" };
+    match hours {
+        0.0 => "the same as here".to_string(),
+        h if h > 0.0 => format!("{} {} ahead", amount, unit),
+        _ => format!("{} {} behind", amount, unit),
+    }
+}
+
+/// `time_in tokyo`: current local time somewhere else.
+pub fn time_in(arg: &str) -> String {
+    let words = question_words(arg, &["what", "time", "is", "it", "in", "the", "now"]);
+    let place = words
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+    if place.is_empty() {
+        return "Error: Please name a city or time zone.".to_string();
+    }
+    let Some(tz) = find_timezone(&place) else {
+        return format!("Error: I don't know the time zone for '{}'.", place);
+    };
+
+    let there = Utc::now().with_timezone(&tz);
+    let here = Local::now();
+    let offset_hours = (there.offset().fix().local_minus_utc() - here.offset().local_minus_utc()) as f64 / 3600.0;
+    let relative = relative_offset(offset_hours);
+    let message = format!(
+        "In {} it is {} on {}, {}.",
+        place,
+        there.format("%I:%M %p"),
+        there.format("%A"),
+        relative
+    );
+    attach_data(json!({
+        "place": place,
+        "timezone": tz.name(),
+        "time": there.to_rfc3339(),
+        "offset_from_local_hours": offset_hours,
+    }));
+    if let Err(e) = speak_to_speaker(&format!("In {} it is {}", place, locale::speak_datetime(there.naive_local()))) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn parse(text: &str) -> Option<NaiveDate> {
+        // 4 March 2026 is a Wednesday.
+        parse_date(&text.split_whitespace().collect::<Vec<_>>(), date(2026, 3, 4))
+    }
+
+    #[test]
+    fn parses_named_days() {
+        assert_eq!(parse("tomorrow"), Some(date(2026, 3, 5)));
+        assert_eq!(parse("day after tomorrow"), Some(date(2026, 3, 6)));
+        assert_eq!(parse("friday"), Some(date(2026, 3, 6)));
+        assert_eq!(parse("wednesday"), Some(date(2026, 3, 4)));
+        assert_eq!(parse("next wednesday"), Some(date(2026, 3, 11)));
+        assert_eq!(parse("last monday"), Some(date(2026, 3, 2)));
+    }
+
+    #[test]
+    fn parses_calendar_dates() {
+        assert_eq!(parse("25 december"), Some(date(2026, 12, 25)));
+        assert_eq!(parse("the 1st of january"), Some(date(2027, 1, 1)));
+        assert_eq!(parse("march 4"), Some(date(2026, 3, 4)));
+        assert_eq!(parse("14 march 2030"), Some(date(2030, 3, 14)));
+        assert_eq!(parse("2027-03-14"), Some(date(2027, 3, 14)));
+        assert_eq!(parse("31 february 2027"), None);
+    }
+
+    #[test]
+    fn parses_relative_dates() {
+        assert_eq!(parse("in 10 days"), Some(date(2026, 3, 14)));
+        assert_eq!(parse("3 weeks from now"), Some(date(2026, 3, 25)));
+        assert_eq!(parse("2 months ago"), Some(date(2026, 1, 4)));
+        assert_eq!(parse("a week after 25 december"), Some(date(2027, 1, 1)));
+        assert_eq!(shift_date(date(2026, 1, 31), 1, "month"), Some(date(2026, 2, 28)));
+    }
+
+    #[test]
+    fn finds_dates_inside_sentences() {
+        let words = ["dentist", "next", "friday", "at", "9"];
+        assert_eq!(find_date(&words, date(2026, 3, 4)), Some((1, 2, date(2026, 3, 6))));
+    }
+
+    #[test]
+    fn describes_offsets() {
+        assert_eq!(relative_offset(0.0), "the same as here");
+        assert_eq!(relative_offset(1.0), "1 hour ahead");
+        assert_eq!(relative_offset(-1.0), "1 hour behind");
+        assert_eq!(relative_offset(5.5), "5.5 hours ahead");
+    }
+
+    #[test]
+    fn resolves_places_to_time_zones() {
+        assert_eq!(find_timezone("tokyo"), Some(chrono_tz::Asia::Tokyo));
+        assert_eq!(find_timezone("New York"), Some(chrono_tz::America::New_York));
+        assert_eq!(find_timezone("india"), Some(chrono_tz::Asia::Kolkata));
+        assert_eq!(find_timezone("atlantis"), None);
+    }
+}
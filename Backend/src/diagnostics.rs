@@ -0,0 +1,762 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    fs, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+#[cfg(target_os = "linux")]
+use std::{
+    mem,
+    os::fd::{AsRawFd, FromRawFd},
+};
+
+use crate::{attach_data, data_path, locale, speak_to_speaker};
+#[cfg(target_os = "linux")]
+use crate::network::sockaddr_ip;
+
+const DIAGNOSTICS_FILE: &str = "diagnostics.json";
+const DEFAULT_TIMEOUT_MS: u64 = 2000;
+const DEFAULT_PINGS: u32 = 4;
+const MAX_PINGS: u32 = 20;
+const PING_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_HOPS: u32 = 30;
+/// A trace gives up after this long, however many hops are left.
+const MAX_TRACE_TIME: Duration = Duration::from_secs(20);
+// Traceroute probes go to unlikely UDP ports, as the classic tool does.
+#[cfg(target_os = "linux")]
+const TRACE_BASE_PORT: u16 = 33434;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Settings {
+    /// DNS server used by `resolve`, `ping`, `check_port` and `trace_route`.
+    /// `None` uses the system resolver.
+    resolver: Option<SocketAddr>,
+    timeout_ms: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { resolver: None, timeout_ms: DEFAULT_TIMEOUT_MS }
+    }
+}
+
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+
+fn settings() -> Settings {
+    let mut cached = SETTINGS.lock().unwrap();
+    cached
+        .get_or_insert_with(|| {
+            fs::read_to_string(data_path(DIAGNOSTICS_FILE))
+                .ok()
+                .and_then(|text| serde_json::from_str(&text).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+fn save_settings(settings: Settings) -> io::Result<()> {
+    fs::write(data_path(DIAGNOSTICS_FILE), serde_json::to_string_pretty(&settings)?)?;
+    *SETTINGS.lock().unwrap() = Some(settings);
+    Ok(())
+}
+
+fn timeout() -> Duration {
+    Duration::from_millis(settings().timeout_ms)
+}
+
+fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
+
+fn speak_millis(ms: f64) -> String {
+    if ms < 10.0 {
+        format!("{} milliseconds", locale::format_decimal(ms, 1))
+    } else {
+        format!("{} milliseconds", ms.round())
+    }
+}
+
+fn speak(message: String) -> String {
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+// --- Name resolution ---------------------------------------------------------
+
+fn encode_query(id: u16, name: &str, record_type: u16) -> Option<Vec<u8>> {
+    let mut packet = Vec::with_capacity(name.len() + 18);
+    packet.extend_from_slice(&id.to_be_bytes());
+    // Standard query with recursion desired, one question.
+    packet.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return None;
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&record_type.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    Some(packet)
+}
+
+/// Returns the offset just past a possibly compressed name.
+fn skip_name(packet: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(pos)? as usize;
+        if len & 0xC0 == 0xC0 {
+            return Some(pos + 2);
+        }
+        if len == 0 {
+            return Some(pos + 1);
+        }
+        pos += len + 1;
+    }
+}
+
+fn read_u16(packet: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*packet.get(pos)?, *packet.get(pos + 1)?]))
+}
+
+fn decode_answers(packet: &[u8], id: u16) -> Result<Vec<IpAddr>, String> {
+    if packet.len() < 12 || read_u16(packet, 0) != Some(id) {
+        return Err("the resolver sent a malformed reply".to_string());
+    }
+    match packet[3] & 0x0F {
+        0 => {}
+        3 => return Ok(Vec::new()),
+        code => return Err(format!("the resolver refused the query (code {})", code)),
+    }
+    let malformed = || "the resolver sent a malformed reply".to_string();
+    let questions = read_u16(packet, 4).ok_or_else(malformed)?;
+    let answers = read_u16(packet, 6).ok_or_else(malformed)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(packet, pos).ok_or_else(malformed)? + 4;
+    }
+    let mut found = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(packet, pos).ok_or_else(malformed)?;
+        let record_type = read_u16(packet, pos).ok_or_else(malformed)?;
+        let length = read_u16(packet, pos + 8).ok_or_else(malformed)? as usize;
+        let data = packet.get(pos + 10..pos + 10 + length).ok_or_else(malformed)?;
+        match (record_type, length) {
+            (1, 4) => found.push(IpAddr::V4(Ipv4Addr::new(data[0], data[1], data[2], data[3]))),
+            (28, 16) => found.push(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(data).unwrap()))),
+            _ => {}
+        }
+        pos += 10 + length;
+    }
+    Ok(found)
+}
+
+fn query(server: SocketAddr, name: &str, record_type: u16, timeout: Duration) -> Result<Vec<IpAddr>, String> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+    let id = (nanos ^ std::process::id()) as u16 ^ record_type;
+    let packet = encode_query(id, name, record_type).ok_or_else(|| format!("{} is not a valid host name", name))?;
+    let bind: SocketAddr = if server.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { (Ipv6Addr::UNSPECIFIED, 0).into() };
+    let socket = UdpSocket::bind(bind).map_err(|e| e.to_string())?;
+    socket.connect(server).map_err(|e| e.to_string())?;
+    socket.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    socket.send(&packet).map_err(|e| e.to_string())?;
+
+    let mut reply = [0u8; 1500];
+    let deadline = Instant::now() + timeout;
+    loop {
+        match socket.recv(&mut reply) {
+            // Ignore stray replies to earlier queries.
+            Ok(len) if read_u16(&reply[..len], 0) != Some(id) && Instant::now() < deadline => continue,
+            Ok(len) => return decode_answers(&reply[..len], id),
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                return Err(format!("the resolver at {} did not answer", server))
+            }
+            Err(e) => return Err(format!("the resolver at {} is unreachable: {}", server, e)),
+        }
+    }
+}
+
+/// Resolves a host through the configured resolver, or the system one.
+fn lookup(host: &str) -> Result<Vec<IpAddr>, String> {
+    let host = host.trim().trim_start_matches('[').trim_end_matches(']');
+    if let Ok(address) = host.parse::<IpAddr>() {
+        return Ok(vec![address]);
+    }
+    if host.eq_ignore_ascii_case("localhost") {
+        return Ok(vec![IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)]);
+    }
+    let settings = settings();
+    let mut addresses = match settings.resolver {
+        Some(server) => {
+            let timeout = Duration::from_millis(settings.timeout_ms);
+            let mut found = query(server, host, 1, timeout)?;
+            found.extend(query(server, host, 28, timeout)?);
+            found
+        }
+        None => (host, 0)
+            .to_socket_addrs()
+            .map_err(|_| format!("{} could not be resolved", host))?
+            .map(|address| address.ip())
+            .collect(),
+    };
+    addresses.dedup();
+    if addresses.is_empty() {
+        return Err(format!("{} could not be resolved", host));
+    }
+    Ok(addresses)
+}
+
+/// Picks the address to probe, preferring IPv4.
+fn target(host: &str) -> Result<IpAddr, String> {
+    let addresses = lookup(host)?;
+    Ok(addresses.iter().find(|a| a.is_ipv4()).copied().unwrap_or(addresses[0]))
+}
+
+/// Splits "db-host 5432" or "5432 is open on db-host" into host words and numbers.
+fn host_and_numbers(arg: &str, fillers: &[&str]) -> (Option<String>, Vec<u32>) {
+    let mut host = None;
+    let mut numbers = Vec::new();
+    for word in arg.split_whitespace() {
+        let word = word.trim_end_matches(['?', '.', ',']);
+        let lowered = word.to_lowercase();
+        if fillers.contains(&lowered.as_str()) {
+            continue;
+        }
+        match word.parse::<u32>() {
+            Ok(number) => numbers.push(number),
+            Err(_) if host.is_none() => host = Some(word.to_string()),
+            Err(_) => {}
+        }
+    }
+    (host, numbers)
+}
+
+/// `resolve example.internal`: looks a name up through the configured resolver.
+pub fn resolve(arg: &str) -> String {
+    let (Some(host), _) = host_and_numbers(arg, &["resolve", "look", "up", "lookup", "the", "address", "of", "for"]) else {
+        return "Error: Say which name to resolve, e.g. 'resolve example.com'.".to_string();
+    };
+    let resolver = settings().resolver;
+    let started = Instant::now();
+    let result = lookup(&host);
+    let elapsed = millis(started.elapsed());
+    attach_data(json!({
+        "host": host,
+        "resolver": resolver.map(|r| r.to_string()).unwrap_or_else(|| "system".to_string()),
+        "addresses": result.as_ref().ok(),
+        "error": result.as_ref().err(),
+        "time_ms": elapsed,
+    }));
+    match result {
+        Ok(addresses) => {
+            let list: Vec<String> = addresses.iter().map(IpAddr::to_string).collect();
+            let message = match list.as_slice() {
+                [one] => format!("{} resolves to {}.", host, one),
+                many => format!("{} resolves to {} addresses: {}.", host, many.len(), many.join(", ")),
+            };
+            speak(message)
+        }
+        Err(e) => format!("Error: {}.", e),
+    }
+}
+
+/// `set_resolver 127.0.0.1:5353`, or `set_resolver system` to go back to the system resolver.
+pub fn set_resolver(arg: &str) -> String {
+    let arg = arg.trim();
+    let resolver = match arg.to_lowercase().as_str() {
+        "" => return "Error: Give a resolver address such as 127.0.0.1:53, or 'system'.".to_string(),
+        "system" | "default" | "reset" => None,
+        _ => match arg.parse::<SocketAddr>().or_else(|_| arg.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53))) {
+            Ok(address) => Some(address),
+            Err(_) => return format!("Error: {} is not an IP address with an optional port.", arg),
+        },
+    };
+    let settings = Settings { resolver, ..settings() };
+    if let Err(e) = save_settings(settings) {
+        return format!("Error: Failed to save the resolver: {}", e);
+    }
+    speak(match resolver {
+        Some(address) => format!("Names will be resolved through {}.", address),
+        None => "Names will be resolved by the system resolver.".to_string(),
+    })
+}
+
+/// `set_network_timeout 3`: seconds to wait for each probe or DNS reply.
+pub fn set_network_timeout(arg: &str) -> String {
+    let Some(seconds) = arg
+        .split_whitespace()
+        .find_map(|w| w.parse::<f64>().ok())
+        .filter(|s| *s > 0.0 && *s <= 30.0)
+    else {
+        return "Error: Give a timeout between 0 and 30 seconds.".to_string();
+    };
+    let settings = Settings { timeout_ms: (seconds * 1000.0) as u64, ..settings() };
+    if let Err(e) = save_settings(settings) {
+        return format!("Error: Failed to save the timeout: {}", e);
+    }
+    speak(format!("Network checks will wait up to {} seconds.", locale::format_decimal(seconds, 1)))
+}
+
+// --- Port check --------------------------------------------------------------
+
+/// "open", "closed" when the connection is refused, or "filtered" when
+/// nothing answers in time.
+fn port_state(address: SocketAddr, timeout: Duration) -> io::Result<&'static str> {
+    match TcpStream::connect_timeout(&address, timeout) {
+        Ok(_) => Ok("open"),
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Ok("closed"),
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => Ok("filtered"),
+        Err(e) => Err(e),
+    }
+}
+
+/// `check_port 5432 db-host`: whether a TCP port accepts connections.
+pub fn check_port(arg: &str) -> String {
+    let (host, numbers) = host_and_numbers(arg, &["check", "if", "whether", "port", "is", "open", "on", "at", "of", "the"]);
+    let Some(port) = numbers.first().and_then(|p| u16::try_from(*p).ok()) else {
+        return "Error: Say which port, e.g. 'check_port 5432 db-host'.".to_string();
+    };
+    let host = host.unwrap_or_else(|| "localhost".to_string());
+    let address = match target(&host) {
+        Ok(address) => address,
+        Err(e) => return format!("Error: {}.", e),
+    };
+
+    let timeout = timeout();
+    let started = Instant::now();
+    let (state, message) = match port_state(SocketAddr::new(address, port), timeout) {
+        Ok("open") => (
+            "open",
+            format!("Port {} is open on {}, connected in {}.", port, host, speak_millis(millis(started.elapsed()))),
+        ),
+        Ok("closed") => ("closed", format!("Port {} is closed on {}; the connection was refused.", port, host)),
+        Ok(state) => (
+            state,
+            format!("Port {} on {} did not answer within {} seconds.", port, host, timeout.as_secs_f64()),
+        ),
+        Err(e) => ("unreachable", format!("Could not reach {}: {}.", host, e)),
+    };
+    attach_data(json!({
+        "host": host,
+        "address": address,
+        "port": port,
+        "state": state,
+        "time_ms": millis(started.elapsed()),
+    }));
+    speak(message)
+}
+
+// --- Ping --------------------------------------------------------------------
+
+/// An unprivileged ICMP socket. The kernel fills in the identifier and only
+/// delivers replies to our own requests. Needs `net.ipv4.ping_group_range`
+/// to include the backend's group.
+#[cfg(target_os = "linux")]
+fn icmp_socket(v6: bool) -> io::Result<UdpSocket> {
+    let (domain, protocol) = if v6 { (libc::AF_INET6, libc::IPPROTO_ICMPV6) } else { (libc::AF_INET, libc::IPPROTO_ICMP) };
+    let fd = unsafe { libc::socket(domain, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, protocol) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { UdpSocket::from_raw_fd(fd) })
+}
+
+/// Elsewhere pings always fall back to TCP.
+#[cfg(not(target_os = "linux"))]
+fn icmp_socket(_v6: bool) -> io::Result<UdpSocket> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+fn echo_request(v6: bool, sequence: u16) -> Vec<u8> {
+    let mut packet = vec![if v6 { 128 } else { 8 }, 0, 0, 0, 0, 0];
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(b"aurora-ping-data");
+    let mut sum: u32 = packet.chunks(2).map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32).sum();
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    packet[2..4].copy_from_slice(&(!(sum as u16)).to_be_bytes());
+    packet
+}
+
+fn icmp_echo(socket: &UdpSocket, address: IpAddr, sequence: u16, timeout: Duration) -> io::Result<Option<Duration>> {
+    let v6 = address.is_ipv6();
+    let started = Instant::now();
+    socket.send_to(&echo_request(v6, sequence), SocketAddr::new(address, 0))?;
+    let mut reply = [0u8; 1500];
+    while let Some(remaining) = timeout.checked_sub(started.elapsed()).filter(|r| !r.is_zero()) {
+        socket.set_read_timeout(Some(remaining))?;
+        match socket.recv(&mut reply) {
+            Ok(len) if len >= 8 && reply[0] == if v6 { 129 } else { 0 } && reply[6..8] == sequence.to_be_bytes() => {
+                return Ok(Some(started.elapsed()));
+            }
+            Ok(_) => continue,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(None)
+}
+
+/// Falls back to timing a TCP handshake when ICMP sockets aren't allowed.
+/// A refused connection still proves the host answered.
+fn tcp_echo(address: IpAddr, timeout: Duration) -> Option<Duration> {
+    let started = Instant::now();
+    match TcpStream::connect_timeout(&SocketAddr::new(address, 80), timeout) {
+        Ok(_) => Some(started.elapsed()),
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Some(started.elapsed()),
+        Err(_) => None,
+    }
+}
+
+/// Sends `count` echoes and returns the method used with each round trip in
+/// milliseconds, `None` for the ones that timed out.
+fn echo_times(address: IpAddr, count: u32, timeout: Duration) -> io::Result<(&'static str, Vec<Option<f64>>)> {
+    let socket = icmp_socket(address.is_ipv6());
+    let method = if socket.is_ok() { "icmp" } else { "tcp" };
+    let mut times = Vec::new();
+    for sequence in 1..=count {
+        if sequence > 1 {
+            thread::sleep(PING_INTERVAL);
+        }
+        let reply = match &socket {
+            Ok(socket) => icmp_echo(socket, address, sequence as u16, timeout)?,
+            Err(_) => tcp_echo(address, timeout),
+        };
+        times.push(reply.map(millis));
+    }
+    Ok((method, times))
+}
+
+/// `ping localhost` or `ping db-host 10`: round-trip times to a host.
+pub fn ping(arg: &str) -> String {
+    let (host, numbers) = host_and_numbers(arg, &["ping", "times", "time", "the", "host"]);
+    let Some(host) = host else {
+        return "Error: Say which host to ping, e.g. 'ping localhost'.".to_string();
+    };
+    let count = numbers.first().copied().unwrap_or(DEFAULT_PINGS).clamp(1, MAX_PINGS);
+    let address = match target(&host) {
+        Ok(address) => address,
+        Err(e) => return format!("Error: {}.", e),
+    };
+
+    let (method, times) = match echo_times(address, count, timeout()) {
+        Ok(result) => result,
+        Err(e) => return format!("Error: Failed to ping {}: {}", host, e),
+    };
+
+    let answered: Vec<f64> = times.iter().flatten().copied().collect();
+    let (min, max) = answered.iter().fold((f64::MAX, 0.0f64), |(lo, hi), t| (lo.min(*t), hi.max(*t)));
+    let average = answered.iter().sum::<f64>() / answered.len().max(1) as f64;
+    attach_data(json!({
+        "host": host,
+        "address": address,
+        "method": method,
+        "sent": count,
+        "received": answered.len(),
+        "times_ms": times,
+        "min_ms": (!answered.is_empty()).then_some(min),
+        "average_ms": (!answered.is_empty()).then_some(average),
+        "max_ms": (!answered.is_empty()).then_some(max),
+    }));
+
+    let name = if host == address.to_string() { host.clone() } else { format!("{} ({})", host, address) };
+    let message = if answered.is_empty() {
+        format!("{} did not answer any of {} pings.", name, count)
+    } else {
+        format!(
+            "{} answered {} of {} pings, averaging {}.",
+            name,
+            answered.len(),
+            count,
+            speak_millis(average)
+        )
+    };
+    let lines: Vec<String> = times
+        .iter()
+        .enumerate()
+        .map(|(i, time)| match time {
+            Some(ms) => format!("seq={} time={} ms", i + 1, locale::format_decimal(*ms, 2)),
+            None => format!("seq={} timed out", i + 1),
+        })
+        .collect();
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    format!("{}\n{}", message, lines.join("\n"))
+}
+
+// --- Traceroute --------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+struct Hop {
+    ttl: u32,
+    address: Option<IpAddr>,
+    time_ms: Option<f64>,
+    reached: bool,
+}
+
+#[cfg(target_os = "linux")]
+fn set_option(socket: &UdpSocket, level: libc::c_int, name: libc::c_int, value: libc::c_int) -> io::Result<()> {
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            level,
+            name,
+            &value as *const _ as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Reads one ICMP error queued on the socket by `IP_RECVERR`, returning the
+/// ICMP type and the address of the router or host that sent it.
+#[cfg(target_os = "linux")]
+fn queued_error(socket: &UdpSocket) -> io::Result<Option<(u8, Option<IpAddr>)>> {
+    let mut data = [0u8; 64];
+    let mut control = [0u8; 512];
+    let mut iov = libc::iovec { iov_base: data.as_mut_ptr() as *mut libc::c_void, iov_len: data.len() };
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    message.msg_controllen = control.len() as _;
+
+    if unsafe { libc::recvmsg(socket.as_raw_fd(), &mut message, libc::MSG_ERRQUEUE | libc::MSG_DONTWAIT) } < 0 {
+        let error = io::Error::last_os_error();
+        return if error.kind() == io::ErrorKind::WouldBlock { Ok(None) } else { Err(error) };
+    }
+    unsafe {
+        let mut header = libc::CMSG_FIRSTHDR(&message);
+        while !header.is_null() {
+            let level = (*header).cmsg_level;
+            let kind = (*header).cmsg_type;
+            if (level == libc::SOL_IP && kind == libc::IP_RECVERR) || (level == libc::SOL_IPV6 && kind == libc::IPV6_RECVERR) {
+                let error = libc::CMSG_DATA(header) as *const libc::sock_extended_err;
+                let extended = std::ptr::read_unaligned(error);
+                if extended.ee_origin == libc::SO_EE_ORIGIN_ICMP || extended.ee_origin == libc::SO_EE_ORIGIN_ICMP6 {
+                    return Ok(Some((extended.ee_type, sockaddr_ip(libc::SO_EE_OFFENDER(error)))));
+                }
+            }
+            header = libc::CMSG_NXTHDR(&message, header);
+        }
+    }
+    Ok(None)
+}
+
+/// Sleeps until an error is queued on the socket or `timeout` passes.
+/// `poll` always reports `POLLERR`, so no events need to be requested.
+#[cfg(target_os = "linux")]
+fn wait_for_error(socket: &UdpSocket, timeout: Duration) -> io::Result<()> {
+    let mut fd = libc::pollfd { fd: socket.as_raw_fd(), events: 0, revents: 0 };
+    let millis = timeout.as_millis().clamp(1, libc::c_int::MAX as u128) as libc::c_int;
+    if unsafe { libc::poll(&mut fd, 1, millis) } < 0 {
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn probe_hop(address: IpAddr, ttl: u32, timeout: Duration) -> io::Result<Hop> {
+    let v6 = address.is_ipv6();
+    let bind: SocketAddr = if v6 { (Ipv6Addr::UNSPECIFIED, 0).into() } else { ([0, 0, 0, 0], 0).into() };
+    let socket = UdpSocket::bind(bind)?;
+    if v6 {
+        set_option(&socket, libc::SOL_IPV6, libc::IPV6_RECVERR, 1)?;
+        set_option(&socket, libc::SOL_IPV6, libc::IPV6_UNICAST_HOPS, ttl as libc::c_int)?;
+    } else {
+        set_option(&socket, libc::SOL_IP, libc::IP_RECVERR, 1)?;
+        socket.set_ttl(ttl)?;
+    }
+    socket.connect(SocketAddr::new(address, TRACE_BASE_PORT + ttl as u16))?;
+
+    let started = Instant::now();
+    socket.send(b"aurora-traceroute")?;
+    let time_exceeded = if v6 { 3 } else { 11 };
+    loop {
+        if let Some((kind, from)) = queued_error(&socket)? {
+            return Ok(Hop {
+                ttl,
+                address: from,
+                time_ms: Some(millis(started.elapsed())),
+                // Anything but "time exceeded" (usually "port unreachable") ends the trace.
+                reached: kind != time_exceeded,
+            });
+        }
+        match timeout.checked_sub(started.elapsed()).filter(|r| !r.is_zero()) {
+            Some(remaining) => wait_for_error(&socket, remaining)?,
+            None => return Ok(Hop { ttl, address: None, time_ms: None, reached: false }),
+        }
+    }
+}
+
+/// Reading ICMP errors off a UDP socket needs Linux's `IP_RECVERR`.
+#[cfg(not(target_os = "linux"))]
+fn probe_hop(_address: IpAddr, _ttl: u32, _timeout: Duration) -> io::Result<Hop> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "tracing routes is only supported on Linux"))
+}
+
+/// `trace_route example.com`: the routers between here and a host.
+pub fn trace_route(arg: &str) -> String {
+    let (host, numbers) = host_and_numbers(arg, &["trace", "route", "traceroute", "to", "the", "hops", "max"]);
+    let Some(host) = host else {
+        return "Error: Say which host to trace, e.g. 'trace_route example.com'.".to_string();
+    };
+    let max_hops = numbers.first().copied().unwrap_or(MAX_HOPS).clamp(1, MAX_HOPS);
+    let address = match target(&host) {
+        Ok(address) => address,
+        Err(e) => return format!("Error: {}.", e),
+    };
+
+    let timeout = timeout();
+    let deadline = Instant::now() + MAX_TRACE_TIME;
+    let mut hops = Vec::new();
+    for ttl in 1..=max_hops {
+        let Some(remaining) = deadline.checked_duration_since(Instant::now()).filter(|r| !r.is_zero()) else {
+            break;
+        };
+        let hop = match probe_hop(address, ttl, timeout.min(remaining)) {
+            Ok(hop) => hop,
+            Err(e) => return format!("Error: Failed to trace the route to {}: {}", host, e),
+        };
+        let done = hop.reached;
+        hops.push(hop);
+        if done {
+            break;
+        }
+    }
+
+    let reached = hops.last().is_some_and(|hop| hop.reached && hop.address == Some(address));
+    let lines: Vec<String> = hops
+        .iter()
+        .map(|hop| match (hop.address, hop.time_ms) {
+            (Some(from), Some(ms)) => format!("{:>2}  {}  {} ms", hop.ttl, from, locale::format_decimal(ms, 2)),
+            _ => format!("{:>2}  *", hop.ttl),
+        })
+        .collect();
+    attach_data(json!({ "host": host, "address": address, "reached": reached, "hops": hops }));
+
+    let message = if reached {
+        format!("{} is {} hop{} away.", host, hops.len(), if hops.len() == 1 { "" } else { "s" })
+    } else if hops.len() < max_hops as usize && hops.last().is_none_or(|hop| !hop.reached) {
+        format!(
+            "The route to {} could not be completed within {} seconds, after {} hops.",
+            host,
+            MAX_TRACE_TIME.as_secs(),
+            hops.len()
+        )
+    } else {
+        format!("The route to {} could not be completed within {} hops.", host, hops.len())
+    };
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    format!("{}\n{}", message, lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// A reply to `query` answering with one record of its own type.
+    fn answer(query: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut reply = query.to_vec();
+        reply[2] = 0x81;
+        reply[3] = 0x80;
+        reply[7] = 1;
+        let record_type = &query[query.len() - 4..query.len() - 2];
+        reply.extend_from_slice(&[0xC0, 12]);
+        reply.extend_from_slice(record_type);
+        reply.extend_from_slice(&[0, 1, 0, 0, 0, 60, 0, data.len() as u8]);
+        reply.extend_from_slice(data);
+        reply
+    }
+
+    #[test]
+    fn encodes_queries() {
+        let packet = encode_query(0x1234, "a.bc.", 28).unwrap();
+        assert_eq!(&packet[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&packet[12..], &[1, b'a', 2, b'b', b'c', 0, 0, 28, 0, 1]);
+        assert!(encode_query(1, "a..b", 1).is_none());
+        assert!(encode_query(1, &"x".repeat(64), 1).is_none());
+    }
+
+    #[test]
+    fn decodes_answers() {
+        let query = encode_query(7, "example.internal", 1).unwrap();
+        let reply = answer(&query, &[10, 1, 2, 3]);
+        assert_eq!(decode_answers(&reply, 7), Ok(vec![IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3))]));
+
+        let query = encode_query(8, "example.internal", 28).unwrap();
+        let reply = answer(&query, &Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1).octets());
+        assert_eq!(decode_answers(&reply, 8), Ok(vec!["fd00::1".parse::<IpAddr>().unwrap()]));
+
+        assert!(decode_answers(&reply, 9).is_err());
+        assert!(decode_answers(&reply[..reply.len() - 2], 8).is_err());
+        let mut missing = query.clone();
+        missing[3] = 3;
+        assert_eq!(decode_answers(&missing, 8), Ok(Vec::new()));
+        missing[3] = 5;
+        assert!(decode_answers(&missing, 8).unwrap_err().contains("code 5"));
+    }
+
+    #[test]
+    fn looks_up_through_the_configured_resolver() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut query = [0u8; 512];
+            for _ in 0..2 {
+                let (len, from) = server.recv_from(&mut query).unwrap();
+                let data: &[u8] = match query[len - 3] {
+                    1 => &[10, 1, 2, 3],
+                    _ => &[0xfd, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                };
+                server.send_to(&answer(&query[..len], data), from).unwrap();
+            }
+        });
+        // What set_resolver stores, without writing the settings file.
+        *SETTINGS.lock().unwrap() = Some(Settings { resolver: Some(address), ..Settings::default() });
+
+        let found = lookup("example.internal").unwrap();
+        assert_eq!(found, vec!["10.1.2.3".parse::<IpAddr>().unwrap(), "fd00::1".parse().unwrap()]);
+        assert_eq!(lookup("192.0.2.1").unwrap(), vec!["192.0.2.1".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[test]
+    fn checks_open_and_closed_ports() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap();
+        assert_eq!(port_state(open, Duration::from_secs(2)).unwrap(), "open");
+
+        drop(listener);
+        assert_eq!(port_state(open, Duration::from_secs(2)).unwrap(), "closed");
+    }
+
+    #[test]
+    fn pings_localhost() {
+        let (_, times) = echo_times(IpAddr::V4(Ipv4Addr::LOCALHOST), 2, Duration::from_secs(2)).unwrap();
+        assert_eq!(times.len(), 2);
+        assert!(times.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn splits_hosts_from_numbers() {
+        let (host, numbers) = host_and_numbers("is port 5432 open on db-host?", &["is", "port", "open", "on"]);
+        assert_eq!(host.as_deref(), Some("db-host"));
+        assert_eq!(numbers, vec![5432]);
+    }
+}
@@ -0,0 +1,41 @@
+use axum::response::sse::{Event, KeepAlive, Sse};
+use chrono::Local;
+use serde::Serialize;
+use std::{convert::Infallible, sync::OnceLock};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendEvent {
+    pub kind: String,
+    pub message: String,
+    pub data: serde_json::Value,
+    pub time: String,
+}
+
+fn sender() -> &'static broadcast::Sender<BackendEvent> {
+    static SENDER: OnceLock<broadcast::Sender<BackendEvent>> = OnceLock::new();
+    SENDER.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
+
+/// Pushes an event to every connected `/events` client. Events published
+/// while nobody is listening are dropped.
+pub fn publish(kind: &str, message: &str, data: serde_json::Value) {
+    let _ = sender().send(BackendEvent {
+        kind: kind.to_string(),
+        message: message.to_string(),
+        data,
+        time: Local::now().to_rfc3339(),
+    });
+}
+
+/// `GET /events`: server-sent event stream of backend notifications.
+pub async fn stream_events() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(sender().subscribe()).filter_map(|event| {
+        let event = event.ok()?;
+        Event::default().event(event.kind.clone()).json_data(&event).ok().map(Ok)
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,326 @@
+use chrono::{DateTime, Datelike, Duration, Local};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{attach_data, format_bytes, i18n::tr, locale, paths, speak_to_speaker};
+
+// Spoken file kinds and the extensions they stand for.
+const KINDS: [(&str, &[&str]); 12] = [
+    ("text", &["txt", "md"]),
+    ("image", &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp"]),
+    ("picture", &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp"]),
+    ("photo", &["png", "jpg", "jpeg", "heic"]),
+    ("video", &["mp4", "mkv", "webm", "mov", "avi"]),
+    ("audio", &["mp3", "flac", "ogg", "wav", "m4a"]),
+    ("music", &["mp3", "flac", "ogg", "wav", "m4a"]),
+    ("word", &["doc", "docx", "odt"]),
+    ("spreadsheet", &["xls", "xlsx", "ods", "csv"]),
+    ("rust", &["rs"]),
+    ("python", &["py"]),
+    ("javascript", &["js", "jsx", "mjs"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sort {
+    Name,
+    Size,
+    SmallestFirst,
+    Newest,
+    Oldest,
+    Type,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Since {
+    Today,
+    Yesterday,
+    Days(i64),
+}
+
+#[derive(Debug)]
+struct Filter {
+    folders_only: bool,
+    files_only: bool,
+    extensions: Vec<String>,
+    patterns: Vec<String>,
+    since: Option<Since>,
+    hidden: bool,
+    sort: Sort,
+}
+
+#[derive(Debug, Serialize)]
+struct Listing {
+    name: String,
+    path: PathBuf,
+    kind: &'static str,
+    size: u64,
+    permissions: String,
+    mode: String,
+    modified: Option<i64>,
+    #[serde(skip)]
+    modified_at: Option<DateTime<Local>>,
+}
+
+fn singular(word: &str) -> &str {
+    word.strip_suffix('s').filter(|w| w.len() > 2).unwrap_or(word)
+}
+
+fn parse_filter(rest: &str, folders_only: bool) -> Filter {
+    let mut filter = Filter {
+        folders_only,
+        files_only: false,
+        extensions: Vec::new(),
+        patterns: Vec::new(),
+        since: None,
+        hidden: false,
+        sort: Sort::Name,
+    };
+    let words: Vec<String> = rest
+        .split_whitespace()
+        .map(|w| w.trim_end_matches(['?', ',']).to_lowercase())
+        .collect();
+    for (i, word) in words.iter().enumerate() {
+        let next = words.get(i + 1).map(String::as_str);
+        match word.as_str() {
+            "folders" | "folder" | "directories" | "directory" | "carpetas" => filter.folders_only = true,
+            "hidden" | "ocultos" | "dotfiles" => filter.hidden = true,
+            "today" | "hoy" => filter.since = Some(Since::Today),
+            "yesterday" | "ayer" => filter.since = Some(Since::Yesterday),
+            "week" | "semana" => filter.since = Some(Since::Days(7)),
+            "month" | "mes" => filter.since = Some(Since::Days(30)),
+            "size" | "largest" | "biggest" | "tamaño" => filter.sort = Sort::Size,
+            "smallest" => filter.sort = Sort::SmallestFirst,
+            "date" | "newest" | "recent" | "latest" | "fecha" => filter.sort = Sort::Newest,
+            "modified" | "changed" if matches!(words.get(i.wrapping_sub(1)).map(String::as_str), Some("by")) => {
+                filter.sort = Sort::Newest
+            }
+            "oldest" => filter.sort = Sort::Oldest,
+            "type" | "extension" | "tipo" => filter.sort = Sort::Type,
+            "name" | "alphabetical" | "alphabetically" | "nombre" => filter.sort = Sort::Name,
+            pattern if pattern.contains(['*', '?']) => filter.patterns.push(pattern.to_string()),
+            extension if extension.starts_with('.') && extension.len() > 1 => {
+                filter.extensions.push(extension[1..].to_string())
+            }
+            kind if matches!(next, Some("files" | "file" | "archivos")) => {
+                filter.files_only = true;
+                match KINDS.iter().find(|(name, _)| *name == singular(kind)) {
+                    Some((_, extensions)) => filter.extensions.extend(extensions.iter().map(|e| e.to_string())),
+                    None if kind.chars().all(|c| c.is_ascii_alphanumeric()) && !is_filler(kind) => {
+                        filter.extensions.push(kind.to_string())
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    filter
+}
+
+fn is_filler(word: &str) -> bool {
+    matches!(word, "list" | "show" | "all" | "the" | "my" | "me" | "of" | "los" | "las" | "lista" | "muestra")
+}
+
+fn permissions(mode: u32, kind: &str) -> String {
+    let mut text = String::from(match kind {
+        "folder" => 'd',
+        "symlink" => 'l',
+        _ => '-',
+    });
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 2 != 0 { 'w' } else { '-' });
+        text.push(if bits & 1 != 0 { 'x' } else { '-' });
+    }
+    text
+}
+
+#[cfg(unix)]
+fn mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+/// Only the read-only flag is known elsewhere, so it stands in for the write bits.
+#[cfg(not(unix))]
+fn mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() { 0o444 } else { 0o644 }
+}
+
+fn describe(path: &Path) -> Option<Listing> {
+    let link = fs::symlink_metadata(path).ok()?;
+    // Size, type and time describe a symlink's target when it resolves.
+    let metadata = fs::metadata(path).unwrap_or_else(|_| link.clone());
+    let kind = if link.file_type().is_symlink() {
+        "symlink"
+    } else if metadata.is_dir() {
+        "folder"
+    } else {
+        "file"
+    };
+    let mode = mode(&metadata);
+    let modified_at = metadata.modified().ok().map(DateTime::<Local>::from);
+    Some(Listing {
+        name: path.file_name()?.to_string_lossy().into_owned(),
+        path: path.to_path_buf(),
+        kind,
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        permissions: permissions(mode, kind),
+        mode: format!("{:o}", mode),
+        modified: modified_at.map(|time| time.timestamp()),
+        modified_at,
+    })
+}
+
+fn is_folder(listing: &Listing) -> bool {
+    listing.kind == "folder" || (listing.kind == "symlink" && listing.path.is_dir())
+}
+
+fn matches(listing: &Listing, filter: &Filter, now: DateTime<Local>) -> bool {
+    if !filter.hidden && listing.name.starts_with('.') {
+        return false;
+    }
+    if filter.folders_only && !is_folder(listing) {
+        return false;
+    }
+    if (filter.files_only || !filter.extensions.is_empty()) && is_folder(listing) {
+        return false;
+    }
+    if !filter.extensions.is_empty() {
+        let extension = Path::new(&listing.name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !filter.extensions.contains(&extension) {
+            return false;
+        }
+    }
+    if !filter.patterns.is_empty() && !filter.patterns.iter().any(|p| paths::glob_match(p, &listing.name)) {
+        return false;
+    }
+    match (filter.since, listing.modified_at) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(Since::Today), Some(time)) => time.date_naive() == now.date_naive(),
+        (Some(Since::Yesterday), Some(time)) => time.date_naive() == (now - Duration::days(1)).date_naive(),
+        (Some(Since::Days(days)), Some(time)) => time >= now - Duration::days(days),
+    }
+}
+
+fn sort(listings: &mut [Listing], order: Sort) {
+    let extension = |l: &Listing| Path::new(&l.name).extension().map(|e| e.to_string_lossy().to_lowercase());
+    match order {
+        Sort::Name => listings.sort_by_key(|l| l.name.to_lowercase()),
+        Sort::Size => listings.sort_by_key(|l| Reverse(l.size)),
+        Sort::SmallestFirst => listings.sort_by_key(|l| l.size),
+        Sort::Newest => listings.sort_by_key(|l| Reverse(l.modified)),
+        Sort::Oldest => listings.sort_by_key(|l| l.modified),
+        Sort::Type => listings.sort_by_key(|l| (!is_folder(l), extension(l), l.name.to_lowercase())),
+    }
+}
+
+fn format_modified(time: Option<DateTime<Local>>, now: DateTime<Local>) -> String {
+    match time {
+        Some(time) if time.date_naive() == now.date_naive() => time.format("today %H:%M").to_string(),
+        Some(time) if time.year() == now.year() => format!("{} {}", locale::format_date(time.date_naive()), time.format("%H:%M")),
+        Some(time) => locale::format_date(time.date_naive()),
+        None => "-".to_string(),
+    }
+}
+
+fn list(arg: &str, folders_only: bool) -> String {
+    let (root, rest) = paths::place_or_cwd(arg);
+    let filter = parse_filter(&rest, folders_only);
+    let Ok(entries) = fs::read_dir(&root) else {
+        return tr!("read-directory-failed");
+    };
+
+    let now = Local::now();
+    let mut listings: Vec<Listing> = entries
+        .flatten()
+        .filter_map(|entry| describe(&entry.path()))
+        .filter(|listing| matches(listing, &filter, now))
+        .collect();
+    sort(&mut listings, filter.sort);
+
+    let place = paths::speakable(&root);
+    let count = listings.len().to_string();
+    let message = match (listings.len(), filter.folders_only) {
+        (0, _) => tr!("files-none", place = place),
+        (1, true) => tr!("folders-listed-one", place = place),
+        (1, false) => tr!("files-listed-one", place = place),
+        (_, true) => tr!("folders-listed", count = count, place = place),
+        (_, false) => tr!("files-listed", count = count, place = place),
+    };
+    let lines: Vec<String> = listings
+        .iter()
+        .map(|l| {
+            format!(
+                "{} {:>10}  {:<18} {}{}",
+                l.permissions,
+                if is_folder(l) { "-".to_string() } else { format_bytes(l.size) },
+                format_modified(l.modified_at, now),
+                l.name,
+                if is_folder(l) { "/" } else { "" }
+            )
+        })
+        .collect();
+    attach_data(json!({ "path": root, "entries": listings }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    if lines.is_empty() {
+        return message;
+    }
+    lines.join("\n")
+}
+
+/// `list_files [place] [pdf files] [today|yesterday|this week] [by size|date|name|type] [hidden]`.
+pub fn list_files(arg: &str) -> String {
+    list(arg, false)
+}
+
+/// `list_folders [place] [...]`: like `list_files`, folders only.
+pub fn list_folders(arg: &str) -> String {
+    list(arg, true)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_listing_filters() {
+        let filter = parse_filter("rust files modified this week by size", false);
+        assert!(filter.files_only && !filter.folders_only);
+        assert_eq!(filter.extensions, vec!["rs"]);
+        assert_eq!(filter.since, Some(Since::Days(7)));
+        assert_eq!(filter.sort, Sort::Size);
+
+        let filter = parse_filter("*.LOG .md hidden oldest", false);
+        assert_eq!(filter.patterns, vec!["*.log"]);
+        assert_eq!(filter.extensions, vec!["md"]);
+        assert!(filter.hidden);
+        assert_eq!(filter.sort, Sort::Oldest);
+
+        let filter = parse_filter("all files sorted by date modified", false);
+        assert!(filter.files_only && filter.extensions.is_empty());
+        assert_eq!(filter.sort, Sort::Newest);
+
+        assert_eq!(parse_filter("toml files", false).extensions, vec!["toml"]);
+        assert!(parse_filter("", true).folders_only);
+    }
+
+    #[test]
+    fn spells_permissions() {
+        assert_eq!(permissions(0o755, "folder"), "drwxr-xr-x");
+        assert_eq!(permissions(0o640, "file"), "-rw-r-----");
+    }
+}
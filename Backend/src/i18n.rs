@@ -0,0 +1,322 @@
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike};
+use std::{cell::RefCell, collections::HashMap, fs, sync::{Mutex, OnceLock}};
+
+use crate::{data_path, locale, speak_to_speaker};
+
+const LANGUAGE_FILE: &str = "language";
+const DEFAULT_LANGUAGE: &str = "en";
+
+struct Language {
+    code: &'static str,
+    name: &'static str,
+    aliases: &'static [&'static str],
+    messages: &'static str,
+    grammar: &'static str,
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        name: "English",
+        aliases: &["english", "inglés", "ingles"],
+        messages: include_str!("../locales/en/messages.ftl"),
+        grammar: include_str!("../locales/en/grammar.txt"),
+    },
+    Language {
+        code: "es",
+        name: "Español",
+        aliases: &["spanish", "español", "espanol", "castellano"],
+        messages: include_str!("../locales/es/messages.ftl"),
+        grammar: include_str!("../locales/es/grammar.txt"),
+    },
+];
+
+/// Translates a message id, substituting `{ $name }` placeholders:
+/// `tr!("file-created", name = cleaned_arg)`.
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+pub(crate) use tr;
+
+struct Catalog {
+    messages: HashMap<&'static str, HashMap<String, String>>,
+    // Per language: (normalized phrase, command), longest phrases first.
+    grammar: HashMap<&'static str, Vec<(String, String)>>,
+}
+
+/// Parses the subset of Fluent used by the catalogs: `key = value` lines,
+/// `#` comments, and indented continuation lines joined with newlines.
+fn parse_messages(source: &str) -> HashMap<String, String> {
+    let mut messages: HashMap<String, String> = HashMap::new();
+    let mut last_key: Option<String> = None;
+    for line in source.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            last_key = None;
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            if let Some(value) = last_key.as_ref().and_then(|key| messages.get_mut(key)) {
+                value.push('\n');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().to_string();
+            messages.insert(key.clone(), value.trim().to_string());
+            last_key = Some(key);
+        }
+    }
+    messages
+}
+
+fn parse_grammar(source: &str) -> Vec<(String, String)> {
+    let mut phrases: Vec<(String, String)> = source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .flat_map(|(command, phrases)| {
+            phrases
+                .split('|')
+                .map(normalize)
+                .filter(|phrase| !phrase.is_empty())
+                .map(move |phrase| (phrase, command.trim().to_string()))
+        })
+        .collect();
+    phrases.sort_by_key(|(phrase, _)| std::cmp::Reverse(phrase.len()));
+    phrases
+}
+
+fn catalog() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(|| Catalog {
+        messages: LANGUAGES.iter().map(|lang| (lang.code, parse_messages(lang.messages))).collect(),
+        grammar: LANGUAGES.iter().map(|lang| (lang.code, parse_grammar(lang.grammar))).collect(),
+    })
+}
+
+/// Lowercases and strips punctuation so "¿Qué hora es?" matches "qué hora es".
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| !matches!(c, '?' | '¿' | '!' | '¡' | ',' | '.'))
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn resolve_language(requested: &str) -> Option<&'static str> {
+    let requested = requested.trim().to_lowercase();
+    let primary = requested.split(['-', '_']).next().unwrap_or_default();
+    LANGUAGES
+        .iter()
+        .find(|lang| lang.code == primary || lang.aliases.contains(&requested.as_str()))
+        .map(|lang| lang.code)
+}
+
+static SESSION_LANGUAGE: Mutex<Option<&'static str>> = Mutex::new(None);
+
+thread_local! {
+    static REQUEST_LANGUAGE: RefCell<Option<&'static str>> = const { RefCell::new(None) };
+}
+
+fn session_language() -> &'static str {
+    SESSION_LANGUAGE.lock().unwrap().get_or_insert_with(|| {
+        fs::read_to_string(data_path(LANGUAGE_FILE))
+            .ok()
+            .and_then(|code| resolve_language(&code))
+            .unwrap_or(DEFAULT_LANGUAGE)
+    })
+}
+
+/// Overrides the language for the request being handled on this thread;
+/// `None` falls back to the session language.
+pub fn set_request_language(language: Option<&str>) {
+    let code = language.and_then(resolve_language);
+    REQUEST_LANGUAGE.with(|slot| *slot.borrow_mut() = code);
+}
+
+pub fn language() -> &'static str {
+    REQUEST_LANGUAGE
+        .with(|slot| *slot.borrow())
+        .unwrap_or_else(session_language)
+}
+
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    let catalog = catalog();
+    let template = [language(), DEFAULT_LANGUAGE]
+        .iter()
+        .find_map(|code| catalog.messages.get(code).and_then(|messages| messages.get(key)))
+        .cloned()
+        .unwrap_or_else(|| key.to_string());
+    args.iter().fold(template, |text, (name, value)| {
+        text.replace(&format!("{{ ${} }}", name), value)
+    })
+}
+
+pub fn month_name(month: u32) -> String {
+    translate(&format!("month-{}", month), &[])
+}
+
+/// Looks a spoken month name up in every catalog, so "marzo" works in English too.
+pub fn month_number(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    (1..=12).find(|month| {
+        catalog()
+            .messages
+            .values()
+            .any(|messages| messages.get(&format!("month-{}", month)).is_some_and(|m| m.to_lowercase() == name))
+    })
+}
+
+pub fn weekday_name(date: NaiveDate) -> String {
+    translate(&format!("weekday-{}", date.weekday().number_from_monday()), &[])
+}
+
+/// Speakable date in the active language. English uses the richer rendering
+/// from `locale`; other languages fill the `spoken-date` template.
+pub fn speak_date(date: NaiveDate, with_year: bool) -> String {
+    if language() == DEFAULT_LANGUAGE {
+        return locale::speak_date(date, with_year);
+    }
+    let key = if with_year { "spoken-date" } else { "spoken-date-short" };
+    tr!(
+        key,
+        weekday = weekday_name(date),
+        day = date.day(),
+        month = month_name(date.month()),
+        year = date.year()
+    )
+}
+
+pub fn speak_time(time: NaiveTime) -> String {
+    if language() == DEFAULT_LANGUAGE {
+        return locale::speak_time(time);
+    }
+    if time.minute() == 0 {
+        tr!("spoken-time-exact", hour = time.hour())
+    } else {
+        tr!("spoken-time", hour = time.hour(), minute = time.minute())
+    }
+}
+
+/// Maps an utterance onto a command using the active language's phrase
+/// grammar, returning the command line to dispatch.
+pub fn match_intent(utterance: &str) -> Option<String> {
+    let spoken = normalize(utterance);
+    let grammar = catalog().grammar.get(language())?;
+    let (phrase, command) = grammar
+        .iter()
+        .find(|(phrase, _)| spoken == *phrase || spoken.starts_with(&format!("{} ", phrase)))?;
+    let rest = argument_after(utterance, phrase.split_whitespace().count());
+    if rest.is_empty() {
+        Some(command.clone())
+    } else {
+        Some(format!("{} {}", command, rest))
+    }
+}
+
+/// The utterance after its first `phrase_words` words, as it was said: case,
+/// dots and commas survive, so file names, hosts and patterns arrive intact.
+/// Words that were only punctuation don't count towards the phrase.
+fn argument_after(utterance: &str, phrase_words: usize) -> String {
+    let mut words = utterance.split_whitespace().peekable();
+    let mut matched = 0;
+    while matched < phrase_words {
+        match words.next() {
+            Some(word) if !normalize(word).is_empty() => matched += 1,
+            Some(_) => {}
+            None => break,
+        }
+    }
+    words
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_start_matches(['¿', '¡'])
+        .trim_end_matches(['?', '!'])
+        .trim()
+        .to_string()
+}
+
+fn available() -> String {
+    LANGUAGES
+        .iter()
+        .map(|lang| format!("{} ({})", lang.name, lang.code))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `set_language es` or `set_language spanish`: changes the session language.
+pub fn set_language(arg: &str) -> String {
+    let Some(code) = resolve_language(arg) else {
+        return tr!("language-unknown", language = arg.trim(), available = available());
+    };
+    *SESSION_LANGUAGE.lock().unwrap() = Some(code);
+    REQUEST_LANGUAGE.with(|slot| *slot.borrow_mut() = None);
+    if let Err(e) = fs::write(data_path(LANGUAGE_FILE), code) {
+        return format!("Failed to save language: {}", e);
+    }
+
+    let name = LANGUAGES.iter().find(|lang| lang.code == code).map(|lang| lang.name).unwrap_or(code);
+    let message = tr!("language-set", language = name);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn list_languages(_arg: &str) -> String {
+    tr!("languages-available", available = available(), current = language())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intent(language: &str, utterance: &str) -> Option<String> {
+        set_request_language(Some(language));
+        match_intent(utterance)
+    }
+
+    #[test]
+    fn keeps_the_argument_as_spoken() {
+        assert_eq!(intent("en", "Look up example.internal?").as_deref(), Some("resolve example.internal"));
+        assert_eq!(
+            intent("en", "copy Report.pdf to /Users/Foo").as_deref(),
+            Some("copy_file_or_folder Report.pdf to /Users/Foo")
+        );
+        assert_eq!(intent("en", r"grep for \S+, TODO").as_deref(), Some(r"search_content \S+, TODO"));
+    }
+
+    #[test]
+    fn matches_longest_phrase_ignoring_case_and_punctuation() {
+        assert_eq!(intent("en", "What time is it in Tokyo?").as_deref(), Some("time_in Tokyo"));
+        assert_eq!(intent("en", "What time is it?").as_deref(), Some("current_time"));
+        assert_eq!(intent("es", "¿Qué hora es?").as_deref(), Some("current_time"));
+        assert_eq!(intent("es", "¿ Dónde está mi Currículum ?").as_deref(), Some("find_file Currículum"));
+        assert_eq!(intent("en", "sing a song"), None);
+    }
+
+    #[test]
+    fn parses_fluent_subset() {
+        let messages = parse_messages("# comment\ngreeting = Hello { $name }\nlong = First\n  second\n");
+        assert_eq!(messages["greeting"], "Hello { $name }");
+        assert_eq!(messages["long"], "First\nsecond");
+    }
+
+    #[test]
+    fn translates_with_fallback() {
+        set_request_language(Some("es"));
+        assert_eq!(tr!("file-created", name = "a.txt"), "Archivo 'a.txt' creado correctamente.");
+        assert_eq!(translate("no-such-key", &[]), "no-such-key");
+        set_request_language(Some("en"));
+        assert_eq!(month_number("marzo"), Some(3));
+    }
+}
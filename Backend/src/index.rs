@@ -0,0 +1,686 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use walkdir::WalkDir;
+
+use crate::{attach_data, data_path, i18n::tr, paths, search, speak_to_speaker};
+
+const SETTINGS_FILE: &str = "file_index_settings.json";
+const INDEX_FILE: &str = "file_index.json";
+/// Changes are written to disk at most this often.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+const POLL_TIMEOUT_MS: i32 = 1000;
+#[cfg(target_os = "linux")]
+const WATCH_MASK: u32 = libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_CLOSE_WRITE
+    | libc::IN_ONLYDIR
+    | libc::IN_DONT_FOLLOW;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Settings {
+    roots: Vec<PathBuf>,
+    /// Index dot-files and dot-folders too.
+    hidden: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { roots: dirs::home_dir().into_iter().collect(), hidden: false }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Indexed {
+    is_dir: bool,
+    modified: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    roots: Vec<PathBuf>,
+    built_at: Option<i64>,
+    entries: BTreeMap<PathBuf, Indexed>,
+}
+
+#[derive(Debug, Serialize)]
+struct Found {
+    path: PathBuf,
+    is_dir: bool,
+    rank: u32,
+    modified: Option<i64>,
+}
+
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+/// `None` until the saved index is loaded or the first build finishes.
+static INDEX: Mutex<Option<Index>> = Mutex::new(None);
+static BUILDING: AtomicBool = AtomicBool::new(false);
+static REBUILD: AtomicBool = AtomicBool::new(false);
+static DIRTY: AtomicBool = AtomicBool::new(false);
+static WATCHED: AtomicUsize = AtomicUsize::new(0);
+/// Set when the kernel refused a watch because `max_user_watches` ran out.
+static WATCH_LIMIT_HIT: AtomicBool = AtomicBool::new(false);
+
+fn settings() -> Settings {
+    let mut cached = SETTINGS.lock().unwrap();
+    cached
+        .get_or_insert_with(|| {
+            fs::read_to_string(data_path(SETTINGS_FILE))
+                .ok()
+                .and_then(|text| serde_json::from_str(&text).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+fn save_settings(settings: Settings) -> io::Result<()> {
+    fs::write(data_path(SETTINGS_FILE), serde_json::to_string_pretty(&settings)?)?;
+    *SETTINGS.lock().unwrap() = Some(settings);
+    Ok(())
+}
+
+fn load_index() -> Option<Index> {
+    let text = fs::read_to_string(data_path(INDEX_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Writes through a temporary file so a crash never leaves half an index.
+fn save_index() -> io::Result<()> {
+    let text = match INDEX.lock().unwrap().as_ref() {
+        Some(index) => serde_json::to_string(index)?,
+        None => return Ok(()),
+    };
+    DIRTY.store(false, Ordering::Relaxed);
+    let path = data_path(INDEX_FILE);
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, text)?;
+    fs::rename(temporary, path)
+}
+
+fn is_hidden(name: &OsStr) -> bool {
+    name.as_encoded_bytes().first() == Some(&b'.')
+}
+
+fn indexed(metadata: Option<fs::Metadata>, is_dir: bool) -> Indexed {
+    let modified = metadata.and_then(|m| m.modified().ok()).map(|time| DateTime::<Local>::from(time).timestamp());
+    Indexed { is_dir, modified }
+}
+
+// --- inotify -----------------------------------------------------------------
+
+#[derive(PartialEq)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+enum Change {
+    Added,
+    Removed,
+    Modified,
+}
+
+struct Event {
+    directory: PathBuf,
+    name: OsString,
+    is_dir: bool,
+    change: Change,
+}
+
+#[cfg(target_os = "linux")]
+struct Watcher {
+    fd: std::os::fd::OwnedFd,
+    directories: std::collections::HashMap<i32, PathBuf>,
+}
+
+#[cfg(target_os = "linux")]
+impl Watcher {
+    fn new() -> Option<Watcher> {
+        use std::os::fd::{FromRawFd, OwnedFd};
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+        if fd < 0 {
+            return None;
+        }
+        Some(Watcher { fd: unsafe { OwnedFd::from_raw_fd(fd) }, directories: Default::default() })
+    }
+
+    fn watch(&mut self, directory: &Path) {
+        use std::{ffi::CString, os::fd::AsRawFd, os::unix::ffi::OsStrExt};
+        let Ok(path) = CString::new(directory.as_os_str().as_bytes()) else {
+            return;
+        };
+        let wd = unsafe { libc::inotify_add_watch(self.fd.as_raw_fd(), path.as_ptr(), WATCH_MASK) };
+        if wd < 0 {
+            if io::Error::last_os_error().raw_os_error() == Some(libc::ENOSPC) {
+                WATCH_LIMIT_HIT.store(true, Ordering::Relaxed);
+            }
+            return;
+        }
+        // Watching the same directory again returns its existing descriptor,
+        // so a directory moved within the tree just gets its new path.
+        self.directories.insert(wd, directory.to_path_buf());
+        WATCHED.store(self.directories.len(), Ordering::Relaxed);
+    }
+
+    fn unwatch_under(&mut self, directory: &Path) {
+        use std::os::fd::AsRawFd;
+        let fd = self.fd.as_raw_fd();
+        self.directories.retain(|wd, path| {
+            let keep = !path.starts_with(directory);
+            if !keep {
+                unsafe { libc::inotify_rm_watch(fd, *wd) };
+            }
+            keep
+        });
+        WATCHED.store(self.directories.len(), Ordering::Relaxed);
+    }
+
+    /// Waits up to `POLL_TIMEOUT_MS` for events. `None` means the queue
+    /// overflowed and the index has to be rebuilt from scratch.
+    fn next_events(&mut self) -> Option<Vec<Event>> {
+        use std::{os::fd::AsRawFd, os::unix::ffi::OsStrExt};
+        let mut poll = libc::pollfd { fd: self.fd.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        if unsafe { libc::poll(&mut poll, 1, POLL_TIMEOUT_MS) } <= 0 {
+            return Some(Vec::new());
+        }
+        let mut buffer = vec![0u8; 64 * 1024];
+        let read = unsafe { libc::read(self.fd.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len()) };
+        if read <= 0 {
+            return Some(Vec::new());
+        }
+
+        let header = std::mem::size_of::<libc::inotify_event>();
+        let mut events = Vec::new();
+        let mut offset = 0;
+        while offset + header <= read as usize {
+            let event: libc::inotify_event = unsafe { std::ptr::read_unaligned(buffer[offset..].as_ptr().cast()) };
+            let name = &buffer[offset + header..offset + header + event.len as usize];
+            offset += header + event.len as usize;
+            if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                return None;
+            }
+            if event.mask & libc::IN_IGNORED != 0 {
+                self.directories.remove(&event.wd);
+                continue;
+            }
+            let Some(directory) = self.directories.get(&event.wd) else {
+                continue;
+            };
+            let name = name.split(|b| *b == 0).next().unwrap_or_default();
+            if name.is_empty() {
+                continue;
+            }
+            let change = if event.mask & (libc::IN_DELETE | libc::IN_MOVED_FROM) != 0 {
+                Change::Removed
+            } else if event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 {
+                Change::Added
+            } else {
+                Change::Modified
+            };
+            events.push(Event {
+                directory: directory.clone(),
+                name: OsStr::from_bytes(name).to_os_string(),
+                is_dir: event.mask & libc::IN_ISDIR != 0,
+                change,
+            });
+        }
+        Some(events)
+    }
+}
+
+/// Without inotify nothing is watched; the index is only rebuilt on request.
+#[cfg(not(target_os = "linux"))]
+enum Watcher {}
+
+#[cfg(not(target_os = "linux"))]
+impl Watcher {
+    fn new() -> Option<Watcher> {
+        None
+    }
+
+    fn watch(&mut self, _directory: &Path) {
+        match *self {}
+    }
+
+    fn unwatch_under(&mut self, _directory: &Path) {
+        match *self {}
+    }
+
+    fn next_events(&mut self) -> Option<Vec<Event>> {
+        match *self {}
+    }
+}
+
+// --- Building and updating -----------------------------------------------------
+
+/// Adds everything under `top` to `entries`, watching each directory found.
+fn scan(top: &Path, hidden: bool, entries: &mut BTreeMap<PathBuf, Indexed>, mut watcher: Option<&mut Watcher>) {
+    let walker = WalkDir::new(top)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| hidden || entry.depth() == 0 || !is_hidden(entry.file_name()));
+    for entry in walker.flatten() {
+        // Paths that aren't UTF-8 can't be stored in the JSON index.
+        if entry.path().to_str().is_none() {
+            continue;
+        }
+        let is_dir = entry.file_type().is_dir();
+        if is_dir {
+            if let Some(watcher) = watcher.as_deref_mut() {
+                watcher.watch(entry.path());
+            }
+        }
+        entries.insert(entry.path().to_path_buf(), indexed(entry.metadata().ok(), is_dir));
+    }
+}
+
+fn build(settings: &Settings, mut watcher: Option<&mut Watcher>) {
+    BUILDING.store(true, Ordering::Relaxed);
+    WATCH_LIMIT_HIT.store(false, Ordering::Relaxed);
+    let mut entries = BTreeMap::new();
+    for root in &settings.roots {
+        scan(root, settings.hidden, &mut entries, watcher.as_deref_mut());
+        // The roots themselves are places, not answers.
+        entries.remove(root);
+    }
+    // Lookups keep using the previous index until the new one is complete.
+    *INDEX.lock().unwrap() = Some(Index {
+        roots: settings.roots.clone(),
+        built_at: Some(Local::now().timestamp()),
+        entries,
+    });
+    BUILDING.store(false, Ordering::Relaxed);
+    if let Err(e) = save_index() {
+        eprintln!("Failed to save the file index: {}", e);
+    }
+}
+
+/// The path an event is about, or `None` when it is not indexed.
+fn event_path(event: &Event, hidden: bool) -> Option<PathBuf> {
+    if !hidden && is_hidden(&event.name) {
+        return None;
+    }
+    let path = event.directory.join(&event.name);
+    path.to_str().is_some().then_some(path)
+}
+
+impl Index {
+    /// Applies one change. `scanned` holds the contents of a folder that was
+    /// added, since a folder moved in arrives without events for them.
+    fn apply(&mut self, event: &Event, hidden: bool, scanned: BTreeMap<PathBuf, Indexed>) {
+        let Some(path) = event_path(event, hidden) else {
+            return;
+        };
+        match event.change {
+            Change::Removed => {
+                self.entries.remove(&path);
+                if event.is_dir {
+                    let inside: Vec<PathBuf> = self
+                        .entries
+                        .range(path.clone()..)
+                        .take_while(|(p, _)| p.starts_with(&path))
+                        .map(|(p, _)| p.clone())
+                        .collect();
+                    for p in inside {
+                        self.entries.remove(&p);
+                    }
+                }
+            }
+            Change::Added if event.is_dir => self.entries.extend(scanned),
+            _ => {
+                let entry = indexed(fs::symlink_metadata(&path).ok(), event.is_dir);
+                self.entries.insert(path, entry);
+            }
+        }
+    }
+}
+
+fn apply(event: Event, settings: &Settings, watcher: &mut Watcher) {
+    let Some(path) = event_path(&event, settings.hidden) else {
+        return;
+    };
+    // Folders are scanned before the index is locked, so lookups don't wait.
+    let mut scanned = BTreeMap::new();
+    match event.change {
+        Change::Added if event.is_dir => scan(&path, settings.hidden, &mut scanned, Some(watcher)),
+        Change::Removed if event.is_dir => watcher.unwatch_under(&path),
+        _ => {}
+    }
+    if let Some(index) = INDEX.lock().unwrap().as_mut() {
+        index.apply(&event, settings.hidden, scanned);
+        DIRTY.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Applies filesystem changes until the roots change or events are lost.
+fn follow(settings: &Settings, watcher: &mut Watcher) {
+    let mut saved = Instant::now();
+    while !REBUILD.load(Ordering::Relaxed) {
+        match watcher.next_events() {
+            Some(events) => {
+                for event in events {
+                    apply(event, settings, watcher);
+                }
+            }
+            None => return,
+        }
+        if DIRTY.load(Ordering::Relaxed) && saved.elapsed() >= SAVE_INTERVAL {
+            if let Err(e) = save_index() {
+                eprintln!("Failed to save the file index: {}", e);
+            }
+            saved = Instant::now();
+        }
+    }
+}
+
+/// Loads the saved index, rebuilds it in the background and then keeps it
+/// current with inotify.
+pub fn start() {
+    thread::spawn(|| {
+        if let Some(saved) = load_index() {
+            *INDEX.lock().unwrap() = Some(saved);
+        }
+        loop {
+            REBUILD.store(false, Ordering::Relaxed);
+            let settings = settings();
+            let mut watcher = Watcher::new();
+            build(&settings, watcher.as_mut());
+            match watcher.as_mut() {
+                Some(watcher) => follow(&settings, watcher),
+                None => {
+                    while !REBUILD.load(Ordering::Relaxed) {
+                        thread::sleep(Duration::from_millis(POLL_TIMEOUT_MS as u64));
+                    }
+                }
+            }
+            if DIRTY.load(Ordering::Relaxed) {
+                let _ = save_index();
+            }
+        }
+    });
+}
+
+// --- Commands ------------------------------------------------------------------
+
+impl Index {
+    /// Every entry matching the query, best first: by rank, then most recently
+    /// modified, then shallowest.
+    fn find(&self, query: &search::NameQuery, within: Option<&Path>) -> Vec<Found> {
+        let mut found: Vec<Found> = self
+            .entries
+            .iter()
+            .filter(|(path, _)| within.is_none_or(|within| path.starts_with(within)))
+            .filter(|(_, entry)| if entry.is_dir { !query.files_only } else { !query.folders_only })
+            .filter_map(|(path, entry)| {
+                let name = path.file_name()?.to_string_lossy();
+                let rank = search::rank(&query.pattern, &name, query.fuzzy)?;
+                Some(Found { path: path.clone(), is_dir: entry.is_dir, rank, modified: entry.modified })
+            })
+            .collect();
+        found.sort_by(|a, b| {
+            a.rank
+                .cmp(&b.rank)
+                .then(b.modified.cmp(&a.modified))
+                .then(a.path.components().count().cmp(&b.path.components().count()))
+                .then(a.path.cmp(&b.path))
+        });
+        found
+    }
+}
+
+fn speak(message: String) -> String {
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `find_file resume [in place] [limit N] [exact]`: looks a name up in the
+/// file index, best match first.
+pub fn find_file(arg: &str) -> String {
+    let (within, rest) = paths::place(arg);
+    let query = search::parse_name_query(&rest);
+    if query.pattern.is_empty() {
+        return tr!("file-name-missing");
+    }
+
+    let (mut found, built_at) = match INDEX.lock().unwrap().as_ref() {
+        Some(index) => (index.find(&query, within.as_deref()), index.built_at),
+        None => return "Sorry, the file index is still being built. Try again in a moment.".to_string(),
+    };
+    let total = found.len();
+    found.truncate(query.limit);
+    let Some(best) = found.first() else {
+        return tr!("search-not-found", name = query.pattern);
+    };
+
+    let best_path = best.path.display().to_string();
+    let message = if total == 1 {
+        tr!("search-found", path = best_path)
+    } else {
+        tr!("search-found-many", count = total.to_string(), name = query.pattern, path = best_path)
+    };
+    let lines: Vec<String> = found
+        .iter()
+        .map(|f| format!("{}{}", f.path.display(), if f.is_dir { "/" } else { "" }))
+        .collect();
+    attach_data(json!({
+        "pattern": query.pattern,
+        "total": total,
+        "indexed_at": built_at,
+        "matches": found,
+    }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+/// `index_status`: what is indexed and whether changes are being followed.
+pub fn index_status(_arg: &str) -> String {
+    let settings = settings();
+    let (count, built_at) = match INDEX.lock().unwrap().as_ref() {
+        Some(index) => (index.entries.len(), index.built_at),
+        None => (0, None),
+    };
+    let watched = WATCHED.load(Ordering::Relaxed);
+    let building = BUILDING.load(Ordering::Relaxed);
+    let limited = WATCH_LIMIT_HIT.load(Ordering::Relaxed);
+
+    let roots: Vec<String> = settings.roots.iter().map(|root| paths::speakable(root)).collect();
+    let mut message = format!("The file index has {} entries from {}.", count, roots.join(", "));
+    if building {
+        message.push_str(" It is being rebuilt now.");
+    }
+    if limited {
+        message.push_str(" Some folders are not watched because the inotify watch limit was reached.");
+    }
+    let mut lines = vec![
+        format!("Entries: {}", count),
+        format!("Watched folders: {}", watched),
+        format!("Roots: {}", settings.roots.iter().map(|r| r.display().to_string()).collect::<Vec<_>>().join(", ")),
+        format!("Hidden files: {}", if settings.hidden { "indexed" } else { "skipped" }),
+    ];
+    if let Some(time) = built_at.and_then(|t| DateTime::from_timestamp(t, 0)) {
+        lines.push(format!("Last full scan: {}", time.with_timezone(&Local).format("%Y-%m-%d %H:%M")));
+    }
+    if limited {
+        lines.push("Watch limit reached; raise fs.inotify.max_user_watches to follow every folder".to_string());
+    }
+    attach_data(json!({
+        "entries": count,
+        "watched": watched,
+        "building": building,
+        "watch_limit_hit": limited,
+        "built_at": built_at,
+        "settings": settings,
+    }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+fn rebuild_with(settings: Settings, message: String) -> String {
+    if let Err(e) = save_settings(settings) {
+        return format!("Error: Failed to save the index settings: {}", e);
+    }
+    REBUILD.store(true, Ordering::Relaxed);
+    speak(message)
+}
+
+/// The roots after adding `root`, or `None` when it is already covered. A new
+/// root that contains existing ones replaces them.
+fn with_root(roots: &[PathBuf], root: &Path) -> Option<Vec<PathBuf>> {
+    if roots.iter().any(|existing| root.starts_with(existing)) {
+        return None;
+    }
+    let mut roots: Vec<PathBuf> = roots.iter().filter(|existing| !existing.starts_with(root)).cloned().collect();
+    roots.push(root.to_path_buf());
+    Some(roots)
+}
+
+/// `add_index_root documents`: indexes another folder.
+pub fn add_index_root(arg: &str) -> String {
+    let Some(root) = paths::place(arg).0 else {
+        return "Error: Say which folder to index, such as documents or /srv/share.".to_string();
+    };
+    if !root.is_dir() {
+        return format!("Error: {} is not a folder.", root.display());
+    }
+    let mut settings = settings();
+    let Some(roots) = with_root(&settings.roots, &root) else {
+        return speak(format!("{} is already indexed.", paths::speakable(&root)));
+    };
+    settings.roots = roots;
+    rebuild_with(settings, format!("Indexing {}.", paths::speakable(&root)))
+}
+
+/// `remove_index_root downloads`: stops indexing a folder.
+pub fn remove_index_root(arg: &str) -> String {
+    let Some(root) = paths::place(arg).0 else {
+        return "Error: Say which indexed folder to remove.".to_string();
+    };
+    let mut settings = settings();
+    let before = settings.roots.len();
+    settings.roots.retain(|existing| *existing != root);
+    if settings.roots.len() == before {
+        return format!("Error: {} is not an indexed folder.", root.display());
+    }
+    rebuild_with(settings, format!("No longer indexing {}.", paths::speakable(&root)))
+}
+
+/// `rebuild_index [hidden|no hidden]`: rescans every root from scratch.
+pub fn rebuild_index(arg: &str) -> String {
+    let mut settings = settings();
+    let words: Vec<String> = arg.split_whitespace().map(str::to_lowercase).collect();
+    if words.iter().any(|w| w == "hidden") {
+        settings.hidden = !words.iter().any(|w| w == "no" || w == "without");
+    }
+    rebuild_with(settings, "Rebuilding the file index.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aurora-index-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn event(directory: &Path, name: &str, is_dir: bool, change: Change) -> Event {
+        Event { directory: directory.to_path_buf(), name: name.into(), is_dir, change }
+    }
+
+    #[test]
+    fn follows_changes() {
+        let dir = temp_dir("changes");
+        let root = dir.join("root");
+        for file in ["a/x.txt", "a/y/z.txt", "ab.txt", "b.txt", "outside/moved/f.txt", "outside/moved/.git/config"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        let mut index = Index::default();
+        scan(&root, false, &mut index.entries, None);
+        index.entries.remove(&root);
+        index.entries.retain(|path, _| !path.starts_with(root.join("outside")));
+        assert_eq!(index.entries.len(), 6);
+
+        fs::write(root.join("new.txt"), "new").unwrap();
+        index.apply(&event(&root, "new.txt", false, Change::Added), false, BTreeMap::new());
+        assert!(index.entries.get(&root.join("new.txt")).is_some_and(|entry| !entry.is_dir && entry.modified.is_some()));
+
+        index.apply(&event(&root, ".secret", false, Change::Added), false, BTreeMap::new());
+        assert!(!index.entries.contains_key(&root.join(".secret")));
+        index.apply(&event(&root, ".secret", false, Change::Added), true, BTreeMap::new());
+        assert!(index.entries.contains_key(&root.join(".secret")));
+
+        index.apply(&event(&root, "a", true, Change::Removed), false, BTreeMap::new());
+        assert!(!index.entries.keys().any(|path| path.starts_with(root.join("a"))));
+        assert!(index.entries.contains_key(&root.join("ab.txt")));
+        assert!(index.entries.contains_key(&root.join("b.txt")));
+
+        fs::rename(root.join("outside/moved"), root.join("moved")).unwrap();
+        let mut scanned = BTreeMap::new();
+        scan(&root.join("moved"), false, &mut scanned, None);
+        index.apply(&event(&root, "moved", true, Change::Added), false, scanned);
+        assert!(index.entries.get(&root.join("moved")).is_some_and(|entry| entry.is_dir));
+        assert!(index.entries.contains_key(&root.join("moved/f.txt")));
+        assert!(!index.entries.keys().any(|path| path.starts_with(root.join("moved/.git"))));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn finds_filtered_entries_best_first() {
+        let entry = |is_dir, modified| Indexed { is_dir, modified };
+        let index = Index {
+            roots: vec![PathBuf::from("/p")],
+            built_at: None,
+            entries: BTreeMap::from([
+                (PathBuf::from("/p/docs/resume.pdf"), entry(false, Some(2))),
+                (PathBuf::from("/p/old/resume.pdf"), entry(false, Some(1))),
+                (PathBuf::from("/p/resume"), entry(true, None)),
+                (PathBuf::from("/p/docs/resumes-archive"), entry(true, None)),
+                (PathBuf::from("/p/notes.txt"), entry(false, Some(3))),
+            ]),
+        };
+        let find = |query: &str, within: Option<&str>| -> Vec<String> {
+            index
+                .find(&search::parse_name_query(query), within.map(Path::new))
+                .into_iter()
+                .map(|found| found.path.display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            find("resume", None),
+            ["/p/docs/resume.pdf", "/p/old/resume.pdf", "/p/resume", "/p/docs/resumes-archive"]
+        );
+        assert_eq!(find("file resume", None), ["/p/docs/resume.pdf", "/p/old/resume.pdf"]);
+        assert_eq!(find("folder resume", None), ["/p/resume", "/p/docs/resumes-archive"]);
+        assert_eq!(find("resume", Some("/p/docs")), ["/p/docs/resume.pdf", "/p/docs/resumes-archive"]);
+        assert!(find("rsme exact", None).is_empty());
+    }
+
+    #[test]
+    fn new_roots_replace_the_roots_they_contain() {
+        let roots: Vec<PathBuf> = ["/h/u/docs", "/h/u/music", "/srv"].iter().map(PathBuf::from).collect();
+        assert_eq!(with_root(&roots, Path::new("/h/u")), Some(vec![PathBuf::from("/srv"), PathBuf::from("/h/u")]));
+        assert_eq!(with_root(&roots, Path::new("/h/u/docs/work")), None);
+        assert_eq!(with_root(&roots, Path::new("/srv")), None);
+        assert_eq!(with_root(&roots, Path::new("/h/u/docs2")).map(|roots| roots.len()), Some(4));
+    }
+}
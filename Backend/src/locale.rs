@@ -0,0 +1,354 @@
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::{fs, sync::Mutex};
+
+use crate::{data_path, i18n, speak_to_speaker};
+
+const LOCALE_FILE: &str = "locale.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DateOrder {
+    Dmy,
+    Mdy,
+    Ymd,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MonthStyle {
+    Long,
+    Short,
+    Numeric,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocaleSettings {
+    pub name: String,
+    pub date_order: DateOrder,
+    pub hour12: bool,
+    pub month_style: MonthStyle,
+    pub decimal_separator: char,
+    pub thousands_separator: Option<char>,
+}
+
+impl Default for LocaleSettings {
+    fn default() -> Self {
+        preset("en-GB").unwrap()
+    }
+}
+
+fn preset(name: &str) -> Option<LocaleSettings> {
+    let (date_order, hour12, decimal_separator, thousands_separator) = match name.to_lowercase().replace('_', "-").as_str() {
+        "en-gb" | "en-au" | "en-nz" => (DateOrder::Dmy, false, '.', Some(',')),
+        "en-us" => (DateOrder::Mdy, true, '.', Some(',')),
+        "en-in" => (DateOrder::Dmy, true, '.', Some(',')),
+        "en-ca" => (DateOrder::Ymd, true, '.', Some(',')),
+        "de-de" | "nl-nl" | "es-es" | "it-it" => (DateOrder::Dmy, false, ',', Some('.')),
+        "fr-fr" => (DateOrder::Dmy, false, ',', Some(' ')),
+        "ja-jp" | "zh-cn" | "sv-se" => (DateOrder::Ymd, false, '.', Some(',')),
+        _ => return None,
+    };
+    let name = match name.split_once(['-', '_']) {
+        Some((language, region)) => format!("{}-{}", language.to_lowercase(), region.to_uppercase()),
+        None => name.to_string(),
+    };
+    Some(LocaleSettings {
+        name,
+        date_order,
+        hour12,
+        month_style: MonthStyle::Long,
+        decimal_separator,
+        thousands_separator,
+    })
+}
+
+static SETTINGS: Mutex<Option<LocaleSettings>> = Mutex::new(None);
+
+pub fn settings() -> LocaleSettings {
+    let mut cached = SETTINGS.lock().unwrap();
+    cached
+        .get_or_insert_with(|| {
+            fs::read_to_string(data_path(LOCALE_FILE))
+                .ok()
+                .and_then(|text| serde_json::from_str(&text).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+fn save_settings(settings: LocaleSettings) -> std::io::Result<()> {
+    fs::write(data_path(LOCALE_FILE), serde_json::to_string_pretty(&settings)?)?;
+    *SETTINGS.lock().unwrap() = Some(settings);
+    Ok(())
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    let settings = settings();
+    // Month names follow the session language; the rest of the layout follows the locale.
+    let month = match settings.month_style {
+        MonthStyle::Long => i18n::month_name(date.month()),
+        MonthStyle::Short => i18n::month_name(date.month()).chars().take(3).collect(),
+        MonthStyle::Numeric => date.format("%m").to_string(),
+    };
+    let separator = if settings.month_style == MonthStyle::Numeric { "/" } else { " " };
+    let (day, year) = (date.format("%d").to_string(), date.year().to_string());
+    let parts = match settings.date_order {
+        DateOrder::Dmy => [day, month, year],
+        DateOrder::Mdy => [month, day, year],
+        DateOrder::Ymd => [year, month, day],
+    };
+    if settings.date_order == DateOrder::Mdy && settings.month_style != MonthStyle::Numeric {
+        format!("{} {}, {}", parts[0], parts[1], parts[2])
+    } else {
+        parts.join(separator)
+    }
+}
+
+pub fn format_time(time: NaiveTime) -> String {
+    if settings().hour12 {
+        time.format("%I:%M:%S %p").to_string()
+    } else {
+        time.format("%H:%M:%S").to_string()
+    }
+}
+
+/// Formats a number with the locale's decimal and thousands separators.
+pub fn format_decimal(value: f64, decimals: usize) -> String {
+    let settings = settings();
+    let text = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            if let Some(separator) = settings.thousands_separator {
+                grouped.push(separator);
+            }
+        }
+        grouped.push(digit);
+    }
+    let sign = if value < 0.0 && text.chars().any(|c| c != '0' && c != '.') { "-" } else { "" };
+    if fraction.is_empty() {
+        format!("{}{}", sign, grouped)
+    } else {
+        format!("{}{}{}{}", sign, grouped, settings.decimal_separator, fraction)
+    }
+}
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve",
+    "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS: [&str; 10] = ["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+/// Spells out numbers below one million, e.g. 215 -> "two hundred and fifteen".
+pub fn number_words(n: u32) -> String {
+    match n {
+        0..=19 => ONES[n as usize].to_string(),
+        20..=99 if n.is_multiple_of(10) => TENS[(n / 10) as usize].to_string(),
+        20..=99 => format!("{}-{}", TENS[(n / 10) as usize], ONES[(n % 10) as usize]),
+        100..=999 if n.is_multiple_of(100) => format!("{} hundred", ONES[(n / 100) as usize]),
+        100..=999 => format!("{} hundred and {}", ONES[(n / 100) as usize], number_words(n % 100)),
+        1000..=999_999 if n.is_multiple_of(1000) => format!("{} thousand", number_words(n / 1000)),
+        1000..=999_999 => {
+            let joiner = if n % 1000 < 100 { " and " } else { " " };
+            format!("{} thousand{}{}", number_words(n / 1000), joiner, number_words(n % 1000))
+        }
+        _ => n.to_string(),
+    }
+}
+
+/// Spells out ordinals, e.g. 18 -> "eighteenth", 22 -> "twenty-second".
+pub fn ordinal_words(n: u32) -> String {
+    let words = number_words(n);
+    let irregular = [
+        ("one", "first"),
+        ("two", "second"),
+        ("three", "third"),
+        ("five", "fifth"),
+        ("eight", "eighth"),
+        ("nine", "ninth"),
+        ("twelve", "twelfth"),
+    ];
+    for (cardinal, ordinal) in irregular {
+        if let Some(stem) = words.strip_suffix(cardinal) {
+            return format!("{}{}", stem, ordinal);
+        }
+    }
+    match words.strip_suffix('y') {
+        Some(stem) => format!("{}ieth", stem),
+        None => format!("{}th", words),
+    }
+}
+
+/// Years are read in pairs the way people say them: 1999 -> "nineteen
+/// ninety-nine", 2026 -> "twenty twenty-six", 2005 -> "two thousand five".
+fn year_words(year: i32) -> String {
+    let year = year.unsigned_abs();
+    let (high, low) = (year / 100, year % 100);
+    match (high, low) {
+        (_, _) if !(1100..10000).contains(&year) => number_words(year),
+        (20, 0) => number_words(year),
+        (20, 1..=9) => format!("{} {}", number_words(2000), number_words(low)),
+        (_, 0) => format!("{} hundred", number_words(high)),
+        (_, 1..=9) => format!("{} oh {}", number_words(high), number_words(low)),
+        _ => format!("{} {}", number_words(high), number_words(low)),
+    }
+}
+
+/// Reads a time aloud, e.g. "nine fifteen in the morning" or "twelve noon".
+pub fn speak_time(time: NaiveTime) -> String {
+    let (hour, minute) = (time.hour(), time.minute());
+    match (hour, minute) {
+        (0, 0) => return "midnight".to_string(),
+        (12, 0) => return "twelve noon".to_string(),
+        _ => {}
+    }
+
+    let hour12 = match hour % 12 {
+        0 => 12,
+        h => h,
+    };
+    let minutes = match minute {
+        0 => " o'clock".to_string(),
+        1..=9 => format!(" oh {}", number_words(minute)),
+        _ => format!(" {}", number_words(minute)),
+    };
+    let period = match hour {
+        0..=11 => "in the morning",
+        12..=16 => "in the afternoon",
+        17..=20 => "in the evening",
+        _ => "at night",
+    };
+    format!("{}{} {}", number_words(hour12), minutes, period)
+}
+
+/// "Sunday the eighteenth of October", optionally followed by the year.
+pub fn speak_date(date: NaiveDate, with_year: bool) -> String {
+    let spoken = format!("{} the {} of {}", date.format("%A"), ordinal_words(date.day()), date.format("%B"));
+    if with_year {
+        format!("{}, {}", spoken, year_words(date.year()))
+    } else {
+        spoken
+    }
+}
+
+/// "nine fifteen in the morning, Sunday the eighteenth of October".
+pub fn speak_datetime(moment: NaiveDateTime) -> String {
+    format!("{}, {}", speak_time(moment.time()), speak_date(moment.date(), false))
+}
+
+fn describe(settings: &LocaleSettings) -> String {
+    format!(
+        "Locale {}: date order {:?}, {} clock, {:?} month names, decimal separator '{}', thousands separator '{}'. Example: {} {}, {}",
+        settings.name,
+        settings.date_order,
+        if settings.hour12 { "12-hour" } else { "24-hour" },
+        settings.month_style,
+        settings.decimal_separator,
+        settings.thousands_separator.map(String::from).unwrap_or_default(),
+        format_date(Local::now().date_naive()),
+        format_time(Local::now().time()),
+        format_decimal(1234.5, 1)
+    )
+}
+
+/// `set_locale en-US`, or adjust one setting: `set_locale 12 hour`,
+/// `set_locale month first`, `set_locale short months`, `set_locale decimal comma`.
+pub fn set_locale(arg: &str) -> String {
+    let lowered = arg.trim().to_lowercase();
+    let mut updated = settings();
+
+    if let Some(preset) = preset(&lowered) {
+        updated = preset;
+    } else {
+        match lowered.replace('-', " ").as_str() {
+            "12 hour" | "12 hour clock" | "twelve hour" => updated.hour12 = true,
+            "24 hour" | "24 hour clock" | "twenty four hour" => updated.hour12 = false,
+            "day first" => updated.date_order = DateOrder::Dmy,
+            "month first" => updated.date_order = DateOrder::Mdy,
+            "year first" => updated.date_order = DateOrder::Ymd,
+            "long months" | "month names" => updated.month_style = MonthStyle::Long,
+            "short months" => updated.month_style = MonthStyle::Short,
+            "numeric months" | "month numbers" => updated.month_style = MonthStyle::Numeric,
+            "decimal comma" => {
+                updated.decimal_separator = ',';
+                updated.thousands_separator = Some('.');
+            }
+            "decimal point" | "decimal dot" => {
+                updated.decimal_separator = '.';
+                updated.thousands_separator = Some(',');
+            }
+            _ => {
+                return format!(
+                    "Error: Unknown locale setting '{}'. Try en-US, en-GB, de-DE, 12 hour, month first or decimal comma.",
+                    arg.trim()
+                )
+            }
+        }
+        updated.name = format!("{} (custom)", updated.name.trim_end_matches(" (custom)"));
+    }
+
+    if let Err(e) = save_settings(updated) {
+        return format!("Failed to save locale: {}", e);
+    }
+    let message = describe(&settings());
+    if let Err(e) = speak_to_speaker("Locale updated.") {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn show_locale(_arg: &str) -> String {
+    let message = describe(&settings());
+    if let Err(e) = speak_to_speaker(&format!("Your locale is {}.", settings().name)) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spells_numbers() {
+        assert_eq!(number_words(0), "zero");
+        assert_eq!(number_words(40), "forty");
+        assert_eq!(number_words(42), "forty-two");
+        assert_eq!(number_words(300), "three hundred");
+        assert_eq!(number_words(215), "two hundred and fifteen");
+        assert_eq!(number_words(7000), "seven thousand");
+        assert_eq!(number_words(2005), "two thousand and five");
+        assert_eq!(number_words(12_345), "twelve thousand three hundred and forty-five");
+    }
+
+    #[test]
+    fn spells_ordinals() {
+        assert_eq!(ordinal_words(1), "first");
+        assert_eq!(ordinal_words(12), "twelfth");
+        assert_eq!(ordinal_words(18), "eighteenth");
+        assert_eq!(ordinal_words(20), "twentieth");
+        assert_eq!(ordinal_words(22), "twenty-second");
+    }
+
+    #[test]
+    fn reads_years_in_pairs() {
+        assert_eq!(year_words(1999), "nineteen ninety-nine");
+        assert_eq!(year_words(2026), "twenty twenty-six");
+        assert_eq!(year_words(2005), "two thousand five");
+        assert_eq!(year_words(2000), "two thousand");
+        assert_eq!(year_words(1900), "nineteen hundred");
+        assert_eq!(year_words(1907), "nineteen oh seven");
+    }
+
+    #[test]
+    fn speaks_times() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert_eq!(speak_time(time(0, 0)), "midnight");
+        assert_eq!(speak_time(time(12, 0)), "twelve noon");
+        assert_eq!(speak_time(time(9, 15)), "nine fifteen in the morning");
+        assert_eq!(speak_time(time(19, 5)), "seven oh five in the evening");
+    }
+}
@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, sync::Mutex};
+
+use crate::{data_path, dispatch, i18n::tr, is_failure, speak_to_speaker, CommandMap};
+
+const MACROS_FILE: &str = "macros.json";
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Macro {
+    pub steps: Vec<String>,
+    #[serde(default = "default_stop_on_failure")]
+    pub stop_on_failure: bool,
+}
+
+fn default_stop_on_failure() -> bool {
+    true
+}
+
+struct Recording {
+    name: String,
+    steps: Vec<String>,
+}
+
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+/// The saved macros, or the built-in ones before anything was saved. A file
+/// that can't be parsed is an error, so it is never saved over.
+fn load_macros() -> Result<BTreeMap<String, Macro>, String> {
+    match fs::read_to_string(data_path(MACROS_FILE)) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| unreadable(&e)),
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(unreadable(&e)),
+        Err(_) => {
+            let mut macros = BTreeMap::new();
+            macros.insert(
+                "morning report".to_string(),
+                Macro {
+                    steps: vec![
+                        "current_date".to_string(),
+                        "get_uptime".to_string(),
+                        "memory_usage".to_string(),
+                        "disk_usage".to_string(),
+                    ],
+                    stop_on_failure: true,
+                },
+            );
+            Ok(macros)
+        }
+    }
+}
+
+fn unreadable(error: &dyn std::fmt::Display) -> String {
+    tr!("macros-unreadable", path = data_path(MACROS_FILE).display(), error = error)
+}
+
+fn save_macros(macros: &BTreeMap<String, Macro>) -> std::io::Result<()> {
+    let text = serde_json::to_string_pretty(macros)?;
+    fs::write(data_path(MACROS_FILE), text)
+}
+
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Turns a spoken step such as "current date" into "current_date" by finding
+/// the longest run of leading words that names a registered command.
+fn resolve_step(commands: &CommandMap, step: &str) -> String {
+    let words: Vec<&str> = step.split_whitespace().collect();
+    for len in (1..=words.len()).rev() {
+        let name = words[..len].join("_").to_lowercase();
+        if commands.contains_key(&name) {
+            let rest = words[len..].join(" ");
+            return if rest.is_empty() { name } else { format!("{} {}", name, rest) };
+        }
+    }
+    step.trim().to_string()
+}
+
+/// Substitutes `$1`, `$2`, ... with positional arguments and `$*` with all of them.
+fn substitute_args(step: &str, args: &str) -> String {
+    let positional: Vec<&str> = args.split_whitespace().collect();
+    let mut result = step.replace("$*", args);
+    for (i, value) in positional.iter().enumerate().rev() {
+        result = result.replace(&format!("${}", i + 1), value);
+    }
+    result
+}
+
+fn run_macro_steps(
+    commands: &CommandMap,
+    macros: &BTreeMap<String, Macro>,
+    name: &str,
+    mac: &Macro,
+    args: &str,
+    depth: usize,
+) -> String {
+    if depth >= MAX_DEPTH {
+        return tr!("macro-too-deep", name = name);
+    }
+
+    let mut responses = Vec::new();
+    for (index, step) in mac.steps.iter().enumerate() {
+        let line = substitute_args(step, args);
+        // "run_macro <name>" is resolved here too, so it counts towards the depth.
+        let (explicit, spoken) = match line.split_once(char::is_whitespace) {
+            Some(("run_macro", rest)) => (true, rest),
+            _ => (false, line.as_str()),
+        };
+        let response = match find_macro(macros, spoken) {
+            Some((inner_name, inner, inner_args)) => {
+                run_macro_steps(commands, macros, &inner_name, &inner, &inner_args, depth + 1)
+            }
+            None if explicit => tr!("macro-missing", name = normalize_name(spoken)),
+            None => dispatch(commands, &line),
+        };
+        let failed = is_failure(&response);
+        responses.push(response);
+        if failed && mac.stop_on_failure {
+            responses.push(tr!("macro-stopped", name = name, step = index + 1, count = mac.steps.len()));
+            break;
+        }
+    }
+    responses.join("\n")
+}
+
+/// Looks for a macro whose name prefixes the given line, preferring the longest name.
+/// The arguments keep the case they were given in.
+fn find_macro(macros: &BTreeMap<String, Macro>, line: &str) -> Option<(String, Macro, String)> {
+    let spoken = normalize_name(line);
+    macros
+        .iter()
+        .filter(|(name, _)| spoken == **name || spoken.starts_with(&format!("{} ", name)))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(name, mac)| {
+            let args = line.split_whitespace().skip(name.split_whitespace().count()).collect::<Vec<_>>().join(" ");
+            (name.clone(), mac.clone(), args)
+        })
+}
+
+pub fn run_macro(commands: &CommandMap, arg: &str) -> String {
+    let macros = match load_macros() {
+        Ok(macros) => macros,
+        Err(e) => return e,
+    };
+    match find_macro(&macros, arg) {
+        Some((name, mac, args)) => {
+            if let Err(e) = speak_to_speaker(&tr!("macro-running", name = name)) {
+                return format!("Error: {}", e);
+            }
+            run_macro_steps(commands, &macros, &name, &mac, &args, 0)
+        }
+        None => tr!("macro-missing", name = normalize_name(arg)),
+    }
+}
+
+/// Runs the line as a macro if it starts with a macro name.
+pub fn run_by_phrase(commands: &CommandMap, line: &str) -> Option<String> {
+    let macros = match load_macros() {
+        Ok(macros) => macros,
+        Err(e) => {
+            eprintln!("Ignoring macros: {}", e);
+            return None;
+        }
+    };
+    find_macro(&macros, line).map(|(name, mac, args)| run_macro_steps(commands, &macros, &name, &mac, &args, 0))
+}
+
+pub fn define_macro(commands: &CommandMap, arg: &str) -> String {
+    let Some((name, body)) = arg.split_once(" as ").or_else(|| arg.split_once('=')) else {
+        return tr!("macro-define-usage");
+    };
+    let name = normalize_name(name);
+    let (body, stop_on_failure) = match body.trim().strip_suffix("and continue on failure") {
+        Some(rest) => (rest, false),
+        None => (body, true),
+    };
+    let steps: Vec<String> = body
+        .split(';')
+        .flat_map(|part| part.split(" then "))
+        .map(|step| resolve_step(commands, step))
+        .filter(|step| !step.is_empty())
+        .collect();
+
+    if name.is_empty() || steps.is_empty() {
+        return tr!("macro-define-empty");
+    }
+
+    let mut macros = match load_macros() {
+        Ok(macros) => macros,
+        Err(e) => return e,
+    };
+    let count = steps.len();
+    macros.insert(name.clone(), Macro { steps, stop_on_failure });
+    if let Err(e) = save_macros(&macros) {
+        return tr!("macro-save-failed", name = name, error = e);
+    }
+
+    let message = tr!("macro-saved", name = name, count = count);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn list_macros(_arg: &str) -> String {
+    let macros = match load_macros() {
+        Ok(macros) => macros,
+        Err(e) => return e,
+    };
+    if macros.is_empty() {
+        return tr!("macros-none");
+    }
+    if let Err(e) = speak_to_speaker(&tr!("macros-count", count = macros.len())) {
+        return format!("Error: {}", e);
+    }
+    macros
+        .iter()
+        .map(|(name, mac)| format!("{}: {}", name, mac.steps.join("; ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn delete_macro(arg: &str) -> String {
+    let name = normalize_name(arg);
+    let mut macros = match load_macros() {
+        Ok(macros) => macros,
+        Err(e) => return e,
+    };
+    if macros.remove(&name).is_none() {
+        return tr!("macro-missing", name = name);
+    }
+    match save_macros(&macros) {
+        Ok(_) => tr!("macro-deleted", name = name),
+        Err(e) => tr!("macro-delete-failed", name = name, error = e),
+    }
+}
+
+pub fn start_recording(arg: &str) -> String {
+    let name = normalize_name(arg);
+    if name.is_empty() {
+        return tr!("recording-name-missing");
+    }
+    let mut recording = RECORDING.lock().unwrap();
+    if let Some(current) = recording.as_ref() {
+        return tr!("recording-already", name = current.name);
+    }
+    *recording = Some(Recording { name: name.clone(), steps: Vec::new() });
+
+    let message = tr!("recording-started", name = name);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn stop_recording(_arg: &str) -> String {
+    if RECORDING.lock().unwrap().is_none() {
+        return tr!("recording-none");
+    }
+    // Loaded first so an unreadable file leaves the recording running instead of losing it.
+    let mut macros = match load_macros() {
+        Ok(macros) => macros,
+        Err(e) => return tr!("recording-kept", error = e),
+    };
+    let Some(recording) = RECORDING.lock().unwrap().take() else {
+        return tr!("recording-none");
+    };
+    if recording.steps.is_empty() {
+        return tr!("recording-discarded", name = recording.name);
+    }
+
+    let count = recording.steps.len();
+    macros.insert(
+        recording.name.clone(),
+        Macro { steps: recording.steps, stop_on_failure: true },
+    );
+    if let Err(e) = save_macros(&macros) {
+        return tr!("macro-save-failed", name = recording.name, error = e);
+    }
+
+    let message = tr!("macro-recorded", name = recording.name, count = count);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// Appends a successfully executed command to the macro being recorded, if any.
+pub fn record(line: &str) {
+    let command = line.split_whitespace().next().unwrap_or_default();
+    if matches!(command, "start_recording" | "stop_recording") {
+        return;
+    }
+    if let Some(recording) = RECORDING.lock().unwrap().as_mut() {
+        recording.steps.push(line.trim().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::set_request_language;
+
+    fn commands() -> CommandMap {
+        let mut commands = CommandMap::new();
+        commands.insert("echo".to_string(), Box::new(|arg: &str| format!("echo {}", arg)));
+        commands.insert("fail".to_string(), Box::new(|_: &str| "Error: It broke.".to_string()));
+        commands.insert("current_date".to_string(), Box::new(|_: &str| "today".to_string()));
+        commands
+    }
+
+    fn macros(entries: &[(&str, &[&str], bool)]) -> BTreeMap<String, Macro> {
+        entries
+            .iter()
+            .map(|(name, steps, stop_on_failure)| {
+                let steps = steps.iter().map(|step| step.to_string()).collect();
+                (name.to_string(), Macro { steps, stop_on_failure: *stop_on_failure })
+            })
+            .collect()
+    }
+
+    fn run(macros: &BTreeMap<String, Macro>, line: &str) -> String {
+        let (name, mac, args) = find_macro(macros, line).unwrap();
+        run_macro_steps(&commands(), macros, &name, &mac, &args, 0)
+    }
+
+    #[test]
+    fn substitutes_arguments() {
+        assert_eq!(substitute_args("echo $2 $1", "a B"), "echo B a");
+        assert_eq!(substitute_args("echo $*", "Hello World"), "echo Hello World");
+        assert_eq!(substitute_args("echo $1 $3", "x"), "echo x $3");
+
+        set_request_language(Some("en"));
+        let macros = macros(&[("greet", &["echo hi $1", "echo $*"], true)]);
+        assert_eq!(run(&macros, "Greet Ada Lovelace"), "echo hi Ada\necho Ada Lovelace");
+    }
+
+    #[test]
+    fn stops_at_the_first_failure_unless_told_to_continue() {
+        set_request_language(Some("en"));
+        let macros = macros(&[
+            ("strict", &["echo one", "fail", "echo two"], true),
+            ("lenient", &["fail", "echo two"], false),
+        ]);
+        assert_eq!(run(&macros, "strict"), "echo one\nError: It broke.\nMacro 'strict' stopped at step 2 of 3.");
+        assert_eq!(run(&macros, "lenient"), "Error: It broke.\necho two");
+    }
+
+    #[test]
+    fn limits_self_referencing_macros() {
+        set_request_language(Some("en"));
+        let nested = |name: &str| {
+            let mut lines: Vec<String> = vec![format!("Error: Macro '{}' nests too deeply.", name)];
+            lines.extend((0..MAX_DEPTH).map(|_| format!("Macro '{}' stopped at step 1 of 1.", name)));
+            lines.join("\n")
+        };
+        let macros = macros(&[
+            ("loop", &["run_macro loop"], true),
+            ("spin", &["spin"], true),
+            ("ping", &["run_macro pong"], true),
+            ("pong", &["ping"], true),
+            ("lost", &["run_macro nothing here"], true),
+        ]);
+        assert_eq!(run(&macros, "loop"), nested("loop"));
+        assert_eq!(run(&macros, "spin"), nested("spin"));
+        assert!(run(&macros, "ping").starts_with("Error: Macro 'p"));
+        assert!(run(&macros, "lost").starts_with("Error: No macro named 'nothing here'."));
+    }
+
+    #[test]
+    fn resolves_spoken_steps_and_records_commands() {
+        assert_eq!(resolve_step(&commands(), "Current Date"), "current_date");
+        assert_eq!(resolve_step(&commands(), " echo Hi there "), "echo Hi there");
+        assert_eq!(resolve_step(&commands(), "what time is it"), "what time is it");
+
+        *RECORDING.lock().unwrap() = Some(Recording { name: "demo".to_string(), steps: Vec::new() });
+        record("current_date");
+        record("start_recording other");
+        record("echo  Hi ");
+        record("stop_recording");
+        let recording = RECORDING.lock().unwrap().take().unwrap();
+        assert_eq!(recording.steps, vec!["current_date", "echo  Hi"]);
+        record("echo lost");
+        assert!(RECORDING.lock().unwrap().is_none());
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::Local;
+use serde::Serialize;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use sysinfo::{CpuExt, NetworkExt, NetworksExt, System, SystemExt};
+
+use crate::{alerts, storage, trends};
+
+/// How often the collector refreshes. CPU usage is averaged over this window.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+// Interfaces change rarely, so their list is only rescanned every few ticks.
+const NETWORK_RESCAN_TICKS: u64 = 12;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CpuSnapshot {
+    pub name: String,
+    pub brand: String,
+    pub usage: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSnapshot {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total: u64,
+    pub available: u64,
+}
+
+/// Traffic counters of one interface. Rates are bytes per second over the
+/// last refresh.
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkSnapshot {
+    pub name: String,
+    pub received: u64,
+    pub transmitted: u64,
+    pub receive_rate: f64,
+    pub transmit_rate: f64,
+}
+
+/// One reading of the host, all sizes in bytes.
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub time: i64,
+    pub cpu_usage: f32,
+    pub cpus: Vec<CpuSnapshot>,
+    pub load_average: [f64; 3],
+    pub memory_total: u64,
+    pub memory_used: u64,
+    pub memory_free: u64,
+    pub memory_available: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+    pub uptime: u64,
+    pub disks: Vec<DiskSnapshot>,
+    pub networks: Vec<NetworkSnapshot>,
+}
+
+static LATEST: Mutex<Option<Snapshot>> = Mutex::new(None);
+
+fn read(sys: &System, elapsed: Duration) -> Snapshot {
+    let load = sys.load_average();
+    let seconds = elapsed.as_secs_f64().max(0.001);
+    let mut networks: Vec<NetworkSnapshot> = sys
+        .networks()
+        .iter()
+        .map(|(name, data)| NetworkSnapshot {
+            name: name.clone(),
+            received: data.total_received(),
+            transmitted: data.total_transmitted(),
+            receive_rate: data.received() as f64 / seconds,
+            transmit_rate: data.transmitted() as f64 / seconds,
+        })
+        .collect();
+    networks.sort_by(|a, b| a.name.cmp(&b.name));
+    Snapshot {
+        time: Local::now().timestamp(),
+        cpu_usage: sys.global_cpu_info().cpu_usage(),
+        cpus: sys
+            .cpus()
+            .iter()
+            .map(|cpu| CpuSnapshot {
+                name: cpu.name().to_string(),
+                brand: cpu.brand().to_string(),
+                usage: cpu.cpu_usage(),
+            })
+            .collect(),
+        load_average: [load.one, load.five, load.fifteen],
+        memory_total: sys.total_memory(),
+        memory_used: sys.used_memory(),
+        memory_free: sys.free_memory(),
+        memory_available: sys.available_memory(),
+        swap_total: sys.total_swap(),
+        swap_used: sys.used_swap(),
+        uptime: sys.uptime(),
+        // The same mounts `list_drives` shows, so alerts and trends agree with it.
+        disks: storage::filesystems(false)
+            .into_iter()
+            .map(|filesystem| DiskSnapshot {
+                name: filesystem.device,
+                mount_point: filesystem.mount_point,
+                file_system: filesystem.file_system,
+                total: filesystem.total,
+                available: filesystem.available,
+            })
+            .collect(),
+        networks,
+    }
+}
+
+/// `elapsed` is the time since the previous refresh, used to turn the
+/// network counters' deltas into rates.
+fn refresh(sys: &mut System, tick: u64, elapsed: Duration) -> Snapshot {
+    sys.refresh_cpu();
+    sys.refresh_memory();
+    if tick.is_multiple_of(NETWORK_RESCAN_TICKS) {
+        sys.refresh_networks_list();
+    }
+    sys.refresh_networks();
+    read(sys, elapsed)
+}
+
+/// Takes a first reading with a short CPU sampling window, since sysinfo
+/// reports usage as the difference between two refreshes.
+fn sample() -> (System, Snapshot) {
+    let mut sys = System::new();
+    sys.refresh_cpu();
+    sys.refresh_networks_list();
+    let window = System::MINIMUM_CPU_UPDATE_INTERVAL.max(Duration::from_millis(250));
+    std::thread::sleep(window);
+    let snapshot = refresh(&mut sys, 0, window);
+    (sys, snapshot)
+}
+
+/// The latest reading. Before the collector has produced one, a reading is
+/// taken on the spot.
+pub fn snapshot() -> Snapshot {
+    if let Some(snapshot) = LATEST.lock().unwrap().clone() {
+        return snapshot;
+    }
+    let (_, snapshot) = sample();
+    snapshot
+}
+
+/// Spawns the collector that keeps one `System` and refreshes it on an interval.
+pub fn start() {
+    tokio::spawn(async {
+        let Ok((sys, first)) = tokio::task::spawn_blocking(sample).await else {
+            return;
+        };
+        trends::record(&first);
+        *LATEST.lock().unwrap() = Some(first);
+
+        let sys = Arc::new(Mutex::new((sys, Instant::now())));
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        interval.tick().await;
+        for tick in 1.. {
+            interval.tick().await;
+            let sys = Arc::clone(&sys);
+            let reading = tokio::task::spawn_blocking(move || {
+                let (sys, refreshed) = &mut *sys.lock().unwrap();
+                let snapshot = refresh(sys, tick, refreshed.elapsed());
+                *refreshed = Instant::now();
+                trends::record(&snapshot);
+                snapshot
+            });
+            if let Ok(snapshot) = reading.await {
+                *LATEST.lock().unwrap() = Some(snapshot.clone());
+                // Speaking a warning can take a while, so rules are checked off the loop.
+                tokio::task::spawn_blocking(move || alerts::evaluate(&snapshot));
+            }
+        }
+    });
+}
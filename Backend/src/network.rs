@@ -0,0 +1,365 @@
+use serde::Serialize;
+use serde_json::json;
+use std::{collections::HashMap, fs, net::IpAddr};
+#[cfg(unix)]
+use std::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr},
+    ptr,
+};
+
+use crate::{attach_data, format_bytes, metrics, speak_to_speaker};
+
+#[derive(Debug, Clone, Serialize)]
+struct Address {
+    address: IpAddr,
+    prefix: u32,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+struct Counters {
+    rx_bytes: u64,
+    rx_packets: u64,
+    rx_errors: u64,
+    rx_dropped: u64,
+    tx_bytes: u64,
+    tx_packets: u64,
+    tx_errors: u64,
+    tx_dropped: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Interface {
+    name: String,
+    state: String,
+    mac: Option<String>,
+    mtu: Option<u32>,
+    speed_mbps: Option<u32>,
+    addresses: Vec<Address>,
+    counters: Counters,
+    receive_rate: Option<f64>,
+    transmit_rate: Option<f64>,
+}
+
+impl Interface {
+    fn is_loopback(&self) -> bool {
+        self.name == "lo" || self.addresses.iter().any(|a| a.address.is_loopback())
+    }
+
+    fn is_up(&self) -> bool {
+        // Loopback and some virtual devices report "unknown" while passing traffic.
+        matches!(self.state.as_str(), "up" | "unknown")
+    }
+}
+
+fn sys_value(name: &str, file: &str) -> Option<String> {
+    fs::read_to_string(format!("/sys/class/net/{}/{}", name, file))
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Parses `/proc/net/dev`, keyed by interface name.
+fn counters() -> HashMap<String, Counters> {
+    let Ok(text) = fs::read_to_string("/proc/net/dev") else {
+        return HashMap::new();
+    };
+    text.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, values) = line.split_once(':')?;
+            let values: Vec<u64> = values.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+            if values.len() < 12 {
+                return None;
+            }
+            Some((
+                name.trim().to_string(),
+                Counters {
+                    rx_bytes: values[0],
+                    rx_packets: values[1],
+                    rx_errors: values[2],
+                    rx_dropped: values[3],
+                    tx_bytes: values[8],
+                    tx_packets: values[9],
+                    tx_errors: values[10],
+                    tx_dropped: values[11],
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn prefix_length(netmask: *const libc::sockaddr) -> u32 {
+    if netmask.is_null() {
+        return 0;
+    }
+    unsafe {
+        match (*netmask).sa_family as i32 {
+            libc::AF_INET => (*(netmask as *const libc::sockaddr_in)).sin_addr.s_addr.count_ones(),
+            libc::AF_INET6 => {
+                (*(netmask as *const libc::sockaddr_in6)).sin6_addr.s6_addr.iter().map(|b| b.count_ones()).sum()
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// The IP address in a `sockaddr`, if it is an IPv4 or IPv6 one.
+///
+/// # Safety
+/// `addr` must be null or point to a valid socket address of its family.
+#[cfg(unix)]
+pub(crate) unsafe fn sockaddr_ip(addr: *const libc::sockaddr) -> Option<IpAddr> {
+    if addr.is_null() {
+        return None;
+    }
+    match (*addr).sa_family as i32 {
+        libc::AF_INET => {
+            let addr = &*(addr as *const libc::sockaddr_in);
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr))))
+        }
+        libc::AF_INET6 => {
+            let addr = &*(addr as *const libc::sockaddr_in6);
+            Some(IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr)))
+        }
+        _ => None,
+    }
+}
+
+/// IPv4 and IPv6 addresses per interface. `/sys` doesn't expose addresses,
+/// so they come from `getifaddrs`.
+#[cfg(unix)]
+fn addresses() -> HashMap<String, Vec<Address>> {
+    let mut found: HashMap<String, Vec<Address>> = HashMap::new();
+    let mut head: *mut libc::ifaddrs = ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut head) } != 0 {
+        return found;
+    }
+    let mut cursor = head;
+    while !cursor.is_null() {
+        let entry = unsafe { &*cursor };
+        cursor = entry.ifa_next;
+        let Some(address) = (unsafe { sockaddr_ip(entry.ifa_addr) }) else {
+            continue;
+        };
+        let name = unsafe { CStr::from_ptr(entry.ifa_name) }.to_string_lossy().into_owned();
+        found.entry(name).or_default().push(Address { address, prefix: prefix_length(entry.ifa_netmask) });
+    }
+    unsafe { libc::freeifaddrs(head) };
+    found
+}
+
+#[cfg(not(unix))]
+fn addresses() -> HashMap<String, Vec<Address>> {
+    HashMap::new()
+}
+
+/// Interfaces from `/sys/class/net`. Where that doesn't exist, as off Linux,
+/// the collector's interface list is used and only addresses and traffic are known.
+fn interfaces() -> Vec<Interface> {
+    let networks = metrics::snapshot().networks;
+    let names: Vec<String> = match fs::read_dir("/sys/class/net") {
+        Ok(entries) => entries.flatten().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect(),
+        Err(_) => networks.iter().map(|network| network.name.clone()).collect(),
+    };
+    let mut counters = counters();
+    let mut addresses = addresses();
+    let totals: HashMap<String, Counters> = networks
+        .iter()
+        .map(|network| {
+            let counters = Counters { rx_bytes: network.received, tx_bytes: network.transmitted, ..Counters::default() };
+            (network.name.clone(), counters)
+        })
+        .collect();
+    let rates: HashMap<String, (f64, f64)> = networks
+        .into_iter()
+        .map(|network| (network.name, (network.receive_rate, network.transmit_rate)))
+        .collect();
+
+    let mut interfaces: Vec<Interface> = names
+        .into_iter()
+        .map(|name| {
+            let rate = rates.get(&name);
+            Interface {
+                state: sys_value(&name, "operstate").unwrap_or_else(|| "unknown".to_string()),
+                mac: sys_value(&name, "address").filter(|mac| mac != "00:00:00:00:00:00"),
+                mtu: sys_value(&name, "mtu").and_then(|mtu| mtu.parse().ok()),
+                // Virtual devices report -1 or refuse the read entirely.
+                speed_mbps: sys_value(&name, "speed").and_then(|speed| speed.parse().ok()),
+                addresses: addresses.remove(&name).unwrap_or_default(),
+                counters: counters.remove(&name).or_else(|| totals.get(&name).cloned()).unwrap_or_default(),
+                receive_rate: rate.map(|r| r.0),
+                transmit_rate: rate.map(|r| r.1),
+                name,
+            }
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
+
+/// The interface carrying the default IPv4 route, from `/proc/net/route`.
+fn default_interface() -> Option<String> {
+    let text = fs::read_to_string("/proc/net/route").ok()?;
+    text.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        (fields.get(1) == Some(&"00000000")).then(|| fields[0].to_string())
+    })
+}
+
+fn rate(bytes_per_second: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_second as u64))
+}
+
+fn describe(interface: &Interface) -> String {
+    let mut lines = vec![format!(
+        "{}: {}{}",
+        interface.name,
+        interface.state,
+        interface.mtu.map(|mtu| format!(", mtu {}", mtu)).unwrap_or_default()
+    )];
+    if let Some(mac) = &interface.mac {
+        lines.push(format!("  mac {}", mac));
+    }
+    if let Some(speed) = interface.speed_mbps {
+        lines.push(format!("  link speed {} Mb/s", speed));
+    }
+    for address in &interface.addresses {
+        lines.push(format!("  inet {}/{}", address.address, address.prefix));
+    }
+    let c = &interface.counters;
+    lines.push(format!(
+        "  RX {} ({} packets, {} errors, {} dropped)",
+        format_bytes(c.rx_bytes),
+        c.rx_packets,
+        c.rx_errors,
+        c.rx_dropped
+    ));
+    lines.push(format!(
+        "  TX {} ({} packets, {} errors, {} dropped)",
+        format_bytes(c.tx_bytes),
+        c.tx_packets,
+        c.tx_errors,
+        c.tx_dropped
+    ));
+    if let (Some(rx), Some(tx)) = (interface.receive_rate, interface.transmit_rate) {
+        lines.push(format!("  now receiving {}, sending {}", rate(rx), rate(tx)));
+    }
+    lines.join("\n")
+}
+
+fn matching(arg: &str) -> Vec<Interface> {
+    let wanted = arg.trim().to_lowercase();
+    interfaces()
+        .into_iter()
+        .filter(|interface| wanted.is_empty() || interface.name.to_lowercase() == wanted)
+        .collect()
+}
+
+/// `list_networks [interface]`: every interface with its addresses, link state and counters.
+pub fn list_networks(arg: &str) -> String {
+    let interfaces = matching(arg);
+    if interfaces.is_empty() {
+        return if arg.trim().is_empty() {
+            "Error: Could not read any interfaces from /sys/class/net.".to_string()
+        } else {
+            format!("Error: There is no network interface called {}.", arg.trim())
+        };
+    }
+
+    let up: Vec<&str> = interfaces
+        .iter()
+        .filter(|i| i.is_up() && !i.is_loopback())
+        .map(|i| i.name.as_str())
+        .collect();
+    let summary = match up.as_slice() {
+        [] => format!("{} network interfaces, none of them connected.", interfaces.len()),
+        [name] => format!("{} network interfaces; {} is up.", interfaces.len(), name),
+        names => format!("{} network interfaces; {} are up.", interfaces.len(), names.join(", ")),
+    };
+    attach_data(json!({ "interfaces": interfaces }));
+    if let Err(e) = speak_to_speaker(&summary) {
+        return format!("Error: {}", e);
+    }
+    interfaces.iter().map(describe).collect::<Vec<_>>().join("\n\n")
+}
+
+/// `network_usage [interface]`: current receive and send rates from the metrics collector.
+pub fn network_usage(arg: &str) -> String {
+    let interfaces: Vec<Interface> = matching(arg)
+        .into_iter()
+        .filter(|i| !arg.trim().is_empty() || (i.is_up() && !i.is_loopback()))
+        .filter(|i| i.receive_rate.is_some())
+        .collect();
+    if interfaces.is_empty() {
+        return "Error: No network traffic has been measured yet.".to_string();
+    }
+
+    let busiest = interfaces
+        .iter()
+        .max_by(|a, b| {
+            let total = |i: &Interface| i.receive_rate.unwrap_or(0.0) + i.transmit_rate.unwrap_or(0.0);
+            total(a).total_cmp(&total(b))
+        })
+        .unwrap();
+    let message = format!(
+        "{} is receiving {} and sending {}.",
+        busiest.name,
+        rate(busiest.receive_rate.unwrap_or(0.0)),
+        rate(busiest.transmit_rate.unwrap_or(0.0))
+    );
+    let lines: Vec<String> = interfaces
+        .iter()
+        .map(|i| {
+            format!(
+                "{}: down {}, up {}",
+                i.name,
+                rate(i.receive_rate.unwrap_or(0.0)),
+                rate(i.transmit_rate.unwrap_or(0.0))
+            )
+        })
+        .collect();
+    attach_data(json!({ "interfaces": interfaces }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+/// `my_ip_address`: the local address of the interface with the default route.
+pub fn my_ip_address(_arg: &str) -> String {
+    let interfaces: Vec<Interface> = interfaces()
+        .into_iter()
+        .filter(|i| i.is_up() && !i.is_loopback() && !i.addresses.is_empty())
+        .collect();
+    let primary = default_interface()
+        .and_then(|name| interfaces.iter().find(|i| i.name == name))
+        .or_else(|| interfaces.first());
+    let Some(primary) = primary else {
+        let message = "You don't have an IP address; no network interface is connected.".to_string();
+        if let Err(e) = speak_to_speaker(&message) {
+            return format!("Error: {}", e);
+        }
+        return message;
+    };
+
+    // Prefer IPv4, then a global IPv6 address over a link-local one.
+    let mut candidates: Vec<&Address> = primary.addresses.iter().collect();
+    candidates.sort_by_key(|a| match a.address {
+        IpAddr::V4(_) => 0,
+        IpAddr::V6(v6) if v6.segments()[0] & 0xffc0 == 0xfe80 => 2,
+        IpAddr::V6(_) => 1,
+    });
+    let address = candidates[0].address;
+    let message = format!("Your IP address is {} on {}.", address, primary.name);
+    let others: Vec<String> = interfaces
+        .iter()
+        .flat_map(|i| i.addresses.iter().map(move |a| format!("{}: {}/{}", i.name, a.address, a.prefix)))
+        .collect();
+    attach_data(json!({ "interface": primary.name, "address": address, "all": interfaces }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    format!("{}\n{}", message, others.join("\n"))
+}
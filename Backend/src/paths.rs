@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+// Words that may come before a spoken place ("in my downloads") or after it
+// ("home folder") and belong to the place rather than the rest of the request.
+const LEADING: [&str; 9] = ["in", "under", "inside", "within", "of", "from", "my", "the", "this"];
+const TRAILING: [&str; 3] = ["folder", "directory", "dir"];
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn named_place(word: &str) -> Option<PathBuf> {
+    match word {
+        "home" => dirs::home_dir(),
+        "downloads" | "download" => dirs::download_dir(),
+        "documents" | "docs" => dirs::document_dir(),
+        "desktop" => dirs::desktop_dir(),
+        "pictures" | "photos" => dirs::picture_dir(),
+        "music" => dirs::audio_dir(),
+        "videos" => dirs::video_dir(),
+        "root" => Some(PathBuf::from("/")),
+        "here" | "project" => std::env::current_dir().ok(),
+        _ => None,
+    }
+}
+
+/// Finds a spoken or literal place in a request, e.g. "in my home folder",
+/// "in downloads", "in this project" or "/var/log". Returns the directory and
+/// the request with the place removed. Named places only count at the start
+/// of the request or after a word like "in" or "my", so "containing home"
+/// stays a search term.
+pub fn place(arg: &str) -> (Option<PathBuf>, String) {
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    for (i, word) in words.iter().enumerate() {
+        let lowered = word.to_lowercase();
+        let literal = word.starts_with('/') || word.starts_with('~') || word.starts_with("./") || word.starts_with("../") || *word == ".";
+        let after_lead = i == 0 || LEADING.contains(&words[i - 1].to_lowercase().as_str());
+        let path = if literal {
+            Some(expand_home(word))
+        } else if after_lead && i > 0 && words[i - 1].eq_ignore_ascii_case("this") && TRAILING.contains(&lowered.as_str()) {
+            std::env::current_dir().ok()
+        } else if after_lead {
+            named_place(&lowered)
+        } else {
+            None
+        };
+        let Some(path) = path else {
+            continue;
+        };
+
+        let mut start = i;
+        while start > 0 && LEADING.contains(&words[start - 1].to_lowercase().as_str()) {
+            start -= 1;
+        }
+        let mut end = i + 1;
+        while end < words.len() && TRAILING.contains(&words[end].to_lowercase().as_str()) {
+            end += 1;
+        }
+        let rest = words[..start].iter().chain(&words[end..]).copied().collect::<Vec<_>>().join(" ");
+        return (Some(path), rest);
+    }
+    (None, arg.trim().to_string())
+}
+
+/// The directory named in a request, or the session's working directory.
+pub fn place_or_cwd(arg: &str) -> (PathBuf, String) {
+    let (path, rest) = place(arg);
+    (path.or_else(|| std::env::current_dir().ok()).unwrap_or_else(|| PathBuf::from(".")), rest)
+}
+
+/// Case-insensitive shell-style match supporting `*` and `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// A path as it should be spoken: "your home folder", or relative to home.
+pub fn speakable(path: &Path) -> String {
+    match dirs::home_dir() {
+        Some(home) if path == home => "your home folder".to_string(),
+        Some(home) => match path.strip_prefix(&home) {
+            Ok(rest) => format!("~/{}", rest.display()),
+            Err(_) => path.display().to_string(),
+        },
+        None => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("*.rs", "Main.RS"));
+        assert!(glob_match("node_modules", "node_modules"));
+        assert!(glob_match("data-??.csv", "data-01.csv"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("*.rs", "main.rs.bak"));
+        assert!(!glob_match("data-?.csv", "data-01.csv"));
+    }
+
+    #[test]
+    fn finds_spoken_places() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(place("in my home folder depth 3"), (Some(home.clone()), "depth 3".to_string()));
+        assert_eq!(place("TODO in ~/notes"), (Some(home.join("notes")), "TODO".to_string()));
+        assert_eq!(place("/var/log top 3"), (Some(PathBuf::from("/var/log")), "top 3".to_string()));
+        assert_eq!(place("in this project"), (std::env::current_dir().ok(), String::new()));
+        assert_eq!(place("in this folder"), (std::env::current_dir().ok(), String::new()));
+        assert_eq!(place("containing home"), (None, "containing home".to_string()));
+    }
+
+    #[test]
+    fn speaks_paths_relative_to_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(speakable(&home), "your home folder");
+        assert_eq!(speakable(&home.join("notes")), "~/notes");
+        assert_eq!(speakable(Path::new("/etc")), "/etc");
+    }
+}
@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{attach_data, data_path, CommandMap};
+
+const PLUGINS_DIR: &str = "plugins";
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(2);
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_OUTPUT_BYTES: u64 = 1024 * 1024;
+#[cfg(unix)]
+const CPU_LIMIT_SECONDS: u64 = 10;
+#[cfg(unix)]
+const MEMORY_LIMIT_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DangerLevel {
+    #[default]
+    Safe,
+    Caution,
+    Dangerous,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PluginDescription {
+    name: String,
+    #[serde(default)]
+    arguments: Vec<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    danger_level: DangerLevel,
+}
+
+#[derive(Debug, Clone)]
+struct Plugin {
+    description: PluginDescription,
+    path: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    response: String,
+    /// Structured data passed through to the client alongside the response.
+    #[serde(default)]
+    data: Option<Value>,
+}
+
+static PLUGINS: Mutex<Vec<Plugin>> = Mutex::new(Vec::new());
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("exe")
+}
+
+#[cfg(unix)]
+fn apply_limits(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    unsafe {
+        command.pre_exec(|| {
+            // Its own process group, so a timeout can take down anything it started.
+            libc::setpgid(0, 0);
+            let cpu = libc::rlimit { rlim_cur: CPU_LIMIT_SECONDS, rlim_max: CPU_LIMIT_SECONDS };
+            let memory = libc::rlimit { rlim_cur: MEMORY_LIMIT_BYTES, rlim_max: MEMORY_LIMIT_BYTES };
+            let files = libc::rlimit { rlim_cur: MAX_OUTPUT_BYTES * 64, rlim_max: MAX_OUTPUT_BYTES * 64 };
+            libc::setrlimit(libc::RLIMIT_CPU, &cpu);
+            libc::setrlimit(libc::RLIMIT_AS, &memory);
+            libc::setrlimit(libc::RLIMIT_FSIZE, &files);
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn apply_limits(_command: &mut Command) {}
+
+/// Kills the plugin and every process left in its group. Grandchildren that
+/// still hold the output pipes would otherwise keep the readers waiting.
+#[cfg(unix)]
+fn kill_group(child: &mut Child) {
+    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+    let _ = child.kill();
+}
+
+#[cfg(not(unix))]
+fn kill_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// Keeps the first `MAX_OUTPUT_BYTES` and reads the rest into a sink, so a
+/// chatty plugin never blocks on a full pipe.
+fn read_capped(mut reader: impl Read) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    let _ = reader.by_ref().take(MAX_OUTPUT_BYTES).read_to_end(&mut buffer);
+    let _ = io::copy(&mut reader, &mut io::sink());
+    buffer
+}
+
+/// Runs a plugin executable, feeding `input` on stdin, and returns its stdout.
+/// The process group is killed if it outlives `timeout`; output beyond the
+/// cap is read and discarded.
+fn run_with_timeout(path: &Path, args: &[&str], input: &[u8], timeout: Duration) -> Result<String, String> {
+    let mut command = Command::new(path);
+    command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    apply_limits(&mut command);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start plugin {}: {}", path.display(), e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_vec();
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+    let stdout = child.stdout.take().map(|stdout| thread::spawn(move || read_capped(stdout)));
+    let stderr = child.stderr.take().map(|stderr| thread::spawn(move || read_capped(stderr)));
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                // Background processes the plugin left behind go with it.
+                kill_group(&mut child);
+                break status;
+            }
+            Ok(None) if started.elapsed() >= timeout => {
+                kill_group(&mut child);
+                let _ = child.wait();
+                return Err(format!("Plugin {} timed out after {}s", path.display(), timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("Failed to wait for plugin {}: {}", path.display(), e)),
+        }
+    };
+
+    let stdout = stdout.and_then(|handle| handle.join().ok()).unwrap_or_default();
+    let stderr = stderr.and_then(|handle| handle.join().ok()).unwrap_or_default();
+    if status.success() {
+        Ok(String::from_utf8_lossy(&stdout).to_string())
+    } else {
+        Err(format!(
+            "Plugin {} failed ({}): {}",
+            path.display(),
+            status,
+            String::from_utf8_lossy(&stderr).trim()
+        ))
+    }
+}
+
+fn describe(path: &Path) -> Result<PluginDescription, String> {
+    let output = run_with_timeout(path, &["--describe"], b"", DESCRIBE_TIMEOUT)?;
+    serde_json::from_str(&output).map_err(|e| format!("Invalid --describe output from {}: {}", path.display(), e))
+}
+
+/// Maps the spoken words onto the plugin's declared argument names; the last
+/// declared argument receives whatever words remain.
+fn build_input(plugin: &PluginDescription, arg: &str) -> Value {
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let mut named = Map::new();
+    for (index, name) in plugin.arguments.iter().enumerate() {
+        let value = if index + 1 == plugin.arguments.len() {
+            words.get(index..).map(|rest| rest.join(" ")).unwrap_or_default()
+        } else {
+            words.get(index).map(|word| word.to_string()).unwrap_or_default()
+        };
+        named.insert(name.clone(), Value::String(value));
+    }
+    json!({
+        "command": plugin.name,
+        "argument": arg,
+        "arguments": named,
+    })
+}
+
+fn invoke(plugin: &Plugin, arg: &str) -> String {
+    let arg = if plugin.description.danger_level == DangerLevel::Dangerous {
+        match arg.trim().strip_suffix("confirm") {
+            Some(rest) => rest.trim().to_string(),
+            None => {
+                return format!(
+                    "Error: '{}' is marked dangerous. Repeat the command ending with 'confirm'.",
+                    plugin.description.name
+                )
+            }
+        }
+    } else {
+        arg.to_string()
+    };
+
+    let input = build_input(&plugin.description, &arg).to_string();
+    match run_with_timeout(&plugin.path, &[], input.as_bytes(), RUN_TIMEOUT) {
+        Ok(output) => match serde_json::from_str::<PluginResponse>(&output) {
+            Ok(parsed) => {
+                if let Some(data) = parsed.data {
+                    attach_data(data);
+                }
+                parsed.response
+            }
+            Err(_) => output.trim().to_string(),
+        },
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+/// Discovers executables in the plugins directory via the `--describe`
+/// handshake and registers each one as a command.
+pub fn load_plugins(commands: &mut CommandMap) {
+    let dir = data_path(PLUGINS_DIR);
+    let _ = fs::create_dir_all(&dir);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+
+    let mut loaded = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !is_executable(&path) {
+            continue;
+        }
+        let description = match describe(&path) {
+            Ok(description) => description,
+            Err(e) => {
+                eprintln!("Skipping plugin: {}", e);
+                continue;
+            }
+        };
+        let name = description.name.trim().replace(' ', "_").to_lowercase();
+        if name.is_empty() || commands.contains_key(&name) {
+            eprintln!("Skipping plugin {}: name '{}' is empty or taken", path.display(), name);
+            continue;
+        }
+
+        let plugin = Arc::new(Plugin { description, path });
+        let handle = Arc::clone(&plugin);
+        commands.insert(name, Box::new(move |arg: &str| invoke(&handle, arg)));
+        loaded.push(plugin.as_ref().clone());
+    }
+
+    println!("🔌 Loaded {} plugins from {}", loaded.len(), dir.display());
+    *PLUGINS.lock().unwrap() = loaded;
+}
+
+pub fn list_plugins(_arg: &str) -> String {
+    let plugins = PLUGINS.lock().unwrap();
+    if plugins.is_empty() {
+        return format!("No plugins found in {}.", data_path(PLUGINS_DIR).display());
+    }
+    plugins
+        .iter()
+        .map(|plugin| {
+            let description = &plugin.description;
+            format!(
+                "{} ({:?}): {} [arguments: {}]",
+                description.name,
+                description.danger_level,
+                description.description,
+                description.arguments.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,310 @@
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use crate::{attach_data, speak_to_speaker};
+
+const TABLES: [(&str, &str); 4] = [
+    ("tcp", "/proc/net/tcp"),
+    ("tcp6", "/proc/net/tcp6"),
+    ("udp", "/proc/net/udp"),
+    ("udp6", "/proc/net/udp6"),
+];
+const TCP_LISTEN: u8 = 0x0A;
+// Unconnected UDP sockets report TCP_CLOSE; those are the ones bound to receive.
+const UDP_UNCONNECTED: u8 = 0x07;
+
+#[derive(Debug, Clone, Serialize)]
+struct Socket {
+    protocol: &'static str,
+    local_address: IpAddr,
+    local_port: u16,
+    remote_address: IpAddr,
+    remote_port: u16,
+    state: &'static str,
+    #[serde(skip)]
+    listening: bool,
+    #[serde(skip)]
+    inode: u64,
+    pid: Option<u32>,
+    process: Option<String>,
+}
+
+impl Socket {
+    fn owner(&self) -> String {
+        match (&self.process, self.pid) {
+            (Some(name), Some(pid)) => format!("{} ({})", name, pid),
+            _ => "unknown owner".to_string(),
+        }
+    }
+
+    fn line(&self) -> String {
+        let local = format!("{}:{}", bracketed(self.local_address), self.local_port);
+        if self.listening {
+            format!("{:<5} {:<40} {}", self.protocol, local, self.owner())
+        } else {
+            let remote = format!("{}:{}", bracketed(self.remote_address), self.remote_port);
+            format!("{:<5} {:<40} {:<40} {:<12} {}", self.protocol, local, remote, self.state, self.owner())
+        }
+    }
+}
+
+fn bracketed(address: IpAddr) -> String {
+    match address {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => format!("[{}]", v6),
+    }
+}
+
+fn tcp_state(state: u8) -> &'static str {
+    match state {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Decodes the kernel's hex "address:port". Addresses are stored as 32-bit
+/// words in host byte order.
+fn parse_endpoint(text: &str) -> Option<(IpAddr, u16)> {
+    let (address, port) = text.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let words: Vec<u32> = (0..address.len() / 8)
+        .map(|i| u32::from_str_radix(&address[i * 8..i * 8 + 8], 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let address = match words.as_slice() {
+        [word] => IpAddr::V4(Ipv4Addr::from(word.to_ne_bytes())),
+        [a, b, c, d] => {
+            let mut bytes = [0u8; 16];
+            for (chunk, word) in bytes.chunks_mut(4).zip([a, b, c, d]) {
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+    Some((address, port))
+}
+
+fn read_table(protocol: &'static str, path: &str) -> Vec<Socket> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let udp = protocol.starts_with("udp");
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (local_address, local_port) = parse_endpoint(fields.get(1)?)?;
+            let (remote_address, remote_port) = parse_endpoint(fields.get(2)?)?;
+            let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+            let listening = if udp { state == UDP_UNCONNECTED } else { state == TCP_LISTEN };
+            Some(Socket {
+                protocol,
+                local_address,
+                local_port,
+                remote_address,
+                remote_port,
+                state: if udp && listening { "BOUND" } else { tcp_state(state) },
+                listening,
+                inode: fields.get(9)?.parse().ok()?,
+                pid: None,
+                process: None,
+            })
+        })
+        .collect()
+}
+
+/// Maps socket inodes to the owning process by scanning `/proc/<pid>/fd`.
+/// Processes of other users are skipped unless the backend runs as root.
+fn socket_owners() -> HashMap<u64, (u32, String)> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return owners;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let name = fs::read_to_string(entry.path().join("comm"))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_default();
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target.strip_prefix("socket:[").and_then(|rest| rest.strip_suffix(']')) {
+                if let Ok(inode) = inode.parse() {
+                    owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+                }
+            }
+        }
+    }
+    owners
+}
+
+fn sockets() -> Vec<Socket> {
+    let owners = socket_owners();
+    let mut sockets: Vec<Socket> = TABLES
+        .iter()
+        .flat_map(|(protocol, path)| read_table(protocol, path))
+        .map(|mut socket| {
+            if let Some((pid, name)) = owners.get(&socket.inode) {
+                socket.pid = Some(*pid);
+                socket.process = Some(name.clone());
+            }
+            socket
+        })
+        .collect();
+    sockets.sort_by_key(|socket| (socket.local_port, socket.protocol));
+    sockets
+}
+
+fn listening() -> Vec<Socket> {
+    sockets().into_iter().filter(|socket| socket.listening).collect()
+}
+
+/// `list_ports`: every listening TCP socket and bound UDP socket with its owner.
+pub fn list_ports(_arg: &str) -> String {
+    let sockets = listening();
+    if sockets.is_empty() {
+        return "Error: Could not read any sockets from /proc/net.".to_string();
+    }
+
+    let tcp = sockets.iter().filter(|s| s.protocol.starts_with("tcp")).count();
+    let summary = format!("{} TCP ports are listening and {} UDP ports are open.", tcp, sockets.len() - tcp);
+    attach_data(json!({ "sockets": sockets }));
+    if let Err(e) = speak_to_speaker(&summary) {
+        return format!("Error: {}", e);
+    }
+    sockets.iter().map(Socket::line).collect::<Vec<_>>().join("\n")
+}
+
+/// `port_owner 8080`: what is listening on a port.
+pub fn port_owner(arg: &str) -> String {
+    let Some(port) = arg.split_whitespace().find_map(|w| w.trim_end_matches('?').parse::<u16>().ok()) else {
+        return "Error: Say which port, e.g. 'port_owner 8080'.".to_string();
+    };
+    let sockets: Vec<Socket> = listening().into_iter().filter(|s| s.local_port == port).collect();
+
+    let message = match sockets.first() {
+        None => format!("Nothing is listening on port {}.", port),
+        Some(socket) => {
+            let mut owners: Vec<String> = sockets.iter().map(Socket::owner).collect();
+            owners.dedup();
+            let protocols: Vec<&str> = sockets.iter().map(|s| s.protocol).collect();
+            match socket.process {
+                Some(_) => format!("Port {} is used by {} over {}.", port, owners.join(" and "), protocols.join(", ")),
+                None => format!(
+                    "Something is listening on port {} over {}, but I can't see which process; it may belong to another user.",
+                    port,
+                    protocols.join(", ")
+                ),
+            }
+        }
+    };
+    attach_data(json!({ "port": port, "sockets": sockets }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `process_ports firefox` or `process_ports 1234`: every socket a process has open.
+pub fn process_ports(arg: &str) -> String {
+    let lowered = arg.to_lowercase().replace('?', "");
+    let target = lowered
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "using" | "use" | "uses" | "has" | "open" | "is" | "does" | "process"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if target.is_empty() {
+        return "Error: Say which process, e.g. 'process_ports firefox'.".to_string();
+    }
+    let pid = target.parse::<u32>().ok();
+    let sockets: Vec<Socket> = sockets()
+        .into_iter()
+        .filter(|socket| match pid {
+            Some(pid) => socket.pid == Some(pid),
+            None => socket.process.as_deref().is_some_and(|name| name.to_lowercase().contains(&target)),
+        })
+        .collect();
+
+    if sockets.is_empty() {
+        let message = format!("{} has no open network sockets that I can see.", target);
+        if let Err(e) = speak_to_speaker(&message) {
+            return format!("Error: {}", e);
+        }
+        return message;
+    }
+
+    let mut listening: Vec<u16> = sockets.iter().filter(|s| s.listening).map(|s| s.local_port).collect();
+    listening.dedup();
+    let connections = sockets.iter().filter(|s| !s.listening).count();
+    let summary = match listening.as_slice() {
+        [] => format!("{} has {} open connections and is not listening on any port.", target, connections),
+        ports => format!(
+            "{} is listening on port{} {} and has {} other connections.",
+            target,
+            if ports.len() == 1 { "" } else { "s" },
+            ports.iter().map(u16::to_string).collect::<Vec<_>>().join(", "),
+            connections
+        ),
+    };
+    attach_data(json!({ "sockets": sockets }));
+    if let Err(e) = speak_to_speaker(&summary) {
+        return format!("Error: {}", e);
+    }
+    sockets.iter().map(Socket::line).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes an address the way the kernel prints it: 32-bit words in host order.
+    fn kernel_hex(bytes: &[u8]) -> String {
+        bytes.chunks(4).map(|word| format!("{:08X}", u32::from_ne_bytes(word.try_into().unwrap()))).collect()
+    }
+
+    #[test]
+    fn decodes_kernel_endpoints() {
+        let v4 = format!("{}:1F90", kernel_hex(&[127, 0, 0, 1]));
+        assert_eq!(parse_endpoint(&v4), Some((IpAddr::V4(Ipv4Addr::LOCALHOST), 8080)));
+
+        let address: Ipv6Addr = "fd00::2:1".parse().unwrap();
+        let v6 = format!("{}:0050", kernel_hex(&address.octets()));
+        assert_eq!(parse_endpoint(&v6), Some((IpAddr::V6(address), 80)));
+
+        assert_eq!(parse_endpoint("0100007F"), None);
+        assert_eq!(parse_endpoint("0100007F:XYZ"), None);
+        assert_eq!(parse_endpoint("0100:0050"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_a_listening_socket() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let sockets = read_table("tcp", "/proc/net/tcp");
+        assert!(sockets.iter().any(|s| s.local_port == port && s.listening && s.state == "LISTEN"));
+    }
+}
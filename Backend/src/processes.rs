@@ -0,0 +1,456 @@
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use sysinfo::{Pid, PidExt, Process, ProcessExt, Signal, System, SystemExt, UserExt};
+
+use crate::{attach_data, format_bytes, i18n::tr, locale, scheduler::parse_number, speak_to_speaker};
+
+const DEFAULT_TOP: usize = 5;
+// Process CPU usage is measured between two refreshes; a refresh older than
+// this is too stale to serve as the first one.
+const MAX_SAMPLE_AGE: Duration = Duration::from_secs(30);
+/// How long the code from a kill preview stays valid.
+const KILL_CONFIRM_WINDOW: Duration = Duration::from_secs(60);
+
+// Kept between requests so CPU usage covers the time since the last query.
+static SYSTEM: Mutex<Option<(System, Instant)>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+struct ProcessInfo {
+    pid: u32,
+    name: String,
+    cpu: f32,
+    memory: u64,
+    status: String,
+    user: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProcessDetails {
+    #[serde(flatten)]
+    info: ProcessInfo,
+    parent: Option<u32>,
+    command: Vec<String>,
+    executable: PathBuf,
+    working_directory: PathBuf,
+    started: u64,
+    run_time: u64,
+    virtual_memory: u64,
+    disk_read: u64,
+    disk_written: u64,
+}
+
+fn with_processes<T>(f: impl FnOnce(&System) -> T) -> T {
+    let mut guard = SYSTEM.lock().unwrap();
+    let stale = guard.as_ref().is_none_or(|(_, at)| at.elapsed() > MAX_SAMPLE_AGE);
+    let (sys, at) = guard.get_or_insert_with(|| (System::new(), Instant::now()));
+    if stale {
+        sys.refresh_users_list();
+        sys.refresh_processes();
+        std::thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL.max(Duration::from_millis(250)));
+    }
+    sys.refresh_processes();
+    *at = Instant::now();
+    f(sys)
+}
+
+fn info(sys: &System, process: &Process) -> ProcessInfo {
+    ProcessInfo {
+        pid: process.pid().as_u32(),
+        name: process.name().to_string(),
+        cpu: process.cpu_usage(),
+        memory: process.memory(),
+        status: process.status().to_string(),
+        user: process
+            .user_id()
+            .and_then(|uid| sys.get_user_by_id(uid))
+            .map(|user| user.name().to_string()),
+    }
+}
+
+fn format_line(process: &ProcessInfo) -> String {
+    format!(
+        "{:>7}  {:<24} CPU {:>6}%  Memory {:>9}  {}",
+        process.pid,
+        process.name,
+        locale::format_decimal(process.cpu as f64, 1),
+        format_bytes(process.memory),
+        process.user.as_deref().unwrap_or("")
+    )
+}
+
+fn by_memory(words: &[&str]) -> bool {
+    words.iter().any(|w| matches!(*w, "memory" | "ram" | "mem"))
+}
+
+fn sorted(sys: &System, memory: bool) -> Vec<ProcessInfo> {
+    let mut processes: Vec<ProcessInfo> = sys
+        .processes()
+        .values()
+        .map(|process| info(sys, process))
+        .collect();
+    if memory {
+        processes.sort_by_key(|process| std::cmp::Reverse(process.memory));
+    } else {
+        processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(b.memory.cmp(&a.memory)));
+    }
+    processes
+}
+
+/// `top_processes`, `top_processes memory 10`: the busiest processes.
+pub fn top_processes(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let memory = by_memory(&words);
+    let count = words.iter().find_map(|w| parse_number(w)).map_or(DEFAULT_TOP, |n| n as usize).max(1);
+
+    let mut processes = with_processes(|sys| sorted(sys, memory));
+    processes.truncate(count);
+    let Some(first) = processes.first() else {
+        return "Error: Could not read the process list.".to_string();
+    };
+
+    let summary = if memory {
+        format!("The process using the most memory is {}, with {}.", first.name, format_bytes(first.memory))
+    } else {
+        format!(
+            "The busiest process is {}, at {} percent CPU.",
+            first.name,
+            locale::format_decimal(first.cpu as f64, 0)
+        )
+    };
+    attach_data(json!({ "sort": if memory { "memory" } else { "cpu" }, "processes": processes }));
+    if let Err(e) = speak_to_speaker(&summary) {
+        return format!("Error: {}", e);
+    }
+    processes.iter().map(format_line).collect::<Vec<_>>().join("\n")
+}
+
+/// `ps_command [memory]`: every running process, busiest first.
+pub fn ps_command(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let processes = with_processes(|sys| sorted(sys, by_memory(&words)));
+
+    attach_data(json!({ "processes": processes }));
+    if let Err(e) = speak_to_speaker(&tr!("process-list-intro")) {
+        return format!("Error: {}", e);
+    }
+    processes.iter().map(format_line).collect::<Vec<_>>().join("\n")
+}
+
+fn matching(sys: &System, name: &str) -> Vec<ProcessInfo> {
+    let name = name.to_lowercase();
+    let mut found: Vec<ProcessInfo> = sys
+        .processes()
+        .values()
+        .filter(|process| process.name().to_lowercase().contains(&name))
+        .map(|process| info(sys, process))
+        .collect();
+    found.sort_by_key(|process| process.pid);
+    found
+}
+
+/// `find_process firefox`: processes whose name contains the text.
+pub fn find_process(arg: &str) -> String {
+    let name = arg.trim();
+    if name.is_empty() {
+        return "Error: Say which process to find, e.g. 'find_process firefox'.".to_string();
+    }
+    let found = with_processes(|sys| matching(sys, name));
+    if found.is_empty() {
+        let message = format!("No process called {} is running.", name);
+        if let Err(e) = speak_to_speaker(&message) {
+            return format!("Error: {}", e);
+        }
+        return message;
+    }
+
+    let memory: u64 = found.iter().map(|p| p.memory).sum();
+    let cpu: f32 = found.iter().map(|p| p.cpu).sum();
+    let summary = format!(
+        "Found {} {} process{}, using {} of memory and {} percent CPU.",
+        found.len(),
+        name,
+        if found.len() == 1 { "" } else { "es" },
+        format_bytes(memory),
+        locale::format_decimal(cpu as f64, 0)
+    );
+    attach_data(json!({ "processes": found }));
+    if let Err(e) = speak_to_speaker(&summary) {
+        return format!("Error: {}", e);
+    }
+    found.iter().map(format_line).collect::<Vec<_>>().join("\n")
+}
+
+fn parse_pid(arg: &str) -> Option<Pid> {
+    arg.split_whitespace()
+        .find_map(|w| w.parse::<usize>().ok())
+        .map(Pid::from)
+}
+
+fn format_seconds(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m {}s", minutes, seconds % 60),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// `process_details 1234`: everything known about one process.
+pub fn process_details(arg: &str) -> String {
+    let Some(pid) = parse_pid(arg) else {
+        return "Error: Say the process ID, e.g. 'process_details 1234'.".to_string();
+    };
+    let Some(details) = with_processes(|sys| {
+        let process = sys.process(pid)?;
+        Some(ProcessDetails {
+            info: info(sys, process),
+            parent: process.parent().map(|parent| parent.as_u32()),
+            command: process.cmd().to_vec(),
+            executable: process.exe().to_path_buf(),
+            working_directory: process.cwd().to_path_buf(),
+            started: process.start_time(),
+            run_time: process.run_time(),
+            virtual_memory: process.virtual_memory(),
+            disk_read: process.disk_usage().total_read_bytes,
+            disk_written: process.disk_usage().total_written_bytes,
+        })
+    }) else {
+        return format!("Error: No process with ID {}.", pid);
+    };
+
+    let started = Local
+        .timestamp_opt(details.started as i64, 0)
+        .single()
+        .map(|t| format!("{} {}", locale::format_date(t.date_naive()), locale::format_time(t.time())))
+        .unwrap_or_default();
+    let process = &details.info;
+    let lines = [
+        format!("PID: {}", process.pid),
+        format!("Name: {}", process.name),
+        format!("Status: {}", process.status),
+        format!("User: {}", process.user.as_deref().unwrap_or("unknown")),
+        format!("Parent: {}", details.parent.map(|pid| pid.to_string()).unwrap_or_default()),
+        format!("CPU: {}%", locale::format_decimal(process.cpu as f64, 1)),
+        format!("Memory: {}", format_bytes(process.memory)),
+        format!("Virtual memory: {}", format_bytes(details.virtual_memory)),
+        format!("Disk read: {}, written: {}", format_bytes(details.disk_read), format_bytes(details.disk_written)),
+        format!("Started: {}", started),
+        format!("Running for: {}", format_seconds(details.run_time)),
+        format!("Command: {}", details.command.join(" ")),
+        format!("Executable: {}", details.executable.display()),
+        format!("Working directory: {}", details.working_directory.display()),
+    ];
+
+    let summary = format!(
+        "Process {} is {}, using {} percent CPU and {} of memory.",
+        process.pid,
+        process.name,
+        locale::format_decimal(process.cpu as f64, 0),
+        format_bytes(process.memory)
+    );
+    attach_data(json!(details));
+    if let Err(e) = speak_to_speaker(&summary) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+/// A previewed kill waiting for its confirmation code.
+struct PendingKill {
+    token: u32,
+    /// PIDs with their start times, so a PID reused since the preview is left alone.
+    targets: Vec<(u32, u64)>,
+    force: bool,
+    expires: Instant,
+}
+
+static PENDING_KILLS: Mutex<Vec<PendingKill>> = Mutex::new(Vec::new());
+
+fn new_token() -> u32 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    1000 + (nanos ^ std::process::id()) % 9000
+}
+
+/// Processes whose name is exactly `name`, ignoring case, or whose PID it is.
+fn exact_targets(sys: &System, name: &str) -> Vec<ProcessInfo> {
+    if let Ok(pid) = name.parse::<usize>() {
+        return sys.process(Pid::from(pid)).map(|p| info(sys, p)).into_iter().collect();
+    }
+    let mut found: Vec<ProcessInfo> = sys
+        .processes()
+        .values()
+        .filter(|process| process.name().eq_ignore_ascii_case(name))
+        .map(|process| info(sys, process))
+        .collect();
+    found.sort_by_key(|process| process.pid);
+    found
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "es" }
+}
+
+/// `kill_process firefox` or `kill_process 1234 [force]` previews the processes
+/// with exactly that name or ID and gives a short-lived code;
+/// `kill_process confirm <code>` then stops exactly those processes, with
+/// SIGTERM, or SIGKILL when the preview asked for force.
+pub fn kill_process(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    if words.contains(&"confirm") {
+        return confirm_kill(&words);
+    }
+    let force = words.contains(&"force");
+    let target: Vec<&str> = words.iter().copied().filter(|w| !matches!(*w, "force" | "process")).collect();
+    if target.is_empty() {
+        return "Error: Say which process to stop, by name or ID.".to_string();
+    }
+    let target = target.join(" ");
+    let own_pid = std::process::id();
+
+    let targets = with_processes(|sys| {
+        exact_targets(sys, &target)
+            .into_iter()
+            .map(|p| {
+                let started = sys.process(Pid::from_u32(p.pid)).map_or(0, |process| process.start_time());
+                (p, started)
+            })
+            .collect::<Vec<_>>()
+    });
+    if targets.is_empty() {
+        return format!("Error: No process is called '{}'. Use find_process to look up the exact name.", target);
+    }
+    if targets.iter().any(|(p, _)| p.pid == own_pid || p.pid == 1) {
+        return "Error: I won't stop myself or the init process.".to_string();
+    }
+
+    let token = new_token();
+    let mut pending = PENDING_KILLS.lock().unwrap();
+    pending.retain(|kill| kill.expires > Instant::now());
+    pending.push(PendingKill {
+        token,
+        targets: targets.iter().map(|(p, started)| (p.pid, *started)).collect(),
+        force,
+        expires: Instant::now() + KILL_CONFIRM_WINDOW,
+    });
+    drop(pending);
+
+    let processes: Vec<&ProcessInfo> = targets.iter().map(|(p, _)| p).collect();
+    let names: Vec<String> = processes.iter().map(|p| format!("{} ({})", p.name, p.pid)).collect();
+    attach_data(json!({
+        "processes": processes,
+        "confirmed": false,
+        "token": token,
+        "expires_in": KILL_CONFIRM_WINDOW.as_secs(),
+        "signal": if force { "SIGKILL" } else { "SIGTERM" },
+    }));
+    let message = format!(
+        "This will {} {} process{}: {}. Say 'kill_process confirm {}' within {} seconds to go ahead.",
+        if force { "force kill" } else { "terminate" },
+        processes.len(),
+        plural(processes.len()),
+        names.join(", "),
+        token,
+        KILL_CONFIRM_WINDOW.as_secs()
+    );
+    let spoken = format!(
+        "Please confirm stopping {} process{} with code {}.",
+        processes.len(),
+        plural(processes.len()),
+        token
+    );
+    if let Err(e) = speak_to_speaker(&spoken) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+fn confirm_kill(words: &[&str]) -> String {
+    let Some(token) = words.iter().find_map(|w| w.parse::<u32>().ok()) else {
+        return "Error: Say the code from the preview, e.g. 'kill_process confirm 1234'.".to_string();
+    };
+    let kill = {
+        let mut pending = PENDING_KILLS.lock().unwrap();
+        pending.retain(|kill| kill.expires > Instant::now());
+        let Some(index) = pending.iter().position(|kill| kill.token == token) else {
+            return format!("Error: Code {} is unknown or has expired. Ask to kill the process again.", token);
+        };
+        pending.remove(index)
+    };
+
+    let signal = if kill.force { Signal::Kill } else { Signal::Term };
+    let (stopped, gone) = with_processes(|sys| {
+        let mut stopped = 0;
+        let mut gone = 0;
+        for &(pid, started) in &kill.targets {
+            match sys.process(Pid::from_u32(pid)).filter(|process| process.start_time() == started) {
+                Some(process) if process.kill_with(signal).unwrap_or(false) => stopped += 1,
+                Some(_) => {}
+                None => gone += 1,
+            }
+        }
+        (stopped, gone)
+    });
+    let failed = kill.targets.len() - stopped - gone;
+    attach_data(json!({
+        "pids": kill.targets.iter().map(|(pid, _)| pid).collect::<Vec<_>>(),
+        "confirmed": true,
+        "signal": if kill.force { "SIGKILL" } else { "SIGTERM" },
+        "stopped": stopped,
+        "already_exited": gone,
+        "failed": failed,
+    }));
+
+    let mut message = format!(
+        "{} {} process{}.",
+        if kill.force { "Killed" } else { "Sent terminate to" },
+        stopped,
+        plural(stopped)
+    );
+    if gone > 0 {
+        message = format!("{} {} had already exited.", message, gone);
+    }
+    if failed > 0 {
+        message = format!("{} Failed to stop {}; you may not have permission.", message, failed);
+    }
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_only_exact_names_or_pids() {
+        let own = std::process::id();
+        let (by_pid, by_name, by_prefix) = with_processes(|sys| {
+            let name = sys.process(Pid::from_u32(own)).unwrap().name().to_string();
+            let prefix = &name[..name.len() - 1];
+            (
+                exact_targets(sys, &own.to_string()),
+                exact_targets(sys, &name.to_uppercase()),
+                exact_targets(sys, prefix),
+            )
+        });
+        assert_eq!(by_pid.len(), 1);
+        assert!(by_name.iter().any(|p| p.pid == own));
+        assert!(by_prefix.iter().all(|p| p.pid != own));
+    }
+
+    #[test]
+    fn confirming_needs_a_previewed_code() {
+        assert!(kill_process("sleep confirm").starts_with("Error: Say the code"));
+        assert!(kill_process("confirm 99999").contains("unknown or has expired"));
+    }
+}
@@ -0,0 +1,539 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{fs, sync::{Arc, Mutex}};
+
+use crate::{data_path, dispatch, is_command, speak_to_speaker, CommandMap};
+
+const SCHEDULE_FILE: &str = "schedule.json";
+const TICK: std::time::Duration = std::time::Duration::from_secs(1);
+// Commands that are scheduled when said with a trailing time, as in
+// "shutdown in 30 minutes". Anything else goes through `schedule`, since many
+// commands take times or dates as their own arguments.
+const SCHEDULABLE: &[&str] = &["shutdown", "restart", "logout", "hibernate", "sleep", "remind_me"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Trigger {
+    Once,
+    Interval { seconds: i64 },
+    Daily { hour: u32, minute: u32 },
+    Weekdays { hour: u32, minute: u32 },
+    Weekly { weekday: u32, hour: u32, minute: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Job {
+    id: u64,
+    command: String,
+    description: String,
+    trigger: Trigger,
+    next_run: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Schedule {
+    next_id: u64,
+    jobs: Vec<Job>,
+}
+
+// Serializes read-modify-write cycles on the schedule file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn load_schedule() -> Schedule {
+    fs::read_to_string(data_path(SCHEDULE_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_schedule(schedule: &Schedule) -> std::io::Result<()> {
+    fs::write(data_path(SCHEDULE_FILE), serde_json::to_string_pretty(schedule)?)
+}
+
+pub fn parse_number(word: &str) -> Option<u32> {
+    if let Ok(n) = word.parse() {
+        return Some(n);
+    }
+    let n = match word {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        "fifteen" => 15,
+        "twenty" => 20,
+        "thirty" => 30,
+        "forty" => 40,
+        "forty-five" => 45,
+        "fifty" => 50,
+        "sixty" => 60,
+        "ninety" => 90,
+        _ => return None,
+    };
+    Some(n)
+}
+
+/// Converts "N unit" into seconds, e.g. "30 minutes" or "an hour".
+pub fn parse_duration(words: &[&str]) -> Option<i64> {
+    let mut total = 0;
+    let mut rest = words;
+    while !rest.is_empty() {
+        let [amount, unit, tail @ ..] = rest else {
+            return None;
+        };
+        let amount = parse_number(amount)? as i64;
+        let unit_seconds = match unit.trim_end_matches('s') {
+            "second" | "sec" => 1,
+            "minute" | "min" => 60,
+            "hour" | "hr" => 3600,
+            "day" => 86400,
+            "week" => 7 * 86400,
+            _ => return None,
+        };
+        total += amount * unit_seconds;
+        rest = match tail {
+            ["and", tail @ ..] => tail,
+            _ => tail,
+        };
+    }
+    (total > 0).then_some(total)
+}
+
+/// Parses spoken clock times such as "5 pm", "5:30pm", "17:00", "noon" or
+/// "7 o'clock". `default_pm` resolves bare hours said about the evening.
+pub fn parse_clock_time(words: &[&str], default_pm: Option<bool>) -> Option<NaiveTime> {
+    let text = words.join(" ").to_lowercase().replace('.', "");
+    match text.as_str() {
+        "noon" | "midday" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+
+    let text = text.trim_end_matches("o'clock").trim();
+    let (clock, meridiem) = if let Some(rest) = text.strip_suffix("am") {
+        (rest.trim(), Some(false))
+    } else if let Some(rest) = text.strip_suffix("pm") {
+        (rest.trim(), Some(true))
+    } else {
+        (text, default_pm)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.trim().parse::<u32>().ok()?, m.trim().parse::<u32>().ok()?),
+        None => (parse_number(clock)?, 0),
+    };
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn weekday_from_name(word: &str) -> Option<Weekday> {
+    word.trim_end_matches('s').parse().ok()
+}
+
+fn at_time(date: chrono::NaiveDate, hour: u32, minute: u32) -> DateTime<Local> {
+    let naive = date.and_hms_opt(hour, minute, 0).unwrap_or_default();
+    Local.from_local_datetime(&naive).earliest().unwrap_or_else(Local::now)
+}
+
+fn next_occurrence(trigger: Trigger, after: DateTime<Local>) -> Option<DateTime<Local>> {
+    let matches_day = |date: chrono::NaiveDate| match trigger {
+        Trigger::Daily { .. } => true,
+        Trigger::Weekdays { .. } => date.weekday().num_days_from_monday() < 5,
+        Trigger::Weekly { weekday, .. } => date.weekday().num_days_from_monday() == weekday,
+        _ => false,
+    };
+    match trigger {
+        Trigger::Once => None,
+        Trigger::Interval { seconds } => Some(after + Duration::seconds(seconds)),
+        Trigger::Daily { hour, minute }
+        | Trigger::Weekdays { hour, minute }
+        | Trigger::Weekly { hour, minute, .. } => (0..=7)
+            .map(|offset| after.date_naive() + Duration::days(offset))
+            .filter(|date| matches_day(*date))
+            .map(|date| at_time(date, hour, minute))
+            .find(|candidate| *candidate > after),
+    }
+}
+
+/// Resolves a spoken clock time to its next occurrence after `now`. A bare
+/// hour such as "5" means the next 5 o'clock, morning or evening.
+pub fn next_at(words: &[&str], now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let (words, tomorrow) = match words {
+        [time @ .., "tomorrow"] => (time, true),
+        _ => (words, false),
+    };
+    let time = parse_clock_time(words, None)?;
+    let today = at_time(now.date_naive(), time.hour(), time.minute());
+    let tomorrow_at = at_time(now.date_naive() + Duration::days(1), time.hour(), time.minute());
+    if tomorrow {
+        return Some(tomorrow_at);
+    }
+    if today > now {
+        return Some(today);
+    }
+    let has_meridiem = words.iter().any(|w| w.ends_with("am") || w.ends_with("pm"));
+    let evening = today + Duration::hours(12);
+    if !has_meridiem && time.hour() < 12 && evening > now {
+        Some(evening)
+    } else {
+        Some(tomorrow_at)
+    }
+}
+
+/// Parses the trailing time expression of a spoken command into a trigger and
+/// its first run time, e.g. "in 30 minutes", "at 5 pm", "every morning at 9".
+fn parse_trigger(words: &[&str], now: DateTime<Local>) -> Option<(Trigger, DateTime<Local>)> {
+    match words {
+        ["in", rest @ ..] | ["after", rest @ ..] => {
+            let seconds = parse_duration(rest)?;
+            Some((Trigger::Once, now + Duration::seconds(seconds)))
+        }
+        ["tomorrow", "at", rest @ ..] => {
+            let time = parse_clock_time(rest, None)?;
+            Some((Trigger::Once, at_time(now.date_naive() + Duration::days(1), time.hour(), time.minute())))
+        }
+        ["at", rest @ ..] => Some((Trigger::Once, next_at(rest, now)?)),
+        ["every", rest @ ..] | ["each", rest @ ..] => parse_recurring(rest, now),
+        ["daily", "at", rest @ ..] => parse_recurring(&[&["day", "at"], rest].concat(), now),
+        ["hourly"] => Some((Trigger::Interval { seconds: 3600 }, now + Duration::hours(1))),
+        _ => None,
+    }
+}
+
+fn parse_recurring(words: &[&str], now: DateTime<Local>) -> Option<(Trigger, DateTime<Local>)> {
+    if let Some(seconds) = parse_duration(words) {
+        return Some((Trigger::Interval { seconds }, now + Duration::seconds(seconds)));
+    }
+    if let Some(seconds) = parse_duration(&[&["1"], words].concat()) {
+        return Some((Trigger::Interval { seconds }, now + Duration::seconds(seconds)));
+    }
+
+    let (day, time_words) = match words {
+        [day, "at", time @ ..] => (*day, time),
+        [day] => (*day, &[][..]),
+        _ => return None,
+    };
+    let default_pm = match day {
+        "morning" => Some(false),
+        "afternoon" | "evening" | "night" => Some(true),
+        _ => None,
+    };
+    let time = if time_words.is_empty() {
+        match day {
+            "morning" => NaiveTime::from_hms_opt(8, 0, 0)?,
+            "afternoon" => NaiveTime::from_hms_opt(14, 0, 0)?,
+            "evening" => NaiveTime::from_hms_opt(18, 0, 0)?,
+            "night" => NaiveTime::from_hms_opt(21, 0, 0)?,
+            _ => return None,
+        }
+    } else {
+        parse_clock_time(time_words, default_pm)?
+    };
+    let (hour, minute) = (time.hour(), time.minute());
+
+    let trigger = match day {
+        "day" | "morning" | "afternoon" | "evening" | "night" => Trigger::Daily { hour, minute },
+        "weekday" => Trigger::Weekdays { hour, minute },
+        name => Trigger::Weekly {
+            weekday: weekday_from_name(name)?.num_days_from_monday(),
+            hour,
+            minute,
+        },
+    };
+    Some((trigger, next_occurrence(trigger, now)?))
+}
+
+/// Splits "shutdown in 30 minutes" into the command part and its trigger.
+/// Returns `None` when the line does not end in a recognisable time expression.
+fn split_trigger(line: &str, now: DateTime<Local>) -> Option<(String, String, Trigger, DateTime<Local>)> {
+    let lowered = line.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    (1..words.len()).find_map(|start| {
+        let (trigger, when) = parse_trigger(&words[start..], now)?;
+        let command = line.split_whitespace().take(start).collect::<Vec<_>>().join(" ");
+        Some((command, words[start..].join(" "), trigger, when))
+    })
+}
+
+fn describe_trigger(trigger: Trigger) -> String {
+    match trigger {
+        Trigger::Once => "once".to_string(),
+        Trigger::Interval { seconds } if seconds % 3600 == 0 => format!("every {} hours", seconds / 3600),
+        Trigger::Interval { seconds } if seconds % 60 == 0 => format!("every {} minutes", seconds / 60),
+        Trigger::Interval { seconds } => format!("every {} seconds", seconds),
+        Trigger::Daily { hour, minute } => format!("every day at {:02}:{:02}", hour, minute),
+        Trigger::Weekdays { hour, minute } => format!("every weekday at {:02}:{:02}", hour, minute),
+        Trigger::Weekly { weekday, hour, minute } => format!(
+            "every {:?} at {:02}:{:02}",
+            Weekday::try_from(weekday as u8).unwrap_or(Weekday::Mon),
+            hour,
+            minute
+        ),
+    }
+}
+
+fn add_job(command: &str, description: &str, trigger: Trigger, when: DateTime<Local>) -> std::io::Result<u64> {
+    let _guard = LOCK.lock().unwrap();
+    let mut schedule = load_schedule();
+    schedule.next_id += 1;
+    let id = schedule.next_id;
+    schedule.jobs.push(Job {
+        id,
+        command: command.to_string(),
+        description: description.to_string(),
+        trigger,
+        next_run: when.timestamp(),
+    });
+    save_schedule(&schedule)?;
+    Ok(id)
+}
+
+/// Schedules the line instead of running it when it is one of the
+/// `SCHEDULABLE` commands and ends in a time expression.
+pub fn try_schedule(commands: &CommandMap, line: &str) -> Option<String> {
+    let name = line.split_whitespace().next()?;
+    if !SCHEDULABLE.contains(&name) || !is_command(commands, name) {
+        return None;
+    }
+    let (command, description, trigger, when) = split_trigger(line, Local::now())?;
+    Some(schedule_command(&command, &description, trigger, when))
+}
+
+fn schedule_command(command: &str, description: &str, trigger: Trigger, when: DateTime<Local>) -> String {
+    match add_job(command, description, trigger, when) {
+        Ok(id) => {
+            let message = format!(
+                "Scheduled task {}: '{}' {}, next at {}.",
+                id,
+                command,
+                description,
+                when.format("%A %H:%M")
+            );
+            if let Err(e) = speak_to_speaker(&message) {
+                return format!("Error: {}", e);
+            }
+            message
+        }
+        Err(e) => format!("Failed to save schedule: {}", e),
+    }
+}
+
+/// `schedule <command> <when>`: explicit form for commands whose arguments
+/// would otherwise swallow the time expression.
+pub fn schedule(commands: &CommandMap, arg: &str) -> String {
+    let Some((command, description, trigger, when)) = split_trigger(arg, Local::now()) else {
+        return "Error: Say when, e.g. 'schedule disk_usage every morning at 9'.".to_string();
+    };
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let resolved = (1..=words.len())
+        .rev()
+        .map(|len| (words[..len].join("_").to_lowercase(), words[len..].join(" ")))
+        .find(|(name, _)| is_command(commands, name))
+        .map(|(name, rest)| if rest.is_empty() { name } else { format!("{} {}", name, rest) });
+    match resolved {
+        Some(command) => schedule_command(&command, &description, trigger, when),
+        None => format!("Error: Unknown command '{}'.", command),
+    }
+}
+
+pub fn remind_me(arg: &str) -> String {
+    let text = arg.trim();
+    let text = text.strip_prefix("to ").unwrap_or(text);
+    if text.is_empty() {
+        return "Error: What should I remind you about?".to_string();
+    }
+    let message = format!("Reminder: {}", text);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn list_scheduled(_arg: &str) -> String {
+    let schedule = {
+        let _guard = LOCK.lock().unwrap();
+        load_schedule()
+    };
+    if schedule.jobs.is_empty() {
+        if let Err(e) = speak_to_speaker("Nothing is scheduled.") {
+            return format!("Error: {}", e);
+        }
+        return "Nothing is scheduled.".to_string();
+    }
+
+    if let Err(e) = speak_to_speaker(&format!("You have {} scheduled tasks.", schedule.jobs.len())) {
+        return format!("Error: {}", e);
+    }
+    schedule
+        .jobs
+        .iter()
+        .map(|job| {
+            let next = Local
+                .timestamp_opt(job.next_run, 0)
+                .single()
+                .map(|t| t.format("%a %d %b %H:%M").to_string())
+                .unwrap_or_default();
+            format!("{}. {} ({}; {}), next run {}", job.id, job.command, job.description, describe_trigger(job.trigger), next)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn cancel_scheduled(arg: &str) -> String {
+    let Some(id) = arg.split_whitespace().last().and_then(parse_number) else {
+        return "Error: Please say which task number to cancel.".to_string();
+    };
+    let _guard = LOCK.lock().unwrap();
+    let mut schedule = load_schedule();
+    let before = schedule.jobs.len();
+    schedule.jobs.retain(|job| job.id != id as u64);
+    if schedule.jobs.len() == before {
+        return format!("Error: No scheduled task {}.", id);
+    }
+    if let Err(e) = save_schedule(&schedule) {
+        return format!("Failed to save schedule: {}", e);
+    }
+
+    let message = format!("Cancelled scheduled task {}.", id);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// Removes due jobs from the schedule, rescheduling recurring ones.
+fn take_due(now: DateTime<Local>) -> Vec<Job> {
+    let _guard = LOCK.lock().unwrap();
+    let mut schedule = load_schedule();
+    let timestamp = now.timestamp();
+    if !schedule.jobs.iter().any(|job| job.next_run <= timestamp) {
+        return Vec::new();
+    }
+
+    let mut due = Vec::new();
+    schedule.jobs.retain_mut(|job| {
+        if job.next_run > timestamp {
+            return true;
+        }
+        due.push(job.clone());
+        match next_occurrence(job.trigger, now) {
+            Some(next) => {
+                job.next_run = next.timestamp();
+                true
+            }
+            None => false,
+        }
+    });
+    if let Err(e) = save_schedule(&schedule) {
+        eprintln!("Failed to save schedule: {}", e);
+    }
+    due
+}
+
+fn run_job(commands: &CommandMap, job: &Job) {
+    // Commands speak their own results, so the response is only logged.
+    let response = dispatch(commands, &job.command);
+    println!("⏰ Scheduled task {} ({}): {}", job.id, job.command, response);
+}
+
+/// Spawns the background loop that runs due jobs through the dispatcher.
+pub fn start(commands: Arc<CommandMap>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            for job in take_due(Local::now()) {
+                let commands = Arc::clone(&commands);
+                tokio::task::spawn_blocking(move || run_job(&commands, &job));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration(&["30", "minutes"]), Some(1800));
+        assert_eq!(parse_duration(&["an", "hour", "and", "ten", "minutes"]), Some(4200));
+        assert_eq!(parse_duration(&["2", "days"]), Some(2 * 86400));
+        assert_eq!(parse_duration(&["soon"]), None);
+        assert_eq!(parse_duration(&[]), None);
+    }
+
+    #[test]
+    fn parses_clock_times() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(parse_clock_time(&["5", "pm"], None), time(17, 0));
+        assert_eq!(parse_clock_time(&["5:30pm"], None), time(17, 30));
+        assert_eq!(parse_clock_time(&["12", "am"], None), time(0, 0));
+        assert_eq!(parse_clock_time(&["17:00"], None), time(17, 0));
+        assert_eq!(parse_clock_time(&["noon"], None), time(12, 0));
+        assert_eq!(parse_clock_time(&["7", "o'clock"], Some(true)), time(19, 0));
+        assert_eq!(parse_clock_time(&["13", "pm"], None), None);
+    }
+
+    #[test]
+    fn bare_hours_mean_the_next_occurrence() {
+        assert_eq!(next_at(&["5"], noon()), Some(Local.with_ymd_and_hms(2026, 3, 4, 17, 0, 0).unwrap()));
+        assert_eq!(next_at(&["9", "am"], noon()), Some(Local.with_ymd_and_hms(2026, 3, 5, 9, 0, 0).unwrap()));
+        assert_eq!(next_at(&["9", "tomorrow"], noon()), Some(Local.with_ymd_and_hms(2026, 3, 5, 9, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn splits_trailing_triggers() {
+        let (command, description, trigger, when) = split_trigger("shutdown in 30 minutes", noon()).unwrap();
+        assert_eq!((command.as_str(), description.as_str(), trigger), ("shutdown", "in 30 minutes", Trigger::Once));
+        assert_eq!(when, noon() + Duration::minutes(30));
+
+        let (command, _, trigger, _) = split_trigger("disk_usage every morning at 9", noon()).unwrap();
+        assert_eq!(command, "disk_usage");
+        assert_eq!(trigger, Trigger::Daily { hour: 9, minute: 0 });
+
+        let (_, _, trigger, _) = split_trigger("backup every friday at 6 pm", noon()).unwrap();
+        assert_eq!(trigger, Trigger::Weekly { weekday: 4, hour: 18, minute: 0 });
+        assert!(split_trigger("list_files", noon()).is_none());
+    }
+
+    #[test]
+    fn only_schedulable_commands_are_scheduled_implicitly() {
+        let mut commands: CommandMap = CommandMap::new();
+        commands.insert("date_of".to_string(), Box::new(|_: &str| String::new()));
+        commands.insert("create_file".to_string(), Box::new(|_: &str| String::new()));
+        assert!(try_schedule(&commands, "date_of in 10 days").is_none());
+        assert!(try_schedule(&commands, "create_file notes at 5").is_none());
+    }
+
+    #[test]
+    fn schedules_spoken_requests() {
+        crate::i18n::set_request_language(Some("en"));
+        let mut commands: CommandMap = CommandMap::new();
+        commands.insert("remind_me".to_string(), Box::new(|arg: &str| format!("Reminder: {}", arg)));
+        commands.insert("disk_usage".to_string(), Box::new(|_: &str| "Disk report".to_string()));
+
+        dispatch(&commands, "remind me to check the build at 5 pm");
+        dispatch(&commands, "run disk usage every morning at 9");
+        let jobs = load_schedule().jobs;
+        let job = |command: &str| jobs.iter().find(|job| job.command == command).unwrap();
+        let reminder = job("remind_me to check the build");
+        assert_eq!((reminder.description.as_str(), reminder.trigger), ("at 5 pm", Trigger::Once));
+        let report = job("disk_usage");
+        assert_eq!(report.trigger, Trigger::Daily { hour: 9, minute: 0 });
+    }
+}
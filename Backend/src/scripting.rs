@@ -0,0 +1,246 @@
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+};
+use sysinfo::{System, SystemExt};
+
+use crate::{data_path, metrics, speak_to_speaker, CommandMap};
+
+const SCRIPTS_DIR: &str = "commands";
+const ALLOWED_PROGRAMS_FILE: &str = "allowed_programs.txt";
+const DEFAULT_ALLOWED_PROGRAMS: &[&str] = &["date", "uname", "whoami", "hostname", "uptime"];
+
+#[derive(Debug, Clone)]
+struct ScriptInfo {
+    name: String,
+    description: String,
+    usage: String,
+    path: PathBuf,
+}
+
+static SCRIPTS: Mutex<Vec<ScriptInfo>> = Mutex::new(Vec::new());
+
+/// Scripts may only touch files inside the personal directory used by `go_my_directory`.
+fn sandbox_root() -> PathBuf {
+    let root = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("my_directory");
+    let _ = fs::create_dir_all(&root);
+    root.canonicalize().unwrap_or(root)
+}
+
+fn sandboxed(path: &str) -> Result<PathBuf, String> {
+    let root = sandbox_root();
+    let candidate = root.join(path);
+    let parent = candidate.parent().unwrap_or(&root);
+    let parent = parent
+        .canonicalize()
+        .map_err(|e| format!("Invalid path '{}': {}", path, e))?;
+    if !parent.starts_with(&root) {
+        return Err(format!("Path '{}' is outside the sandbox.", path));
+    }
+    let resolved = match candidate.file_name() {
+        Some(name) if candidate != root => parent.join(name),
+        _ => return Ok(parent),
+    };
+    // The last component may itself be a symlink; follow it before trusting it.
+    // A dangling link is refused too, since writing through it would create its target.
+    if fs::symlink_metadata(&resolved).is_err() {
+        return Ok(resolved);
+    }
+    let target = resolved
+        .canonicalize()
+        .map_err(|e| format!("Invalid path '{}': {}", path, e))?;
+    if !target.starts_with(&root) {
+        return Err(format!("Path '{}' is outside the sandbox.", path));
+    }
+    Ok(target)
+}
+
+fn allowed_programs() -> Vec<String> {
+    match fs::read_to_string(data_path(ALLOWED_PROGRAMS_FILE)) {
+        Ok(text) => text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect(),
+        Err(_) => DEFAULT_ALLOWED_PROGRAMS.iter().map(|p| p.to_string()).collect(),
+    }
+}
+
+fn script_speak(text: &str) -> String {
+    match speak_to_speaker(text) {
+        Ok(_) => text.to_string(),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+fn script_system_info(key: &str) -> Dynamic {
+    let sys = System::new();
+    match key {
+        "total_memory_mb" => Dynamic::from((metrics::snapshot().memory_total / 1024 / 1024) as i64),
+        "used_memory_mb" => Dynamic::from((metrics::snapshot().memory_used / 1024 / 1024) as i64),
+        "cpu_usage" => Dynamic::from(metrics::snapshot().cpu_usage as f64),
+        "cpu_count" => Dynamic::from(metrics::snapshot().cpus.len() as i64),
+        "cpu_brand" => Dynamic::from(metrics::snapshot().cpus.first().map(|cpu| cpu.brand.clone()).unwrap_or_default()),
+        "uptime_seconds" => Dynamic::from(metrics::snapshot().uptime as i64),
+        "hostname" => Dynamic::from(sys.host_name().unwrap_or_default()),
+        "os" => Dynamic::from(sys.long_os_version().unwrap_or_default()),
+        _ => Dynamic::UNIT,
+    }
+}
+
+fn script_run_program(program: &str, args: Array) -> String {
+    if !allowed_programs().iter().any(|allowed| allowed == program) {
+        return format!("Error: Program '{}' is not allowed.", program);
+    }
+    let args: Vec<String> = args.into_iter().map(|arg| arg.to_string()).collect();
+    match Command::new(program).args(&args).current_dir(sandbox_root()).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+        Ok(output) => format!("Command failed with error: {}", String::from_utf8_lossy(&output.stderr)),
+        Err(e) => format!("Failed to execute command: {}", e),
+    }
+}
+
+fn script_read_file(path: &str) -> String {
+    match sandboxed(path).and_then(|p| fs::read_to_string(p).map_err(|e| e.to_string())) {
+        Ok(content) => content,
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+fn script_write_file(path: &str, content: &str) -> String {
+    match sandboxed(path).and_then(|p| fs::write(p, content).map_err(|e| e.to_string())) {
+        Ok(_) => format!("Wrote {}", path),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+fn script_list_dir(path: &str) -> Array {
+    sandboxed(path)
+        .ok()
+        .and_then(|p| fs::read_dir(p).ok())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| Dynamic::from(entry.file_name().to_string_lossy().into_owned()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn build_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(1_000_000);
+    engine.set_max_call_levels(32);
+    engine.set_max_string_size(1024 * 1024);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    engine.disable_symbol("eval");
+
+    engine.register_fn("speak", script_speak);
+    engine.register_fn("system_info", script_system_info);
+    engine.register_fn("run_program", script_run_program);
+    engine.register_fn("read_file", script_read_file);
+    engine.register_fn("write_file", script_write_file);
+    engine.register_fn("list_dir", script_list_dir);
+    engine
+}
+
+fn metadata_field(metadata: &Map, key: &str) -> Option<String> {
+    metadata
+        .get(key)
+        .and_then(|value| value.clone().into_string().ok())
+        .filter(|value| !value.is_empty())
+}
+
+fn load_script(engine: &Engine, path: &Path) -> Result<(ScriptInfo, AST), String> {
+    let ast = engine
+        .compile_file(path.to_path_buf())
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let metadata: Map = engine
+        .call_fn(&mut Scope::new(), &ast, "metadata", ())
+        .map_err(|e| format!("{}: metadata(): {}", path.display(), e))?;
+    let name = metadata_field(&metadata, "name")
+        .ok_or_else(|| format!("{}: metadata() has no name", path.display()))?;
+
+    let info = ScriptInfo {
+        name: name.trim().replace(' ', "_").to_lowercase(),
+        description: metadata_field(&metadata, "description").unwrap_or_default(),
+        usage: metadata_field(&metadata, "usage").unwrap_or_default(),
+        path: path.to_path_buf(),
+    };
+    Ok((info, ast))
+}
+
+/// Registers every `*.rhai` file in the commands directory. Each script must
+/// define `metadata()` returning `#{ name, description, usage }` and `run(arg)`.
+pub fn load_scripts(commands: &mut CommandMap) {
+    let dir = data_path(SCRIPTS_DIR);
+    let _ = fs::create_dir_all(&dir);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+
+    let engine = Arc::new(build_engine());
+    let mut loaded = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("rhai") {
+            continue;
+        }
+        let (info, ast) = match load_script(&engine, &path) {
+            Ok(script) => script,
+            Err(e) => {
+                eprintln!("Skipping script {}", e);
+                continue;
+            }
+        };
+        if commands.contains_key(&info.name) {
+            eprintln!("Skipping script {}: command '{}' already exists", path.display(), info.name);
+            continue;
+        }
+
+        let engine = Arc::clone(&engine);
+        let ast = Arc::new(ast);
+        commands.insert(
+            info.name.clone(),
+            Box::new(move |arg: &str| {
+                match engine.call_fn::<Dynamic>(&mut Scope::new(), &ast, "run", (arg.to_string(),)) {
+                    Ok(value) => value.to_string(),
+                    Err(e) => format!("Error: {}", e),
+                }
+            }),
+        );
+        loaded.push(info);
+    }
+
+    println!("📜 Loaded {} script commands from {}", loaded.len(), dir.display());
+    *SCRIPTS.lock().unwrap() = loaded;
+}
+
+pub fn list_scripts(_arg: &str) -> String {
+    let scripts = SCRIPTS.lock().unwrap();
+    if scripts.is_empty() {
+        return format!("No script commands found in {}.", data_path(SCRIPTS_DIR).display());
+    }
+    if let Err(e) = speak_to_speaker(&format!("There are {} script commands.", scripts.len())) {
+        return format!("Error: {}", e);
+    }
+    scripts
+        .iter()
+        .map(|script| {
+            format!(
+                "{}: {} (usage: {}) [{}]",
+                script.name,
+                script.description,
+                if script.usage.is_empty() { &script.name } else { &script.usage },
+                script.path.display()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,542 @@
+use chrono::{DateTime, Local};
+use ignore::{types::TypesBuilder, WalkBuilder, WalkState};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+use walkdir::WalkDir;
+
+use crate::{attach_data, i18n::tr, paths, speak_to_speaker};
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+// Files past this size are skipped; they are rarely what a spoken search is after.
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+const SNIPPET_LENGTH: usize = 160;
+
+// Spoken names for the file types known to the `ignore` crate.
+const TYPE_NAMES: [(&str, &str); 12] = [
+    ("python", "py"),
+    ("javascript", "js"),
+    ("typescript", "ts"),
+    ("text", "txt"),
+    ("shell", "sh"),
+    ("bash", "sh"),
+    ("markdown", "markdown"),
+    ("yaml", "yaml"),
+    ("c++", "cpp"),
+    ("golang", "go"),
+    ("ruby", "ruby"),
+    ("rust", "rust"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+struct ContentMatch {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    snippet: String,
+}
+
+#[derive(Debug)]
+struct ContentQuery {
+    pattern: String,
+    regex: bool,
+    case_sensitive: bool,
+    file_types: Vec<String>,
+    limit: usize,
+    hidden: bool,
+    ignored: bool,
+    /// A folder named relative to the working directory, as in "in src".
+    root: Option<PathBuf>,
+}
+
+/// A word naming an existing folder under the working directory.
+fn relative_folder(word: &str) -> Option<PathBuf> {
+    let folder = std::env::current_dir().ok()?.join(word.trim_end_matches(['?', ',']));
+    folder.is_dir().then_some(folder)
+}
+
+/// Takes the search text out of a request. A quoted pattern is used as is;
+/// otherwise the pattern is what's left after the options. Connectors like
+/// "in" never become part of an unquoted pattern; "in src" names the folder
+/// to search when `src` exists.
+fn parse_content_query(rest: &str) -> ContentQuery {
+    let mut query = ContentQuery {
+        pattern: String::new(),
+        regex: false,
+        case_sensitive: false,
+        file_types: Vec::new(),
+        limit: DEFAULT_LIMIT,
+        hidden: false,
+        ignored: false,
+        root: None,
+    };
+    let (quoted, rest) = match (rest.find('"'), rest.rfind('"')) {
+        (Some(start), Some(end)) if end > start => {
+            (Some(rest[start + 1..end].to_string()), format!("{} {}", &rest[..start], &rest[end + 1..]))
+        }
+        _ => (None, rest.to_string()),
+    };
+
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    let mut pattern = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i].to_lowercase();
+        let next = words.get(i + 1).map(|w| w.to_lowercase());
+        match (word.as_str(), next.as_deref()) {
+            ("regex" | "regexp" | "pattern", _) => query.regex = true,
+            ("regular", Some("expression")) => {
+                query.regex = true;
+                i += 1;
+            }
+            ("case", Some("sensitive")) | ("exact", Some("case")) | ("match", Some("case")) => {
+                query.case_sensitive = true;
+                i += 1;
+            }
+            ("limit" | "first" | "max", Some(n)) if n.parse::<usize>().is_ok() => {
+                query.limit = n.parse::<usize>().unwrap().clamp(1, MAX_LIMIT);
+                i += 1;
+            }
+            ("in" | "under" | "inside" | "within", Some(_))
+                if !matches!(words.get(i + 2).map(|w| w.to_lowercase()).as_deref(), Some("files" | "file")) =>
+            {
+                if let Some(folder) = relative_folder(words[i + 1]) {
+                    query.root = Some(folder);
+                    i += 1;
+                }
+            }
+            ("in" | "under" | "inside" | "within", _) => {}
+            ("hidden", _) => query.hidden = true,
+            ("ignored", _) | ("all", Some("files")) => query.ignored = true,
+            ("only" | "just" | "type", Some(kind)) | (kind, Some("files")) if !is_filler(kind) => {
+                let kind = TYPE_NAMES.iter().find(|(name, _)| *name == kind).map_or(kind, |(_, name)| *name);
+                query.file_types.push(kind.to_string());
+                i += 1;
+            }
+            ("files" | "file" | "containing" | "contain" | "contains" | "with" | "for" | "text" | "find" | "search", _)
+                if quoted.is_none() && pattern.is_empty() => {}
+            _ => pattern.push(words[i]),
+        }
+        i += 1;
+    }
+    query.pattern = quoted.unwrap_or_else(|| pattern.join(" "));
+    query
+}
+
+fn is_filler(word: &str) -> bool {
+    matches!(word, "the" | "all" | "my" | "any" | "these" | "those" | "in" | "of" | "find" | "search" | "list" | "show")
+}
+
+fn build_regex(query: &ContentQuery) -> Result<Regex, String> {
+    let source = if query.regex { query.pattern.clone() } else { regex::escape(&query.pattern) };
+    RegexBuilder::new(&source)
+        .case_insensitive(!query.case_sensitive)
+        .build()
+        .map_err(|e| format!("'{}' is not a valid regular expression: {}", query.pattern, e))
+}
+
+fn snippet(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(SNIPPET_LENGTH) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+fn search_file(path: &Path, regex: &Regex) -> Vec<ContentMatch> {
+    let Ok(bytes) = fs::read(path) else {
+        return Vec::new();
+    };
+    // Treat anything with a NUL byte near the start as binary.
+    if bytes.iter().take(8192).any(|b| *b == 0) {
+        return Vec::new();
+    }
+    String::from_utf8_lossy(&bytes)
+        .lines()
+        .enumerate()
+        .filter_map(|(number, line)| {
+            let found = regex.find(line)?;
+            Some(ContentMatch {
+                path: path.to_path_buf(),
+                line: number + 1,
+                column: line[..found.start()].chars().count() + 1,
+                snippet: snippet(line),
+            })
+        })
+        .collect()
+}
+
+/// Walks the tree in parallel, honouring `.gitignore`, `.ignore` and hidden
+/// files unless asked not to, until `limit` matches are found.
+fn search_contents(root: &Path, query: &ContentQuery, regex: &Regex) -> Result<(Vec<ContentMatch>, bool), String> {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(!query.hidden)
+        .git_ignore(!query.ignored)
+        .git_exclude(!query.ignored)
+        .ignore(!query.ignored)
+        .parents(!query.ignored)
+        .require_git(false)
+        .max_filesize(Some(MAX_FILE_SIZE));
+    if !query.file_types.is_empty() {
+        let mut types = TypesBuilder::new();
+        types.add_defaults();
+        for kind in &query.file_types {
+            types.select(kind);
+        }
+        builder.types(types.build().map_err(|e| format!("Unknown file type: {}", e))?);
+    }
+
+    let found = Mutex::new(Vec::new());
+    let count = AtomicUsize::new(0);
+    builder.build_parallel().run(|| {
+        Box::new(|entry| {
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                return WalkState::Continue;
+            }
+            let matches = search_file(entry.path(), regex);
+            if matches.is_empty() {
+                return WalkState::Continue;
+            }
+            let total = count.fetch_add(matches.len(), Ordering::SeqCst) + matches.len();
+            found.lock().unwrap().extend(matches);
+            if total >= query.limit {
+                WalkState::Quit
+            } else {
+                WalkState::Continue
+            }
+        })
+    });
+
+    let mut found = found.into_inner().unwrap();
+    found.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    let truncated = found.len() > query.limit || count.load(Ordering::SeqCst) >= query.limit;
+    found.truncate(query.limit);
+    Ok((found, truncated))
+}
+
+/// `search_content TODO [in place] [regex] [case sensitive] [rust files] [limit N] [hidden] [ignored]`:
+/// files whose contents match, with line numbers and snippets.
+pub fn search_content(arg: &str) -> String {
+    let (root, rest) = paths::place_or_cwd(arg);
+    let query = parse_content_query(&rest);
+    let root = query.root.clone().unwrap_or(root);
+    if query.pattern.trim().is_empty() {
+        return "Error: Say what to look for, e.g. 'search_content TODO'.".to_string();
+    }
+    let regex = match build_regex(&query) {
+        Ok(regex) => regex,
+        Err(e) => return format!("Error: {}", e),
+    };
+    let (matches, truncated) = match search_contents(&root, &query, &regex) {
+        Ok(result) => result,
+        Err(e) => return format!("Error: {}", e),
+    };
+
+    let mut files: Vec<&Path> = matches.iter().map(|m| m.path.as_path()).collect();
+    files.dedup();
+    let message = match (matches.len(), files.len()) {
+        (0, _) => format!("No files in {} contain {}.", paths::speakable(&root), query.pattern),
+        (1, _) => format!("Found {} once, in {}.", query.pattern, display_name(files[0])),
+        (n, 1) => format!("Found {} {} times, all in {}.", query.pattern, n, display_name(files[0])),
+        (n, f) => format!(
+            "Found {} {}{} times in {} files.",
+            query.pattern,
+            if truncated { "at least " } else { "" },
+            n,
+            f
+        ),
+    };
+    let lines: Vec<String> = matches
+        .iter()
+        .map(|m| {
+            let path = m.path.strip_prefix(&root).unwrap_or(&m.path);
+            format!("{}:{}:{}: {}", path.display(), m.line, m.column, m.snippet)
+        })
+        .collect();
+    attach_data(json!({
+        "root": root,
+        "pattern": query.pattern,
+        "regex": query.regex,
+        "case_sensitive": query.case_sensitive,
+        "file_types": query.file_types,
+        "truncated": truncated,
+        "files": files,
+        "matches": matches,
+    }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    if lines.is_empty() {
+        return message;
+    }
+    lines.join("\n")
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string())
+}
+
+const DEFAULT_RESULTS: usize = 20;
+const MAX_RESULTS: usize = 500;
+const DEFAULT_NAME_DEPTH: usize = 12;
+
+#[derive(Debug, Serialize)]
+struct NameMatch {
+    path: PathBuf,
+    is_dir: bool,
+    /// Lower is better: 0 exact name, 1 prefix, 2 substring or glob, 3+ fuzzy.
+    rank: u32,
+    modified: Option<i64>,
+    depth: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct NameQuery {
+    pub pattern: String,
+    pub limit: usize,
+    pub max_depth: usize,
+    pub fuzzy: bool,
+    pub hidden: bool,
+    pub folders_only: bool,
+    pub files_only: bool,
+}
+
+pub(crate) fn parse_name_query(rest: &str) -> NameQuery {
+    let mut query = NameQuery {
+        pattern: String::new(),
+        limit: DEFAULT_RESULTS,
+        max_depth: DEFAULT_NAME_DEPTH,
+        fuzzy: true,
+        hidden: false,
+        folders_only: false,
+        files_only: false,
+    };
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    let mut pattern = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i].to_lowercase();
+        let number = words.get(i + 1).and_then(|w| w.parse::<usize>().ok());
+        match word.as_str() {
+            "depth" | "levels" if number.is_some() => {
+                query.max_depth = number.unwrap().max(1);
+                i += 1;
+            }
+            "limit" | "top" | "first" if number.is_some() => {
+                query.limit = number.unwrap().clamp(1, MAX_RESULTS);
+                i += 1;
+            }
+            "exact" | "exactly" => query.fuzzy = false,
+            "hidden" => query.hidden = true,
+            // "folder called notes", "file named resume": a kind before the name.
+            "folder" | "folders" | "directory" | "directories" if pattern.is_empty() => query.folders_only = true,
+            "file" | "files" if pattern.is_empty() => query.files_only = true,
+            "my" | "the" | "a" | "called" | "named" if pattern.is_empty() => {}
+            _ => pattern.push(words[i]),
+        }
+        i += 1;
+    }
+    query.pattern = crate::clean_filename(&pattern.join(" "));
+    query
+}
+
+/// Scores `pattern` as an in-order subsequence of `name`; fewer gaps rank
+/// higher. `None` when the letters don't all appear in order.
+fn fuzzy_rank(pattern: &str, name: &str) -> Option<u32> {
+    let mut gaps = 0;
+    let mut last: Option<usize> = None;
+    let mut chars = name.char_indices();
+    for wanted in pattern.chars().filter(|c| !c.is_whitespace()) {
+        let (index, _) = chars.by_ref().find(|(_, c)| *c == wanted)?;
+        if last.is_some_and(|last| index > last + 1) {
+            gaps += 1;
+        }
+        last = Some(index);
+    }
+    Some(3 + gaps)
+}
+
+/// How well a file name matches a spoken or typed pattern; lower is better.
+pub(crate) fn rank(pattern: &str, name: &str, fuzzy: bool) -> Option<u32> {
+    let name = name.to_lowercase();
+    let pattern = pattern.to_lowercase();
+    if pattern.contains(['*', '?']) {
+        return paths::glob_match(&pattern, &name).then_some(2);
+    }
+    let stem = Path::new(&name).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    if name == pattern || stem == pattern {
+        Some(0)
+    } else if name.starts_with(&pattern) {
+        Some(1)
+    } else if name.contains(&pattern) {
+        Some(2)
+    } else if fuzzy && pattern.chars().count() >= 3 {
+        // Long gappy matches are noise rather than typos.
+        fuzzy_rank(&pattern, &name).filter(|rank| *rank <= 3 + pattern.chars().count() as u32 / 2)
+    } else {
+        None
+    }
+}
+
+/// Follows symlinks, which walkdir guards against loops by refusing to
+/// descend into a directory that is already one of its own ancestors.
+fn search_names(root: &Path, query: &NameQuery) -> (Vec<NameMatch>, usize) {
+    let mut found = Vec::new();
+    let mut skipped = 0;
+    let walker = WalkDir::new(root)
+        .follow_links(true)
+        .max_depth(query.max_depth)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| query.hidden || entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'));
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let is_dir = entry.file_type().is_dir();
+        if (query.folders_only && !is_dir) || (query.files_only && is_dir) {
+            continue;
+        }
+        let Some(rank) = rank(&query.pattern, &entry.file_name().to_string_lossy(), query.fuzzy) else {
+            continue;
+        };
+        let modified = entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .map(|time| DateTime::<Local>::from(time).timestamp());
+        let depth = entry.depth();
+        found.push(NameMatch { path: entry.into_path(), is_dir, rank, modified, depth });
+    }
+    found.sort_by(|a, b| {
+        a.rank
+            .cmp(&b.rank)
+            .then(b.modified.cmp(&a.modified))
+            .then(a.depth.cmp(&b.depth))
+            .then(a.path.cmp(&b.path))
+    });
+    (found, skipped)
+}
+
+/// `search_file_or_folder resume [in place] [depth N] [limit N] [exact] [hidden]`:
+/// every file or folder whose name matches, best first. Patterns may use `*`
+/// and `?`; otherwise close misspellings also match.
+pub fn search_file_or_folder(arg: &str) -> String {
+    let (root, rest) = paths::place_or_cwd(arg);
+    let query = parse_name_query(&rest);
+    if query.pattern.is_empty() {
+        return tr!("file-name-missing");
+    }
+
+    let (mut found, skipped) = search_names(&root, &query);
+    let total = found.len();
+    found.truncate(query.limit);
+    let Some(best) = found.first() else {
+        return tr!("search-not-found", name = query.pattern);
+    };
+
+    let best_path = best.path.display().to_string();
+    let message = if total == 1 {
+        tr!("search-found", path = best_path)
+    } else {
+        tr!("search-found-many", count = total.to_string(), name = query.pattern, path = best_path)
+    };
+    let lines: Vec<String> = found
+        .iter()
+        .map(|m| format!("{}{}", m.path.display(), if m.is_dir { "/" } else { "" }))
+        .collect();
+    attach_data(json!({
+        "root": root,
+        "pattern": query.pattern,
+        "total": total,
+        "unreadable": skipped,
+        "matches": found,
+    }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    if total > found.len() {
+        format!("{}\n(showing {} of {})", lines.join("\n"), found.len(), total)
+    } else {
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_connectors_out_of_content_patterns() {
+        let query = parse_content_query("TODO in rust files");
+        assert_eq!(query.pattern, "TODO");
+        assert_eq!(query.file_types, vec!["rust"]);
+        assert_eq!(query.root, None);
+
+        let query = parse_content_query("files containing fn main under src limit 5");
+        assert_eq!(query.pattern, "fn main");
+        assert_eq!(query.root, Some(std::env::current_dir().unwrap().join("src")));
+        assert_eq!(query.limit, 5);
+
+        let query = parse_content_query("\"sign in\" in no-such-folder");
+        assert_eq!(query.pattern, "sign in");
+        assert_eq!(query.root, None);
+    }
+
+    #[test]
+    fn parses_content_options() {
+        let query = parse_content_query("regex fo+ case sensitive only python hidden ignored");
+        assert_eq!(query.pattern, "fo+");
+        assert!(query.regex && query.case_sensitive && query.hidden && query.ignored);
+        assert_eq!(query.file_types, vec!["py"]);
+        assert!(build_regex(&parse_content_query("regex (unclosed")).is_err());
+        assert!(build_regex(&parse_content_query("a.b")).unwrap().is_match("A.B"));
+    }
+
+    #[test]
+    fn ranks_exact_prefix_contained_and_fuzzy_names() {
+        assert_eq!(rank("resume", "Resume.pdf", true), Some(0));
+        assert_eq!(rank("res", "resume.pdf", true), Some(1));
+        assert_eq!(rank("sum", "resume.pdf", true), Some(2));
+        assert_eq!(rank("*.PDF", "resume.pdf", true), Some(2));
+        assert_eq!(rank("*.txt", "resume.pdf", true), None);
+        assert_eq!(rank("rsume", "resume.pdf", true), Some(4));
+        assert_eq!(rank("rsume", "resume.pdf", false), None);
+        // Two letters are too few to guess from, and scattered letters are noise.
+        assert_eq!(rank("rm", "readme.md", true), None);
+        assert_eq!(rank("rmd", "readme.md", true), None);
+    }
+
+    #[test]
+    fn scores_fuzzy_gaps() {
+        assert_eq!(fuzzy_rank("abc", "abc"), Some(3));
+        assert_eq!(fuzzy_rank("ac", "abc"), Some(4));
+        assert_eq!(fuzzy_rank("a c", "a-b-c"), Some(4));
+        assert_eq!(fuzzy_rank("ca", "abc"), None);
+    }
+
+    #[test]
+    fn parses_name_queries() {
+        let query = parse_name_query("folder called Tax Returns depth 3 limit 5 exact");
+        assert_eq!(query.pattern, "Tax Returns");
+        assert!(query.folders_only && !query.fuzzy);
+        assert_eq!((query.max_depth, query.limit), (3, 5));
+    }
+}
@@ -0,0 +1,410 @@
+use chrono::Local;
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+use walkdir::WalkDir;
+
+use crate::{attach_data, format_bytes, paths, speak_to_speaker};
+
+const DEFAULT_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 8;
+const DEFAULT_TOP: usize = 5;
+const LISTED: usize = 10;
+/// How long a scan answers repeated questions about the same folder.
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    is_dir: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Report {
+    root: PathBuf,
+    depth: usize,
+    excluded: Vec<String>,
+    total: u64,
+    files: u64,
+    unreadable: u64,
+    /// Everything directly inside the root, largest first.
+    children: Vec<Entry>,
+    /// Directories down to `depth` levels, largest first.
+    directories: Vec<Entry>,
+    largest_files: Vec<Entry>,
+    scanned_at: i64,
+    seconds: f64,
+}
+
+#[derive(Default)]
+struct Tally {
+    directories: HashMap<PathBuf, u64>,
+    files: Vec<Entry>,
+    count: u64,
+    unreadable: u64,
+}
+
+impl Tally {
+    /// Keeps only the biggest files so a huge tree doesn't hold every path.
+    fn trim_files(&mut self) {
+        self.files.sort_by_key(|entry| Reverse(entry.size));
+        self.files.truncate(LISTED);
+    }
+
+    fn merge(&mut self, other: Tally) {
+        for (path, size) in other.directories {
+            *self.directories.entry(path).or_default() += size;
+        }
+        self.files.extend(other.files);
+        self.trim_files();
+        self.count += other.count;
+        self.unreadable += other.unreadable;
+    }
+}
+
+static CACHE: Mutex<Option<HashMap<String, (Instant, Report)>>> = Mutex::new(None);
+
+fn excluded(name: &str, excludes: &[String]) -> bool {
+    excludes.iter().any(|pattern| paths::glob_match(pattern, name))
+}
+
+/// Allocated size, so sparse files and small files on big blocks count as
+/// they do for `du`.
+#[cfg(unix)]
+fn allocated(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// The device a file lives on.
+#[cfg(unix)]
+fn device(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.dev()
+}
+
+/// Mount points can't be told apart here, so everything counts as one device.
+#[cfg(not(unix))]
+fn device(_metadata: &fs::Metadata) -> u64 {
+    0
+}
+
+/// Device and inode of a file with more than one hard link.
+#[cfg(unix)]
+fn hard_link(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn hard_link(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// What every worker of one scan shares.
+struct Scan<'a> {
+    root: &'a Path,
+    /// The root's device; folders mounted from elsewhere are left out, as `du -x` does.
+    device: u64,
+    depth: usize,
+    excludes: &'a [String],
+    /// Files with several hard links, so each is only counted once, as `du` does.
+    links: Mutex<HashSet<(u64, u64)>>,
+}
+
+impl Scan<'_> {
+    fn on_root_device(&self, metadata: &fs::Metadata) -> bool {
+        device(metadata) == self.device
+    }
+
+    fn first_link(&self, metadata: &fs::Metadata) -> bool {
+        hard_link(metadata).is_none_or(|link| self.links.lock().unwrap().insert(link))
+    }
+}
+
+fn walk(scan: &Scan, top: &Path, tally: &mut Tally) {
+    let entries = WalkDir::new(top).follow_links(false).into_iter().filter_entry(|entry| {
+        !excluded(&entry.file_name().to_string_lossy(), scan.excludes)
+            && (!entry.file_type().is_dir() || entry.metadata().is_ok_and(|m| scan.on_root_device(&m)))
+    });
+    for entry in entries {
+        let Ok(entry) = entry else {
+            tally.unreadable += 1;
+            continue;
+        };
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            tally.unreadable += 1;
+            continue;
+        };
+        if !scan.first_link(&metadata) {
+            continue;
+        }
+        let size = allocated(&metadata);
+        tally.count += 1;
+
+        let Ok(relative) = entry.path().strip_prefix(scan.root) else {
+            continue;
+        };
+        let parents: Vec<_> = relative.parent().map(|p| p.components().collect()).unwrap_or_default();
+        let mut directory = scan.root.to_path_buf();
+        for component in parents.iter().take(scan.depth) {
+            directory.push(component);
+            *tally.directories.entry(directory.clone()).or_default() += size;
+        }
+        tally.files.push(Entry { path: entry.into_path(), size, is_dir: false });
+        if tally.files.len() > LISTED * 20 {
+            tally.trim_files();
+        }
+    }
+}
+
+/// Walks the root's subdirectories on several threads, one subtree at a time.
+fn scan(root: &Path, depth: usize, excludes: &[String]) -> Result<Report, String> {
+    let started = Instant::now();
+    let unreadable = |e: std::io::Error| format!("Could not read {}: {}", root.display(), e);
+    let device = device(&fs::metadata(root).map_err(unreadable)?);
+    let entries = fs::read_dir(root).map_err(unreadable)?;
+    let scan = Scan { root, device, depth, excludes, links: Mutex::new(HashSet::new()) };
+    let mut tally = Tally::default();
+    let mut queue = Vec::new();
+    let mut children = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if excluded(&name, excludes) {
+            continue;
+        }
+        match (entry.file_type(), entry.metadata()) {
+            (_, Ok(metadata)) if !scan.on_root_device(&metadata) => {}
+            (Ok(kind), _) if kind.is_dir() => queue.push(entry.path()),
+            (Ok(_), Ok(metadata)) => {
+                if scan.first_link(&metadata) {
+                    let size = allocated(&metadata);
+                    children.push(Entry { path: entry.path(), size, is_dir: false });
+                    tally.files.push(Entry { path: entry.path(), size, is_dir: false });
+                    tally.count += 1;
+                }
+            }
+            _ => tally.unreadable += 1,
+        }
+    }
+
+    let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(queue.len()).max(1);
+    let queue = Mutex::new(queue);
+    let results: Vec<Tally> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut tally = Tally::default();
+                    while let Some(top) = queue.lock().unwrap().pop() {
+                        // The directory itself has no size of its own, but must still be listed.
+                        tally.directories.entry(top.clone()).or_default();
+                        walk(&scan, &top, &mut tally);
+                    }
+                    tally
+                })
+            })
+            .collect();
+        handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+    });
+    for result in results {
+        tally.merge(result);
+    }
+    tally.trim_files();
+
+    let mut directories: Vec<Entry> = tally
+        .directories
+        .iter()
+        .map(|(path, size)| Entry { path: path.clone(), size: *size, is_dir: true })
+        .collect();
+    directories.sort_by_key(|entry| Reverse(entry.size));
+
+    children.extend(directories.iter().filter(|d| d.path.parent() == Some(root)).cloned());
+    children.sort_by_key(|entry| Reverse(entry.size));
+    let total = children.iter().map(|c| c.size).sum();
+    directories.truncate(LISTED * 2);
+
+    Ok(Report {
+        root: root.to_path_buf(),
+        depth,
+        excluded: excludes.to_vec(),
+        total,
+        files: tally.count,
+        unreadable: tally.unreadable,
+        children,
+        directories,
+        largest_files: tally.files,
+        scanned_at: Local::now().timestamp(),
+        seconds: started.elapsed().as_secs_f64(),
+    })
+}
+
+fn cached_scan(root: &Path, depth: usize, excludes: &[String], refresh: bool) -> Result<(Report, bool), String> {
+    let key = format!("{}|{}|{}", root.display(), depth, excludes.join(","));
+    if !refresh {
+        let cache = CACHE.lock().unwrap();
+        if let Some((at, report)) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+            if at.elapsed() < CACHE_TTL {
+                return Ok((report.clone(), true));
+            }
+        }
+    }
+    let report = scan(root, depth, excludes)?;
+    CACHE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(key, (Instant::now(), report.clone()));
+    Ok((report, false))
+}
+
+struct Options {
+    depth: usize,
+    top: usize,
+    excludes: Vec<String>,
+    refresh: bool,
+}
+
+fn parse_options(rest: &str) -> Options {
+    let mut options = Options { depth: DEFAULT_DEPTH, top: DEFAULT_TOP, excludes: Vec::new(), refresh: false };
+    let words: Vec<String> = rest.split_whitespace().map(|w| w.trim_end_matches(['?', ',']).to_string()).collect();
+    let mut i = 0;
+    while i < words.len() {
+        let number = words.get(i + 1).and_then(|w| w.parse::<usize>().ok());
+        match words[i].to_lowercase().as_str() {
+            "depth" | "levels" if number.is_some() => {
+                options.depth = number.unwrap().clamp(1, MAX_DEPTH);
+                i += 1;
+            }
+            "top" if number.is_some() => {
+                options.top = number.unwrap().clamp(1, LISTED);
+                i += 1;
+            }
+            "excluding" | "except" | "without" | "ignoring" => {
+                while let Some(pattern) = words.get(i + 1) {
+                    if matches!(pattern.to_lowercase().as_str(), "depth" | "levels" | "top" | "refresh") {
+                        break;
+                    }
+                    if pattern != "and" {
+                        options.excludes.push(pattern.clone());
+                    }
+                    i += 1;
+                }
+            }
+            "refresh" | "rescan" | "again" => options.refresh = true,
+            _ => {}
+        }
+        i += 1;
+    }
+    options
+}
+
+fn name(entry: &Entry) -> String {
+    entry.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| entry.path.display().to_string())
+}
+
+fn line(entry: &Entry) -> String {
+    format!("{:>10}  {}{}", format_bytes(entry.size), entry.path.display(), if entry.is_dir { "/" } else { "" })
+}
+
+/// `space_usage [place] [depth N] [top N] [excluding pattern...] [refresh]`:
+/// the largest folders and files under a directory, the session's working
+/// directory by default.
+pub fn space_usage(arg: &str) -> String {
+    let (root, rest) = paths::place_or_cwd(arg);
+    let options = parse_options(&rest);
+    if !root.is_dir() {
+        return format!("Error: {} is not a folder.", root.display());
+    }
+
+    let (report, cached) = match cached_scan(&root, options.depth, &options.excludes, options.refresh) {
+        Ok(result) => result,
+        Err(e) => return format!("Error: {}", e),
+    };
+    let top: Vec<String> = report
+        .children
+        .iter()
+        .filter(|entry| entry.size > 0)
+        .take(options.top)
+        .map(|entry| format!("{} with {}", name(entry), format_bytes(entry.size)))
+        .collect();
+    let message = if top.is_empty() {
+        format!("{} is empty.", paths::speakable(&root))
+    } else {
+        format!(
+            "In {}, the biggest are {}, out of {} in total.",
+            paths::speakable(&root),
+            top.join(", "),
+            format_bytes(report.total)
+        )
+    };
+
+    let mut lines = vec![format!(
+        "{} in {} files under {}{}",
+        format_bytes(report.total),
+        report.files,
+        root.display(),
+        if cached { " (cached)" } else { "" }
+    )];
+    if report.unreadable > 0 {
+        lines.push(format!("{} entries could not be read and were skipped", report.unreadable));
+    }
+    lines.push(String::new());
+    lines.push("Largest folders:".to_string());
+    lines.extend(report.directories.iter().take(LISTED).map(line));
+    lines.push(String::new());
+    lines.push("Largest files:".to_string());
+    lines.extend(report.largest_files.iter().map(line));
+
+    attach_data(json!({ "cached": cached, "report": report }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_hard_links_once() {
+        let root = std::env::temp_dir().join(format!("aurora-space-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/b/data"), vec![1u8; 64 * 1024]).unwrap();
+        fs::hard_link(root.join("a/b/data"), root.join("a/copy")).unwrap();
+        fs::hard_link(root.join("a/b/data"), root.join("top")).unwrap();
+        fs::write(root.join("skip.log"), vec![1u8; 64 * 1024]).unwrap();
+
+        let report = scan(&root, 2, &["*.log".to_string()]).unwrap();
+        let _ = fs::remove_dir_all(&root);
+        assert_eq!(report.files, 1);
+        assert_eq!(report.total, report.largest_files[0].size);
+        assert!(report.total >= 64 * 1024);
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = parse_options("depth 3 top 4 excluding node_modules and *.iso refresh");
+        assert_eq!((options.depth, options.top, options.refresh), (3, 4, true));
+        assert_eq!(options.excludes, vec!["node_modules", "*.iso"]);
+        assert_eq!(parse_options("depth 99").depth, MAX_DEPTH);
+    }
+}
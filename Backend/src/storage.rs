@@ -0,0 +1,326 @@
+use serde::Serialize;
+use serde_json::json;
+use std::{collections::HashMap, fs, path::Path};
+#[cfg(target_os = "linux")]
+use std::{ffi::CString, mem, os::unix::ffi::OsStrExt};
+
+use crate::{attach_data, format_bytes, locale, speak_to_speaker};
+
+/// Filesystems that don't store files on a device. Hidden unless "all" is asked for.
+const PSEUDO_FILESYSTEMS: [&str; 27] = [
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts", "devtmpfs",
+    "efivarfs", "fusectl", "fuse.gvfsd-fuse", "fuse.portal", "hugetlbfs", "mqueue", "nsfs", "overlay",
+    "proc", "pstore", "ramfs", "rpc_pipefs", "securityfs", "selinuxfs", "squashfs", "sysfs", "tmpfs",
+    "tracefs",
+];
+const SECTOR_SIZE: u64 = 512;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Filesystem {
+    pub device: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub read_only: bool,
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub pseudo: bool,
+}
+
+impl Filesystem {
+    pub fn used_percent(&self) -> f64 {
+        // Like df, the reserved blocks count as neither used nor available.
+        let usable = self.used + self.available;
+        if usable == 0 {
+            0.0
+        } else {
+            self.used as f64 * 100.0 / usable as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BlockDevice {
+    name: String,
+    kind: &'static str,
+    size: u64,
+    model: Option<String>,
+    removable: bool,
+    read_only: bool,
+    mount_points: Vec<String>,
+    partitions: Vec<BlockDevice>,
+}
+
+/// Undoes the octal escapes `/proc/mounts` uses for spaces and other separators.
+#[cfg(target_os = "linux")]
+fn unescape(field: &str) -> String {
+    field.replace("\\040", " ").replace("\\011", "\t").replace("\\012", "\n").replace("\\134", "\\")
+}
+
+#[cfg(target_os = "linux")]
+fn statvfs(path: &Path) -> Option<(u64, u64, u64)> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stats: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    let block = stats.f_frsize as u64;
+    let total = stats.f_blocks as u64 * block;
+    let free = stats.f_bfree as u64 * block;
+    Some((total, total.saturating_sub(free), stats.f_bavail as u64 * block))
+}
+
+/// Mounted filesystems from `/proc/mounts`, sized with `statvfs`. When a mount
+/// point appears more than once, the last (topmost) mount wins.
+#[cfg(target_os = "linux")]
+pub fn filesystems(include_pseudo: bool) -> Vec<Filesystem> {
+    let Ok(text) = fs::read_to_string("/proc/mounts") else {
+        return Vec::new();
+    };
+    let mut found: Vec<Filesystem> = Vec::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [device, mount_point, file_system, options, ..] = fields.as_slice() else {
+            continue;
+        };
+        let pseudo = PSEUDO_FILESYSTEMS.contains(file_system) || !device.starts_with('/');
+        if pseudo && !include_pseudo {
+            continue;
+        }
+        let mount_point = unescape(mount_point);
+        let Some((total, used, available)) = statvfs(Path::new(&mount_point)) else {
+            continue;
+        };
+        found.retain(|existing| existing.mount_point != mount_point);
+        found.push(Filesystem {
+            device: unescape(device),
+            mount_point,
+            file_system: file_system.to_string(),
+            read_only: options.split(',').any(|option| option == "ro"),
+            total,
+            used,
+            available,
+            pseudo,
+        });
+    }
+    found
+}
+
+/// Without `/proc/mounts`, the mounts sysinfo knows about. Their used space
+/// can't be told apart from reserved blocks, and mount options are unknown.
+#[cfg(not(target_os = "linux"))]
+pub fn filesystems(include_pseudo: bool) -> Vec<Filesystem> {
+    use sysinfo::{DiskExt, System, SystemExt};
+
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+    sys.disks()
+        .iter()
+        .map(|disk| {
+            let file_system = String::from_utf8_lossy(disk.file_system()).into_owned();
+            let device = disk.name().to_string_lossy().into_owned();
+            Filesystem {
+                pseudo: PSEUDO_FILESYSTEMS.contains(&file_system.as_str()),
+                mount_point: disk.mount_point().display().to_string(),
+                read_only: false,
+                total: disk.total_space(),
+                used: disk.total_space().saturating_sub(disk.available_space()),
+                available: disk.available_space(),
+                device,
+                file_system,
+            }
+        })
+        .filter(|filesystem| include_pseudo || !filesystem.pseudo)
+        .collect()
+}
+
+fn sys_block(path: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(path.join(file))
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn block_device(path: &Path, kind: &'static str, mounts: &HashMap<String, Vec<String>>) -> BlockDevice {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let flag = |file: &str| sys_block(path, file).as_deref() == Some("1");
+    BlockDevice {
+        size: sys_block(path, "size").and_then(|s| s.parse::<u64>().ok()).unwrap_or(0) * SECTOR_SIZE,
+        model: sys_block(path, "device/model"),
+        removable: flag("removable"),
+        read_only: flag("ro"),
+        mount_points: mounts.get(&format!("/dev/{}", name)).cloned().unwrap_or_default(),
+        partitions: Vec::new(),
+        kind,
+        name,
+    }
+}
+
+/// Disks and their partitions from `/sys/block`. Loop, RAM and empty devices
+/// are left out unless `include_virtual` is set.
+fn block_devices(include_virtual: bool) -> Vec<BlockDevice> {
+    let Ok(entries) = fs::read_dir("/sys/block") else {
+        return Vec::new();
+    };
+    // Mounts may name a device by a link such as /dev/mapper/vg-root or
+    // /dev/disk/by-uuid/…; resolve it to the /dev/dm-0 or /dev/sda1 it points at.
+    let mut mounts: HashMap<String, Vec<String>> = HashMap::new();
+    for filesystem in filesystems(true) {
+        let device = fs::canonicalize(&filesystem.device)
+            .map(|path| path.display().to_string())
+            .unwrap_or(filesystem.device);
+        mounts.entry(device).or_default().push(filesystem.mount_point);
+    }
+
+    let mut devices: Vec<BlockDevice> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let kind = match name.as_str() {
+                n if n.starts_with("loop") => "loop",
+                n if n.starts_with("zram") || n.starts_with("ram") => "ram",
+                n if n.starts_with("sr") => "rom",
+                _ => "disk",
+            };
+            let mut device = block_device(&entry.path(), kind, &mounts);
+            if !include_virtual && (kind == "loop" || kind == "ram" || device.size == 0) {
+                return None;
+            }
+            let mut partitions: Vec<BlockDevice> = fs::read_dir(entry.path())
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|child| child.path().join("partition").exists())
+                .map(|child| block_device(&child.path(), "partition", &mounts))
+                .collect();
+            partitions.sort_by(|a, b| a.name.cmp(&b.name));
+            device.partitions = partitions;
+            Some(device)
+        })
+        .collect();
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    devices
+}
+
+fn wants_all(arg: &str) -> bool {
+    arg.split_whitespace().any(|word| matches!(word.to_lowercase().as_str(), "all" | "everything" | "pseudo"))
+}
+
+fn percent(value: f64) -> String {
+    format!("{}%", locale::format_decimal(value, 0))
+}
+
+/// `list_drives [all]`: mounted filesystems with their devices and types.
+pub fn list_drives(arg: &str) -> String {
+    let filesystems = filesystems(wants_all(arg));
+    if filesystems.is_empty() {
+        return "Error: Could not read the mounted filesystems from /proc/mounts.".to_string();
+    }
+
+    let lines: Vec<String> = filesystems
+        .iter()
+        .map(|fs| {
+            format!(
+                "{:<24} {:<30} {:<10}{}",
+                fs.device,
+                fs.mount_point,
+                fs.file_system,
+                if fs.read_only { " read-only" } else { "" }
+            )
+        })
+        .collect();
+    let message = match filesystems.len() {
+        1 => "You have 1 drive mounted.".to_string(),
+        count => format!("You have {} drives mounted.", count),
+    };
+    attach_data(json!({ "filesystems": filesystems }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+/// `df_command [all]`: size, used and free space of each mounted filesystem.
+pub fn df_command(arg: &str) -> String {
+    let filesystems = filesystems(wants_all(arg));
+    if filesystems.is_empty() {
+        return "Error: Could not read the mounted filesystems from /proc/mounts.".to_string();
+    }
+
+    let mut lines = vec![format!("{:<24} {:>10} {:>10} {:>10} {:>5}  {}", "Filesystem", "Size", "Used", "Free", "Use%", "Mounted on")];
+    lines.extend(filesystems.iter().map(|fs| {
+        format!(
+            "{:<24} {:>10} {:>10} {:>10} {:>5}  {}",
+            fs.device,
+            format_bytes(fs.total),
+            format_bytes(fs.used),
+            format_bytes(fs.available),
+            percent(fs.used_percent()),
+            fs.mount_point
+        )
+    }));
+
+    let fullest = filesystems.iter().max_by(|a, b| a.used_percent().total_cmp(&b.used_percent())).unwrap();
+    let message = match filesystems.iter().find(|fs| fs.mount_point == "/") {
+        Some(root) if root.mount_point == fullest.mount_point => format!(
+            "The root drive has {} free of {}, {} used.",
+            format_bytes(root.available),
+            format_bytes(root.total),
+            percent(root.used_percent())
+        ),
+        Some(root) => format!(
+            "The root drive has {} free of {}. The fullest drive is {} at {}.",
+            format_bytes(root.available),
+            format_bytes(root.total),
+            fullest.mount_point,
+            percent(fullest.used_percent())
+        ),
+        None => format!("The fullest drive is {} at {}.", fullest.mount_point, percent(fullest.used_percent())),
+    };
+    attach_data(json!({ "filesystems": filesystems }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+fn describe_device(device: &BlockDevice, indent: &str) -> String {
+    let mut details = vec![device.kind.to_string(), format_bytes(device.size)];
+    if let Some(model) = &device.model {
+        details.push(model.clone());
+    }
+    if device.removable {
+        details.push("removable".to_string());
+    }
+    if device.read_only {
+        details.push("read-only".to_string());
+    }
+    if !device.mount_points.is_empty() {
+        details.push(format!("mounted at {}", device.mount_points.join(", ")));
+    }
+    format!("{}{}: {}", indent, device.name, details.join(", "))
+}
+
+/// `list_disks [all]`: block devices and their partitions.
+pub fn list_disks(arg: &str) -> String {
+    let devices = block_devices(wants_all(arg));
+    if devices.is_empty() {
+        return "Error: Could not find any disks in /sys/block.".to_string();
+    }
+
+    let mut lines = Vec::new();
+    for device in &devices {
+        lines.push(describe_device(device, ""));
+        lines.extend(device.partitions.iter().map(|partition| describe_device(partition, "  ")));
+    }
+    let sizes: Vec<String> = devices.iter().map(|d| format!("{} of {}", d.name, format_bytes(d.size))).collect();
+    let message = match devices.len() {
+        1 => format!("You have 1 disk: {}.", sizes[0]),
+        count => format!("You have {} disks: {}.", count, sizes.join(", ")),
+    };
+    attach_data(json!({ "devices": devices }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
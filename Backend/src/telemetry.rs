@@ -0,0 +1,164 @@
+use axum::{http::header, response::IntoResponse};
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+use crate::metrics;
+
+// Upper bounds of the command latency histogram, in seconds. Most commands
+// speak their reply, so latencies are dominated by speech.
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Debug, Default)]
+struct CommandStats {
+    requests: u64,
+    failures: u64,
+    seconds: f64,
+    buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    commands: BTreeMap<String, CommandStats>,
+    unknown_utterances: u64,
+    speech_errors: u64,
+}
+
+static COUNTERS: Mutex<Option<Counters>> = Mutex::new(None);
+
+fn with_counters(f: impl FnOnce(&mut Counters)) {
+    f(COUNTERS.lock().unwrap().get_or_insert_with(Counters::default));
+}
+
+/// Counts a handled request. `command` must be a registered command name
+/// (or a fixed label), never the raw utterance.
+pub fn record_command(command: &str, elapsed: Duration, failed: bool) {
+    with_counters(|counters| {
+        let stats = counters.commands.entry(command.to_string()).or_default();
+        let seconds = elapsed.as_secs_f64();
+        stats.requests += 1;
+        stats.seconds += seconds;
+        if failed {
+            stats.failures += 1;
+        }
+        for (bucket, bound) in stats.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+    });
+}
+
+pub fn record_unknown_utterance() {
+    with_counters(|counters| counters.unknown_utterances += 1);
+}
+
+pub fn record_speech_error() {
+    with_counters(|counters| counters.speech_errors += 1);
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn render_service(out: &mut String) {
+    let counters = COUNTERS.lock().unwrap();
+    let empty = Counters::default();
+    let counters = counters.as_ref().unwrap_or(&empty);
+
+    family(out, "aurora_command_requests_total", "counter", "Commands handled, by command.");
+    for (command, stats) in &counters.commands {
+        let _ = writeln!(out, "aurora_command_requests_total{{command=\"{}\"}} {}", escape(command), stats.requests);
+    }
+    family(out, "aurora_command_failures_total", "counter", "Commands whose reply was an error, by command.");
+    for (command, stats) in &counters.commands {
+        let _ = writeln!(out, "aurora_command_failures_total{{command=\"{}\"}} {}", escape(command), stats.failures);
+    }
+    family(out, "aurora_command_duration_seconds", "histogram", "Time taken to handle a command, including speech.");
+    for (command, stats) in &counters.commands {
+        let command = escape(command);
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+            let _ = writeln!(out, "aurora_command_duration_seconds_bucket{{command=\"{}\",le=\"{}\"}} {}", command, bound, count);
+        }
+        let _ = writeln!(out, "aurora_command_duration_seconds_bucket{{command=\"{}\",le=\"+Inf\"}} {}", command, stats.requests);
+        let _ = writeln!(out, "aurora_command_duration_seconds_sum{{command=\"{}\"}} {}", command, stats.seconds);
+        let _ = writeln!(out, "aurora_command_duration_seconds_count{{command=\"{}\"}} {}", command, stats.requests);
+    }
+    family(out, "aurora_unknown_utterances_total", "counter", "Requests that matched no command, macro or phrase.");
+    let _ = writeln!(out, "aurora_unknown_utterances_total {}", counters.unknown_utterances);
+    family(out, "aurora_speech_errors_total", "counter", "Failures to run the speech synthesizer.");
+    let _ = writeln!(out, "aurora_speech_errors_total {}", counters.speech_errors);
+}
+
+fn render_host(out: &mut String) {
+    let snapshot = metrics::snapshot();
+
+    family(out, "aurora_host_cpu_usage_percent", "gauge", "CPU usage over the collector's sampling window.");
+    let _ = writeln!(out, "aurora_host_cpu_usage_percent {}", snapshot.cpu_usage);
+    family(out, "aurora_host_core_usage_percent", "gauge", "Usage of each logical core.");
+    for cpu in &snapshot.cpus {
+        let _ = writeln!(out, "aurora_host_core_usage_percent{{cpu=\"{}\"}} {}", escape(&cpu.name), cpu.usage);
+    }
+    family(out, "aurora_host_load_average", "gauge", "System load average.");
+    for (period, value) in ["1m", "5m", "15m"].iter().zip(snapshot.load_average) {
+        let _ = writeln!(out, "aurora_host_load_average{{period=\"{}\"}} {}", period, value);
+    }
+    for (name, help, value) in [
+        ("aurora_host_memory_total_bytes", "Total memory.", snapshot.memory_total),
+        ("aurora_host_memory_used_bytes", "Used memory.", snapshot.memory_used),
+        ("aurora_host_memory_free_bytes", "Free memory.", snapshot.memory_free),
+        ("aurora_host_memory_available_bytes", "Memory available for new programs.", snapshot.memory_available),
+        ("aurora_host_swap_total_bytes", "Total swap.", snapshot.swap_total),
+        ("aurora_host_swap_used_bytes", "Used swap.", snapshot.swap_used),
+        ("aurora_host_uptime_seconds", "Time since the host booted.", snapshot.uptime),
+    ] {
+        family(out, name, "gauge", help);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+    family(out, "aurora_host_disk_total_bytes", "gauge", "Disk capacity, by mount point.");
+    for disk in &snapshot.disks {
+        let _ = writeln!(
+            out,
+            "aurora_host_disk_total_bytes{{mount=\"{}\",device=\"{}\",fs=\"{}\"}} {}",
+            escape(&disk.mount_point),
+            escape(&disk.name),
+            escape(&disk.file_system),
+            disk.total
+        );
+    }
+    family(out, "aurora_host_disk_available_bytes", "gauge", "Disk space available, by mount point.");
+    for disk in &snapshot.disks {
+        let _ = writeln!(
+            out,
+            "aurora_host_disk_available_bytes{{mount=\"{}\",device=\"{}\",fs=\"{}\"}} {}",
+            escape(&disk.mount_point),
+            escape(&disk.name),
+            escape(&disk.file_system),
+            disk.available
+        );
+    }
+    family(out, "aurora_host_network_receive_bytes_per_second", "gauge", "Receive rate, by interface.");
+    for network in &snapshot.networks {
+        let _ = writeln!(out, "aurora_host_network_receive_bytes_per_second{{interface=\"{}\"}} {}", escape(&network.name), network.receive_rate);
+    }
+    family(out, "aurora_host_network_transmit_bytes_per_second", "gauge", "Transmit rate, by interface.");
+    for network in &snapshot.networks {
+        let _ = writeln!(out, "aurora_host_network_transmit_bytes_per_second{{interface=\"{}\"}} {}", escape(&network.name), network.transmit_rate);
+    }
+}
+
+/// `GET /metrics`: host and service metrics in the Prometheus text format.
+pub async fn serve_metrics() -> impl IntoResponse {
+    let body = tokio::task::spawn_blocking(|| {
+        let mut out = String::new();
+        render_service(&mut out);
+        render_host(&mut out);
+        out
+    })
+    .await
+    .unwrap_or_default();
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
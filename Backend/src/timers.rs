@@ -0,0 +1,279 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fs, sync::Mutex};
+
+use crate::{
+    data_path, events,
+    scheduler::{next_at, parse_duration, parse_number},
+    speak_to_speaker,
+};
+
+const TIMERS_FILE: &str = "timers.json";
+const TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+enum TimerKind {
+    Timer,
+    Alarm,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Timer {
+    id: u64,
+    kind: TimerKind,
+    label: String,
+    due: i64,
+    duration: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TimerStore {
+    next_id: u64,
+    timers: Vec<Timer>,
+}
+
+// Serializes read-modify-write cycles on the timer file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn load_timers() -> TimerStore {
+    fs::read_to_string(data_path(TIMERS_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_timers(store: &TimerStore) -> std::io::Result<()> {
+    fs::write(data_path(TIMERS_FILE), serde_json::to_string_pretty(store)?)
+}
+
+fn format_duration(seconds: i64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let mut parts = Vec::new();
+    for (value, unit) in [(hours, "hour"), (minutes, "minute"), (seconds, "second")] {
+        if value > 0 {
+            parts.push(format!("{} {}{}", value, unit, if value == 1 { "" } else { "s" }));
+        }
+    }
+    if parts.is_empty() {
+        "0 seconds".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn local_time(timestamp: i64) -> DateTime<Local> {
+    Local.timestamp_opt(timestamp, 0).single().unwrap_or_else(Local::now)
+}
+
+/// Splits "for 10 minutes for pasta" into the time words and an optional label.
+fn split_label<'a>(words: &'a [&'a str]) -> (&'a [&'a str], String) {
+    let words = match words {
+        ["for", rest @ ..] | ["at", rest @ ..] => rest,
+        _ => words,
+    };
+    let label_start = words
+        .iter()
+        .position(|w| matches!(*w, "for" | "called" | "named" | "to"))
+        .unwrap_or(words.len());
+    let label = words.get(label_start + 1..).unwrap_or_default().join(" ");
+    (&words[..label_start], label)
+}
+
+fn add_timer(kind: TimerKind, label: String, due: DateTime<Local>, duration: i64) -> std::io::Result<u64> {
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_timers();
+    store.next_id += 1;
+    let id = store.next_id;
+    store.timers.push(Timer { id, kind, label, due: due.timestamp(), duration });
+    save_timers(&store)?;
+    Ok(id)
+}
+
+fn announce_created(message: String, id: u64) -> String {
+    events::publish("timer_set", &message, json!({ "id": id }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+pub fn set_timer(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let (time_words, label) = split_label(&words);
+    let Some(seconds) = parse_duration(time_words) else {
+        return "Error: Say how long, e.g. 'set timer for 10 minutes'.".to_string();
+    };
+
+    let due = Local::now() + chrono::Duration::seconds(seconds);
+    match add_timer(TimerKind::Timer, label.clone(), due, seconds) {
+        Ok(id) => {
+            let what = if label.is_empty() { String::new() } else { format!(" for {}", label) };
+            announce_created(format!("Timer {} set for {}{}.", id, format_duration(seconds), what), id)
+        }
+        Err(e) => format!("Failed to save timer: {}", e),
+    }
+}
+
+pub fn set_alarm(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let (time_words, label) = split_label(&words);
+    let Some(due) = next_at(time_words, Local::now()) else {
+        return "Error: Say when, e.g. 'set alarm for 7:30 am'.".to_string();
+    };
+
+    match add_timer(TimerKind::Alarm, label, due, 0) {
+        Ok(id) => announce_created(format!("Alarm {} set for {}.", id, due.format("%A %I:%M %p")), id),
+        Err(e) => format!("Failed to save alarm: {}", e),
+    }
+}
+
+pub fn list_timers(_arg: &str) -> String {
+    let store = {
+        let _guard = LOCK.lock().unwrap();
+        load_timers()
+    };
+    if store.timers.is_empty() {
+        if let Err(e) = speak_to_speaker("There are no timers or alarms.") {
+            return format!("Error: {}", e);
+        }
+        return "There are no timers or alarms.".to_string();
+    }
+
+    let now = Local::now().timestamp();
+    let lines: Vec<String> = store
+        .timers
+        .iter()
+        .map(|timer| {
+            let label = if timer.label.is_empty() { String::new() } else { format!(" '{}'", timer.label) };
+            match timer.kind {
+                TimerKind::Timer => format!(
+                    "{}. Timer{}: {} left",
+                    timer.id,
+                    label,
+                    format_duration((timer.due - now).max(0))
+                ),
+                TimerKind::Alarm => format!(
+                    "{}. Alarm{}: {}",
+                    timer.id,
+                    label,
+                    local_time(timer.due).format("%a %d %b %I:%M %p")
+                ),
+            }
+        })
+        .collect();
+
+    if let Err(e) = speak_to_speaker(&format!("You have {} timers and alarms.", lines.len())) {
+        return format!("Error: {}", e);
+    }
+    lines.join("\n")
+}
+
+/// `cancel_timer` cancels the only timer, `cancel_timer N` a specific one and
+/// `cancel_timer all` every timer and alarm.
+pub fn cancel_timer(arg: &str) -> String {
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_timers();
+    let arg = arg.trim().to_lowercase();
+    let before = store.timers.len();
+
+    if arg == "all" || arg == "all timers" {
+        store.timers.clear();
+    } else if let Some(id) = arg.split_whitespace().last().and_then(parse_number) {
+        store.timers.retain(|timer| timer.id != id as u64);
+    } else if before == 1 {
+        store.timers.clear();
+    } else if before == 0 {
+        return "Error: There are no timers to cancel.".to_string();
+    } else {
+        return format!("Error: There are {} timers; say which number to cancel.", before);
+    }
+
+    let cancelled = before - store.timers.len();
+    if cancelled == 0 {
+        return format!("Error: No timer {}.", arg);
+    }
+    if let Err(e) = save_timers(&store) {
+        return format!("Failed to save timers: {}", e);
+    }
+
+    let message = format!("Cancelled {} timer{}.", cancelled, if cancelled == 1 { "" } else { "s" });
+    events::publish("timer_cancelled", &message, json!({ "count": cancelled }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+fn take_expired(now: i64) -> Vec<Timer> {
+    let _guard = LOCK.lock().unwrap();
+    let mut store = load_timers();
+    let (expired, pending): (Vec<Timer>, Vec<Timer>) = store.timers.into_iter().partition(|t| t.due <= now);
+    if expired.is_empty() {
+        return expired;
+    }
+    store.timers = pending;
+    if let Err(e) = save_timers(&store) {
+        eprintln!("Failed to save timers: {}", e);
+    }
+    expired
+}
+
+fn announce_expired(timer: &Timer, now: i64) {
+    let label = if timer.label.is_empty() { String::new() } else { format!(" for {}", timer.label) };
+    let mut message = match timer.kind {
+        TimerKind::Timer => format!("Timer{} is done. {} have passed.", label, format_duration(timer.duration)),
+        TimerKind::Alarm => format!("Alarm{}: it is {}.", label, local_time(timer.due).format("%I:%M %p")),
+    };
+    // Timers that ran out while the backend was down are announced on startup.
+    if now - timer.due > 60 {
+        message.push_str(&format!(" It went off at {} while I was offline.", local_time(timer.due).format("%H:%M")));
+    }
+
+    events::publish("timer_expired", &message, json!({ "id": timer.id, "label": timer.label }));
+    if let Err(e) = speak_to_speaker(&message) {
+        eprintln!("Error: {}", e);
+    }
+}
+
+/// Spawns the background loop that announces expired timers and alarms.
+pub fn start() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            let now = Local::now().timestamp();
+            for timer in take_expired(now) {
+                tokio::task::spawn_blocking(move || announce_expired(&timer, now));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(text: &str) -> (Option<i64>, String) {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let (time_words, label) = split_label(&words);
+        (parse_duration(time_words), label)
+    }
+
+    #[test]
+    fn splits_durations_from_labels() {
+        assert_eq!(split("for 10 minutes for pasta"), (Some(600), "pasta".to_string()));
+        assert_eq!(split("1 hour and 30 minutes called laundry"), (Some(5400), "laundry".to_string()));
+        assert_eq!(split("for ninety seconds"), (Some(90), String::new()));
+        assert_eq!(split("for pasta").0, None);
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(0), "0 seconds");
+        assert_eq!(format_duration(61), "1 minute 1 second");
+        assert_eq!(format_duration(2 * 3600 + 120), "2 hours 2 minutes");
+    }
+}
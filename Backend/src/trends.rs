@@ -0,0 +1,606 @@
+use chrono::{Duration, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    sync::Mutex,
+};
+
+use crate::{attach_data, data_path, locale, metrics::Snapshot, scheduler::parse_duration, speak_to_speaker};
+
+const HISTORY_FILE: &str = "metrics_history.json";
+// Full-resolution samples are kept in memory for the last hour, per-minute
+// buckets for a day, and hourly buckets for the configured retention.
+const RAW_RETENTION: i64 = 3600;
+const MINUTE_RETENTION: i64 = 24 * 3600;
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+// Minute buckets are written out every this many minutes.
+const SAVE_EVERY_MINUTES: i64 = 10;
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Stat {
+    min: f64,
+    max: f64,
+    max_at: i64,
+    total: f64,
+}
+
+impl Stat {
+    fn new(value: f64, time: i64) -> Self {
+        Stat { min: value, max: value, max_at: time, total: value }
+    }
+
+    fn merge(&mut self, other: &Stat) {
+        self.min = self.min.min(other.min);
+        if other.max > self.max {
+            self.max = other.max;
+            self.max_at = other.max_at;
+        }
+        self.total += other.total;
+    }
+}
+
+/// Aggregated readings for a time span starting at `start`. A raw sample is
+/// a bucket with a single sample.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bucket {
+    start: i64,
+    samples: u32,
+    cpu: Stat,
+    memory: Stat,
+    swap: Stat,
+    // Used bytes per mount point.
+    disks: BTreeMap<String, Stat>,
+    // Capacity per mount point, so percentages can be worked out later.
+    disk_totals: BTreeMap<String, u64>,
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 * 100.0 / total as f64
+    }
+}
+
+impl Bucket {
+    fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let time = snapshot.time;
+        Bucket {
+            start: time,
+            samples: 1,
+            cpu: Stat::new(snapshot.cpu_usage as f64, time),
+            memory: Stat::new(percent(snapshot.memory_used, snapshot.memory_total), time),
+            swap: Stat::new(percent(snapshot.swap_used, snapshot.swap_total), time),
+            disks: snapshot
+                .disks
+                .iter()
+                .map(|disk| (disk.mount_point.clone(), Stat::new(disk.total.saturating_sub(disk.available) as f64, time)))
+                .collect(),
+            disk_totals: snapshot.disks.iter().map(|disk| (disk.mount_point.clone(), disk.total)).collect(),
+        }
+    }
+
+    fn merge(&mut self, other: &Bucket) {
+        self.samples += other.samples;
+        self.cpu.merge(&other.cpu);
+        self.memory.merge(&other.memory);
+        self.swap.merge(&other.swap);
+        for (mount, stat) in &other.disks {
+            self.disks.entry(mount.clone()).and_modify(|s| s.merge(stat)).or_insert(*stat);
+        }
+        self.disk_totals.extend(other.disk_totals.iter().map(|(m, t)| (m.clone(), *t)));
+    }
+
+    fn stat(&self, metric: &Metric) -> Option<Stat> {
+        match metric {
+            Metric::Cpu => Some(self.cpu),
+            Metric::Memory => Some(self.memory),
+            Metric::Swap => Some(self.swap),
+            Metric::Disk(mount) => {
+                let used = self.disks.get(mount)?;
+                let total = *self.disk_totals.get(mount)? as f64;
+                let scale = if total > 0.0 { 100.0 / total } else { 0.0 };
+                Some(Stat {
+                    min: used.min * scale,
+                    max: used.max * scale,
+                    max_at: used.max_at,
+                    total: used.total * scale,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct History {
+    retention_days: Option<i64>,
+    minutes: VecDeque<Bucket>,
+    hours: VecDeque<Bucket>,
+    #[serde(skip)]
+    raw: VecDeque<Bucket>,
+    #[serde(skip)]
+    open_minute: Option<Bucket>,
+    #[serde(skip)]
+    open_hour: Option<Bucket>,
+}
+
+impl History {
+    fn retention(&self) -> i64 {
+        self.retention_days.unwrap_or(DEFAULT_RETENTION_DAYS) * 86400
+    }
+
+    /// Adds a sample and rolls finished minutes and hours into their tiers.
+    /// Returns true when the stored tiers should be written out.
+    fn record(&mut self, sample: Bucket) -> bool {
+        let now = sample.start;
+        let mut save = false;
+
+        let minute = now - now.rem_euclid(60);
+        if self.open_minute.as_ref().is_some_and(|open| open.start != minute) {
+            let closed = self.open_minute.take().unwrap();
+            let hour = closed.start - closed.start.rem_euclid(3600);
+            if self.open_hour.as_ref().is_some_and(|open| open.start != hour) {
+                self.hours.push_back(self.open_hour.take().unwrap());
+                save = true;
+            }
+            match &mut self.open_hour {
+                Some(open) => open.merge(&closed),
+                None => self.open_hour = Some(Bucket { start: hour, ..closed.clone() }),
+            }
+            save |= (closed.start / 60) % SAVE_EVERY_MINUTES == 0;
+            self.minutes.push_back(closed);
+        }
+        match &mut self.open_minute {
+            Some(open) => open.merge(&sample),
+            None => self.open_minute = Some(Bucket { start: minute, ..sample.clone() }),
+        }
+        self.raw.push_back(sample);
+
+        let retention = self.retention();
+        prune(&mut self.raw, now - RAW_RETENTION);
+        prune(&mut self.minutes, now - MINUTE_RETENTION);
+        prune(&mut self.hours, now - retention);
+        save
+    }
+
+    /// Buckets covering `since..`, taken from the finest tier that reaches
+    /// back that far, plus the buckets still being filled.
+    fn since(&self, since: i64) -> Vec<Bucket> {
+        let now = Local::now().timestamp();
+        if since >= now - RAW_RETENTION && self.raw.front().is_some_and(|b| b.start <= since + 60) {
+            return self.raw.iter().filter(|b| b.start >= since).cloned().collect();
+        }
+        let mut buckets: Vec<Bucket> = if since >= now - MINUTE_RETENTION {
+            self.minutes.iter().filter(|b| b.start >= since).cloned().collect()
+        } else {
+            let mut buckets: Vec<Bucket> = self.hours.iter().filter(|b| b.start >= since).cloned().collect();
+            buckets.extend(self.open_hour.iter().cloned());
+            buckets
+        };
+        buckets.extend(self.open_minute.iter().cloned());
+        buckets
+    }
+}
+
+fn prune(buckets: &mut VecDeque<Bucket>, before: i64) {
+    while buckets.front().is_some_and(|b| b.start < before) {
+        buckets.pop_front();
+    }
+}
+
+static HISTORY: Mutex<Option<History>> = Mutex::new(None);
+
+fn load_history() -> History {
+    fs::read_to_string(data_path(HISTORY_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_history(history: &History) -> std::io::Result<()> {
+    fs::write(data_path(HISTORY_FILE), serde_json::to_string(history)?)
+}
+
+fn with_history<T>(f: impl FnOnce(&mut History) -> T) -> T {
+    let mut history = HISTORY.lock().unwrap();
+    f(history.get_or_insert_with(load_history))
+}
+
+/// Feeds a collector reading into the history.
+pub fn record(snapshot: &Snapshot) {
+    with_history(|history| {
+        if history.record(Bucket::from_snapshot(snapshot)) {
+            if let Err(e) = save_history(history) {
+                eprintln!("Failed to save metrics history: {}", e);
+            }
+        }
+    });
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Metric {
+    Cpu,
+    Memory,
+    Swap,
+    Disk(String),
+}
+
+impl Metric {
+    fn label(&self) -> String {
+        match self {
+            Metric::Cpu => "CPU usage".to_string(),
+            Metric::Memory => "Memory usage".to_string(),
+            Metric::Swap => "Swap usage".to_string(),
+            Metric::Disk(mount) => format!("Disk usage on {}", mount),
+        }
+    }
+}
+
+/// Finds the metric in a request and returns it with the remaining words.
+fn parse_metric(words: &[&str]) -> Option<(Metric, Vec<String>)> {
+    let position = words.iter().position(|w| {
+        matches!(*w, "cpu" | "processor" | "memory" | "ram" | "swap" | "disk" | "disks" | "storage")
+    })?;
+    let mut tail = &words[position + 1..];
+    let metric = match words[position] {
+        "cpu" | "processor" => Metric::Cpu,
+        "memory" | "ram" => Metric::Memory,
+        "swap" => Metric::Swap,
+        _ => {
+            // "disk /home" or "disk on /home"; the root disk otherwise.
+            let skip = usize::from(tail.first() == Some(&"on"));
+            match tail.get(skip) {
+                Some(mount) if mount.starts_with('/') => {
+                    let metric = Metric::Disk(mount.to_string());
+                    tail = &tail[skip + 1..];
+                    metric
+                }
+                _ => Metric::Disk("/".to_string()),
+            }
+        }
+    };
+    let rest = words[..position].iter().chain(tail).map(|w| w.to_string()).collect();
+    Some((metric, rest))
+}
+
+/// Works out the start of the period in a request: "over the last hour",
+/// "in the past 30 minutes", "today", "yesterday". Defaults to the last hour.
+fn parse_period(words: &[String]) -> Option<(i64, i64, String)> {
+    let now = Local::now();
+    let midnight = |date: chrono::NaiveDate| {
+        Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+            .earliest()
+            .map(|t| t.timestamp())
+            .unwrap_or_default()
+    };
+    let words: Vec<&str> = words
+        .iter()
+        .map(String::as_str)
+        .filter(|w| {
+            !matches!(
+                *w,
+                "what" | "when" | "was" | "did" | "has" | "been" | "it" | "the" | "usage" | "use" | "peak" | "highest"
+                    | "over" | "in" | "during" | "for"
+            )
+        })
+        .collect();
+
+    match words.as_slice() {
+        [] => Some((now.timestamp() - 3600, now.timestamp(), "over the last hour".to_string())),
+        ["today"] => Some((midnight(now.date_naive()), now.timestamp(), "today".to_string())),
+        ["yesterday"] => {
+            let today = midnight(now.date_naive());
+            Some((midnight(now.date_naive() - Duration::days(1)), today, "yesterday".to_string()))
+        }
+        ["last" | "past", rest @ ..] => {
+            let seconds = match rest {
+                [unit] => parse_duration(&["1", unit]),
+                _ => parse_duration(rest),
+            }?;
+            Some((now.timestamp() - seconds, now.timestamp(), format!("over the last {}", rest.join(" "))))
+        }
+        _ => None,
+    }
+}
+
+fn format_percent(value: f64) -> String {
+    format!("{}%", locale::format_decimal(value, 1))
+}
+
+fn format_moment(timestamp: i64) -> String {
+    let moment = Local.timestamp_opt(timestamp, 0).single().unwrap_or_else(Local::now);
+    let today = Local::now().date_naive();
+    if moment.date_naive() == today {
+        locale::format_time(moment.time())
+    } else {
+        format!("{} {}", locale::format_date(moment.date_naive()), locale::format_time(moment.time()))
+    }
+}
+
+fn summarize(metric: &Metric, buckets: &[Bucket], until: i64) -> Option<(Stat, u32)> {
+    let mut summary: Option<(Stat, u32)> = None;
+    for bucket in buckets.iter().filter(|b| b.start < until) {
+        let Some(stat) = bucket.stat(metric) else {
+            continue;
+        };
+        match &mut summary {
+            Some((total, samples)) => {
+                total.merge(&stat);
+                *samples += bucket.samples;
+            }
+            None => summary = Some((stat, bucket.samples)),
+        }
+    }
+    summary
+}
+
+fn parse_request(arg: &str) -> Result<(Metric, i64, i64, String), String> {
+    let lowered = arg.to_lowercase().replace('?', "");
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let Some((metric, rest)) = parse_metric(&words) else {
+        return Err("Error: Say which metric: cpu, memory, swap or disk.".to_string());
+    };
+    let Some((since, until, period)) = parse_period(&rest) else {
+        return Err(format!("Error: I don't understand the period '{}'. Try 'last hour', 'today' or 'yesterday'.", rest.join(" ")));
+    };
+    Ok((metric, since, until, period))
+}
+
+/// `metric_summary cpu over the last hour`: minimum, maximum and average.
+pub fn metric_summary(arg: &str) -> String {
+    let (metric, since, until, period) = match parse_request(arg) {
+        Ok(request) => request,
+        Err(e) => return e,
+    };
+    let buckets = with_history(|history| history.since(since));
+    let Some((stat, samples)) = summarize(&metric, &buckets, until) else {
+        return format!("Error: I have no {} readings {} yet.", metric.label().to_lowercase(), period);
+    };
+
+    let average = stat.total / samples as f64;
+    let message = format!(
+        "{} {}: average {}, minimum {}, maximum {} at {}.",
+        metric.label(),
+        period,
+        format_percent(average),
+        format_percent(stat.min),
+        format_percent(stat.max),
+        format_moment(stat.max_at)
+    );
+    attach_data(json!({
+        "metric": metric.label(),
+        "since": since,
+        "until": until,
+        "average": average,
+        "min": stat.min,
+        "max": stat.max,
+        "max_at": stat.max_at,
+        "samples": samples,
+    }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `metric_peak memory today`: when the metric was highest.
+pub fn metric_peak(arg: &str) -> String {
+    let (metric, since, until, period) = match parse_request(arg) {
+        Ok(request) => request,
+        Err(e) => return e,
+    };
+    let buckets = with_history(|history| history.since(since));
+    let Some((stat, _)) = summarize(&metric, &buckets, until) else {
+        return format!("Error: I have no {} readings {} yet.", metric.label().to_lowercase(), period);
+    };
+
+    let message = format!(
+        "{} peaked {} at {}, reaching {}.",
+        metric.label(),
+        period,
+        format_moment(stat.max_at),
+        format_percent(stat.max)
+    );
+    attach_data(json!({ "metric": metric.label(), "max": stat.max, "max_at": stat.max_at }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// Least-squares slope of (time, value) points, in units per second.
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return None;
+    }
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+fn speak_days(days: f64) -> String {
+    match days {
+        d if d < 1.0 => format!("{} hours", (d * 24.0).round().max(1.0)),
+        d if d < 2.0 => "a day".to_string(),
+        d => format!("{} days", d.round()),
+    }
+}
+
+/// `disk_trend [mount]`: whether a disk is filling up, from the last day of
+/// readings (or longer when asked, e.g. `disk_trend / over the last week`).
+pub fn disk_trend(arg: &str) -> String {
+    let lowered = arg.to_lowercase().replace('?', "");
+    let words: Vec<&str> = lowered
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "is" | "my" | "disk" | "drive" | "on" | "filling" | "up" | "getting" | "full"))
+        .collect();
+    let mount = words.iter().find(|w| w.starts_with('/')).unwrap_or(&"/").to_string();
+    let rest: Vec<String> = words.iter().filter(|w| !w.starts_with('/')).map(|w| w.to_string()).collect();
+
+    let now = Local::now().timestamp();
+    let (since, period) = if rest.is_empty() {
+        (now - 24 * 3600, "over the last day".to_string())
+    } else {
+        match parse_period(&rest) {
+            Some((since, _, period)) => (since, period),
+            None => return format!("Error: I don't understand the period '{}'.", rest.join(" ")),
+        }
+    };
+
+    let buckets = with_history(|history| history.since(since));
+    let points: Vec<(f64, f64)> = buckets
+        .iter()
+        .filter_map(|b| b.disks.get(&mount).map(|used| (b.start as f64, used.total / b.samples as f64)))
+        .collect();
+    let Some(total) = buckets.iter().rev().find_map(|b| b.disk_totals.get(&mount)).copied() else {
+        return format!("Error: I have no readings for disk {}.", mount);
+    };
+    let Some(per_second) = slope(&points) else {
+        return format!("Error: I need more readings of disk {} to see a trend.", mount);
+    };
+
+    let used = points.last().map(|p| p.1).unwrap_or_default();
+    let per_day = per_second * 86400.0;
+    let message = if per_day.abs() < 0.01 * GIB {
+        format!("Disk {} is steady {} at {} used.", mount, period, format_percent(percent(used as u64, total)))
+    } else if per_day > 0.0 {
+        let days_left = (total as f64 - used) / per_day;
+        format!(
+            "Disk {} is filling up by about {} GB per day {}. At this rate it will be full in about {}.",
+            mount,
+            locale::format_decimal(per_day / GIB, 2),
+            period,
+            speak_days(days_left)
+        )
+    } else {
+        format!(
+            "Disk {} is not filling up; usage fell by about {} GB per day {}.",
+            mount,
+            locale::format_decimal(-per_day / GIB, 2),
+            period
+        )
+    };
+    attach_data(json!({
+        "mount_point": mount,
+        "used": used,
+        "total": total,
+        "bytes_per_day": per_day,
+        "samples": points.len(),
+    }));
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+/// `set_metrics_retention 7 days`: how long hourly history is kept.
+pub fn set_metrics_retention(arg: &str) -> String {
+    let lowered = arg.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().filter(|w| *w != "for").collect();
+    let Some(seconds) = parse_duration(&words).filter(|s| *s >= 86400) else {
+        return "Error: Say how long to keep history, at least a day, e.g. 'set_metrics_retention 30 days'.".to_string();
+    };
+    let days = seconds / 86400;
+    let result = with_history(|history| {
+        history.retention_days = Some(days);
+        let now = Local::now().timestamp();
+        let retention = history.retention();
+        prune(&mut history.hours, now - retention);
+        save_history(history)
+    });
+    if let Err(e) = result {
+        return format!("Failed to save metrics history: {}", e);
+    }
+
+    let message = format!("Metrics history will be kept for {} days.", days);
+    if let Err(e) = speak_to_speaker(&message) {
+        return format!("Error: {}", e);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: i64, cpu: f64) -> Bucket {
+        Bucket {
+            start: time,
+            samples: 1,
+            cpu: Stat::new(cpu, time),
+            memory: Stat::new(50.0, time),
+            swap: Stat::new(0.0, time),
+            disks: BTreeMap::from([("/".to_string(), Stat::new(cpu * 10.0, time))]),
+            disk_totals: BTreeMap::from([("/".to_string(), 1000)]),
+        }
+    }
+
+    #[test]
+    fn downsamples_across_minute_and_hour_boundaries() {
+        let hour = 472_222 * 3600;
+        let mut history = History::default();
+        let mut saved = false;
+        for i in 0..=122 {
+            saved |= history.record(sample(hour + 30 * i, i as f64));
+        }
+        assert!(saved);
+
+        assert_eq!(history.minutes.len(), 61);
+        assert!(history.minutes.iter().all(|b| b.samples == 2 && b.start % 60 == 0));
+        assert_eq!(history.hours.len(), 1);
+        let closed = &history.hours[0];
+        assert_eq!((closed.start, closed.samples), (hour, 120));
+        assert_eq!((closed.cpu.min, closed.cpu.max, closed.cpu.max_at), (0.0, 119.0, hour + 119 * 30));
+        assert_eq!(history.open_hour.as_ref().map(|b| (b.start, b.samples)), Some((hour + 3600, 2)));
+        assert_eq!(history.open_minute.as_ref().map(|b| (b.start, b.samples)), Some((hour + 3660, 1)));
+        // Raw samples older than an hour are dropped.
+        assert_eq!(history.raw.front().map(|b| b.start), Some(hour + 60));
+
+        let hours: Vec<Bucket> = history.hours.iter().cloned().collect();
+        let (stat, samples) = summarize(&Metric::Cpu, &hours, i64::MAX).unwrap();
+        assert_eq!(samples, 120);
+        assert_eq!(stat.total / samples as f64, 59.5);
+        let (disk, _) = summarize(&Metric::Disk("/".to_string()), &hours, i64::MAX).unwrap();
+        assert_eq!(disk.max, 119.0);
+        assert!(summarize(&Metric::Disk("/home".to_string()), &hours, i64::MAX).is_none());
+    }
+
+    #[test]
+    fn picks_the_finest_tier_covering_the_window() {
+        let now = Local::now().timestamp();
+        let base = now - now.rem_euclid(60);
+        let mut history = History::default();
+        for k in (0..=180).rev() {
+            history.record(sample(base - 60 * k, 1.0));
+        }
+
+        let raw = history.since(base - 600);
+        assert_eq!(raw.len(), 11);
+        assert!(raw.iter().all(|b| b.samples == 1 && b.start >= base - 600));
+
+        let minutes = history.since(base - 7200);
+        assert_eq!(minutes.len(), 121);
+        assert_eq!(minutes.first().map(|b| b.start), Some(base - 7200));
+
+        let hours = history.since(now - 2 * 86400);
+        assert_eq!(hours.iter().map(|b| b.samples).sum::<u32>(), 181);
+        assert!(hours[..hours.len() - 1].iter().all(|b| b.start % 3600 == 0));
+    }
+
+    #[test]
+    fn slope_follows_the_trend() {
+        assert_eq!(slope(&[(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)]), Some(1.0));
+        assert!(slope(&[(0.0, 9.0), (10.0, 7.0), (20.0, 4.0)]).unwrap() < 0.0);
+        assert_eq!(slope(&[(0.0, 5.0), (60.0, 5.0)]), Some(0.0));
+        assert_eq!(slope(&[(0.0, 5.0)]), None);
+        assert_eq!(slope(&[(3.0, 1.0), (3.0, 2.0)]), None);
+    }
+}
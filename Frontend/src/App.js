@@ -1,4 +1,4 @@
-import React, { useState } from "react";
+import React, { useEffect, useState } from "react";
 import "./Styles/page.css";
 
 const AuroraAssistant = () => {
@@ -13,6 +13,19 @@ const AuroraAssistant = () => {
   const [showMemoryDropdown, setShowMemoryDropdown] = useState(false);
   const [searchTerm, setSearchTerm] = useState("");
   const [showHistory, setShowHistory] = useState(false);
+  const [language, setLanguage] = useState("en");
+
+  // Show notifications pushed by the backend (timers, alerts, progress)
+  useEffect(() => {
+    const events = new EventSource("http://127.0.0.1:8000/events");
+    const showEvent = (event) => {
+      const data = JSON.parse(event.data);
+      setOutput(formatOutput(data.message));
+    };
+    events.addEventListener("timer_expired", showEvent);
+    events.addEventListener("alert", showEvent);
+    return () => events.close();
+  }, []);
 
   // List of commands that require arguments
   const commandsWithArgs = [
@@ -33,7 +46,7 @@ const AuroraAssistant = () => {
   const handleCommand = async (command, argument = "") => {
     try {
       setLoading(true);
-      const payload = argument ? { command: `${command} ${argument}` } : { command };
+      const payload = { command: argument ? `${command} ${argument}` : command, language };
       const response = await fetch("http://127.0.0.1:8000/command", {
         method: "POST",
         headers: { "Content-Type": "application/json" },
@@ -95,7 +108,7 @@ const AuroraAssistant = () => {
 
   const startListening = () => {
     const recognition = new (window.SpeechRecognition || window.webkitSpeechRecognition)();
-    recognition.lang = "en-US";
+    recognition.lang = language === "es" ? "es-ES" : "en-US";
     recognition.start();
 
     recognition.onresult = (event) => {
@@ -103,6 +116,12 @@ const AuroraAssistant = () => {
       
       setSearchTerm(voiceCommand); // Display in search bar
 
+      // The backend matches phrases in other languages against its own grammar
+      if (language !== "en") {
+        handleCommand(voiceCommand);
+        return;
+      }
+
       // List of all backend commands for matching
       const backendCommands = [
         "hello",
@@ -125,6 +144,8 @@ const AuroraAssistant = () => {
         "open_file",
         "create_file",
         "delete_file",
+        "move_file_or_folder",
+        "rename_file_or_folder",
         "create_symlink",
         "print_file_content",
         "search_file_or_folder",
@@ -148,9 +169,112 @@ const AuroraAssistant = () => {
         "hibernate",
         "sleep",
         "help",
+        "start_recording",
+        "stop_recording",
+        "list_macros",
+        "define_macro",
+        "delete_macro",
+        "run_macro",
+        "list_scheduled",
+        "cancel_scheduled",
+        "remind_me",
+        "schedule",
+        "set_timer",
+        "set_alarm",
+        "list_timers",
+        "cancel_timer",
+        "add_event",
+        "list_events",
+        "delete_event",
+        "import_events",
+        "export_events",
+        "day_of",
+        "days_until",
+        "date_of",
+        "time_in",
+        "set_locale",
+        "show_locale",
+        "set_language",
+        "list_languages",
+        "metric_summary",
+        "metric_peak",
+        "disk_trend",
+        "set_metrics_retention",
+        "add_alert",
+        "list_alerts",
+        "delete_alert",
+        "acknowledge_alert",
+        "snooze_alert",
+        "top_processes",
+        "find_process",
+        "process_details",
+        "kill_process",
+        "port_owner",
+        "process_ports",
+        "network_usage",
+        "my_ip_address",
+        "ping",
+        "check_port",
+        "resolve",
+        "trace_route",
+        "set_resolver",
+        "set_network_timeout",
+        "space_usage",
+        "search_content",
+        "find_file",
+        "index_status",
+        "add_index_root",
+        "remove_index_root",
+        "rebuild_index",
+        "copy_file_or_folder",
         "exit",
       ];
 
+      // Natural phrasings that stand in for a backend command
+      const phraseAliases = {
+        "what's on my calendar": "list_events",
+        "whats on my calendar": "list_events",
+        "what time is it in": "time_in",
+        "what day is": "day_of",
+        "what date is": "date_of",
+        "how many days until": "days_until",
+        "what was the": "metric_summary",
+        "when did": "metric_peak",
+        "filling up": "disk_trend",
+        "alert me when": "add_alert",
+        "snooze alerts": "snooze_alert",
+        "acknowledge": "acknowledge_alert",
+        "what's using the most space": "space_usage",
+        "what is using the most space": "space_usage",
+        "what's taking up space": "space_usage",
+        "what is taking up space": "space_usage",
+        "what's using the most": "top_processes",
+        "what is using the most": "top_processes",
+        "terminate process": "kill_process",
+        "what is listening on port": "port_owner",
+        "what's listening on port": "port_owner",
+        "which ports is": "process_ports",
+        "which ports does": "process_ports",
+        "what is my ip": "my_ip_address",
+        "what's my ip": "my_ip_address",
+        "how much bandwidth": "network_usage",
+        "check if port": "check_port",
+        "is port": "check_port",
+        "trace route to": "trace_route",
+        "traceroute to": "trace_route",
+        "look up": "resolve",
+        "files containing": "search_content",
+        "search inside files for": "search_content",
+        "where is my": "find_file",
+        "where's my": "find_file",
+        "find my": "find_file",
+      };
+      const alias = Object.keys(phraseAliases).find((phrase) => voiceCommand.includes(phrase));
+      if (alias) {
+        handleCommand(phraseAliases[alias], voiceCommand.replace(alias, "").trim());
+        return;
+      }
+
       const matchedCommand = backendCommands.find((cmd) =>
         voiceCommand.includes(cmd.replace(/_/g, " "))
       );
@@ -190,7 +314,8 @@ const AuroraAssistant = () => {
           }
         }
       } else {
-        setOutput("Error: Command not recognized.");
+        // Let the backend try to match the phrase against user-defined macros
+        handleCommand(voiceCommand);
       }
     };
 
@@ -205,6 +330,14 @@ const AuroraAssistant = () => {
     <div className="main-container">
       <nav className="navbar">
         <h1 className="navbar-title">Aurora Assistant</h1>
+        <select
+          className="language-select"
+          value={language}
+          onChange={(e) => setLanguage(e.target.value)}
+        >
+          <option value="en">English</option>
+          <option value="es">Español</option>
+        </select>
       </nav>
 
       <div className="content-container">